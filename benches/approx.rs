@@ -4,7 +4,7 @@ extern crate test;
 
 #[cfg(test)]
 mod tests {
-    use rusty::core::{Approx, EPS};
+    use rusty::core::{Approx, FastSqrt, EPS};
     use test::{black_box, Bencher};
 
     #[bench]
@@ -24,7 +24,7 @@ mod tests {
         let y: f32 = 3.1416;
         b.iter(|| {
             for _ in 1..100 {
-                black_box(x - y <= EPS && x - y >= EPS);
+                black_box(x - y <= EPS && x - y >= -EPS);
             }
         });
     }
@@ -166,7 +166,7 @@ mod tests {
 
         b.iter(|| {
             for _ in 1..100 {
-                black_box(Mat4::translation(y).dot(x));
+                black_box(Mat4::translation(y) * x);
             }
         });
     }
@@ -198,7 +198,78 @@ mod tests {
 
         b.iter(|| {
             for _ in 1..100 {
-                black_box(Mat4::x_rotation(1.04719758).dot(x));
+                black_box(Mat4::x_rotation(1.04719758) * x);
+            }
+        });
+    }
+
+    #[bench]
+    fn dot_product(b: &mut Bencher) {
+        let x = Mat4(
+            Vec4(1.0, 1.3, 1.6, 2.0),
+            Vec4(-1.2, 0.6, 0.8, 2.4),
+            Vec4(0.75, 0.36, 1.1, -3.14),
+            Vec4(0.87, 0.32, 1.1, -2.0),
+        );
+        let y = Mat4(
+            Vec4(2.0, 4.0, 8.0, 16.0),
+            Vec4(16.0, 2.0, 4.0, 8.0),
+            Vec4(8.0, 16.0, 2.0, 4.0),
+            Vec4(4.0, 8.0, 16.0, 2.0),
+        );
+
+        b.iter(|| {
+            for _ in 1..100 {
+                black_box(black_box(x) * black_box(y));
+            }
+        });
+    }
+
+    #[bench]
+    fn transform(b: &mut Bencher) {
+        let x = Mat4(
+            Vec4(1.0, 1.3, 1.6, 2.0),
+            Vec4(-1.2, 0.6, 0.8, 2.4),
+            Vec4(0.75, 0.36, 1.1, -3.14),
+            Vec4(0.87, 0.32, 1.1, -2.0),
+        );
+        let y = Vec4(2.3, 3.6, 1.8, 1.0);
+
+        b.iter(|| {
+            for _ in 1..100 {
+                black_box(black_box(x) * black_box(y));
+            }
+        });
+    }
+
+    #[bench]
+    fn det(b: &mut Bencher) {
+        let x = Mat4(
+            Vec4(1.0, 1.3, 1.6, 2.0),
+            Vec4(-1.2, 0.6, 0.8, 2.4),
+            Vec4(0.75, 0.36, 1.1, -3.14),
+            Vec4(0.87, 0.32, 1.1, -2.0),
+        );
+
+        b.iter(|| {
+            for _ in 1..100 {
+                black_box(black_box(x).det());
+            }
+        });
+    }
+
+    #[bench]
+    fn inverse(b: &mut Bencher) {
+        let x = Mat4(
+            Vec4(1.0, 1.3, 1.6, 2.0),
+            Vec4(-1.2, 0.6, 0.8, 2.4),
+            Vec4(0.75, 0.36, 1.1, -3.14),
+            Vec4(0.87, 0.32, 1.1, -2.0),
+        );
+
+        b.iter(|| {
+            for _ in 1..100 {
+                black_box(black_box(x).inverse());
             }
         });
     }
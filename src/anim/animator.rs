@@ -0,0 +1,158 @@
+use crate::anim::{Clip, Graph, Skeleton};
+use crate::core::{Mat4, Transform};
+
+/// Animator plays clips on a skeleton and keeps the resulting joint palette,
+/// which is laid out contiguously so it can be uploaded to a shader as is.
+/// The layers of the graph are blended on top of the clip being played.
+pub struct Animator {
+    pub skeleton: Skeleton,
+    pub clips: Vec<Clip>,
+    pub graph: Graph,
+    clip: Option<usize>,
+    time: f32,
+    rest: Vec<Transform>,
+    pose: Vec<Transform>,
+    palette: Vec<Mat4>,
+}
+
+impl Animator {
+    /// Return an animator of the skeleton in the rest pose.
+    pub fn new(skeleton: Skeleton, clips: Vec<Clip>) -> Self {
+        let pose = skeleton.rest_pose();
+        let mut palette = Vec::new();
+        skeleton.palette(&pose, &mut palette);
+        Self {
+            skeleton,
+            clips,
+            graph: Graph::default(),
+            clip: None,
+            time: 0.0,
+            rest: pose.clone(),
+            pose,
+            palette,
+        }
+    }
+
+    /// Return an index of the clip with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|c| c.name == name)
+    }
+
+    /// Start playing a clip from the beginning. Return false and keep the
+    /// current clip if there is no clip with the index.
+    pub fn play(&mut self, clip: usize) -> bool {
+        if clip >= self.clips.len() {
+            return false;
+        }
+        self.clip = Some(clip);
+        self.time = 0.0;
+        self.advance(0.0);
+        true
+    }
+
+    /// Stop playing and return to the rest pose.
+    pub fn stop(&mut self) {
+        self.clip = None;
+        self.time = 0.0;
+        self.advance(0.0);
+    }
+
+    /// Return an index of the clip being played.
+    #[inline]
+    pub fn clip(&self) -> Option<usize> {
+        self.clip
+    }
+
+    /// Return the playback time in seconds from the beginning of the clip.
+    #[inline]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Move the playback and the graph by the given number of seconds,
+    /// looping the clip, and update the pose and the palette.
+    pub fn advance(&mut self, dt: f32) {
+        // The rest pose is refreshed in place, as the joints are public
+        self.rest.clear();
+        self.rest
+            .extend(self.skeleton.joints.iter().map(|j| j.rest));
+        for (t, &rest) in self.pose.iter_mut().zip(&self.rest) {
+            *t = rest;
+        }
+        if let Some(clip) = self.clip.and_then(|i| self.clips.get(i)) {
+            self.time = if clip.duration > 0.0 {
+                (self.time + dt).rem_euclid(clip.duration)
+            } else {
+                0.0
+            };
+            clip.apply(self.time, &mut self.pose);
+        }
+        self.graph
+            .advance(&self.clips, &self.rest, dt, &mut self.pose);
+        self.skeleton.palette(&self.pose, &mut self.palette);
+    }
+
+    /// Return local transformations of the joints.
+    #[inline]
+    pub fn pose(&self) -> &[Transform] {
+        &self.pose
+    }
+
+    /// Return skinning matrices of the joints, in the skeleton order.
+    #[inline]
+    pub fn palette(&self) -> &[Mat4] {
+        &self.palette
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anim::{Animator, Channel, Clip, Interpolation, Joint, Path, Skeleton};
+    use crate::core::{Mat4, Transform, Vec3, Vec4};
+
+    fn animator() -> Animator {
+        let skeleton = Skeleton::new(
+            vec![Joint {
+                name: String::from("root"),
+                parent: None,
+                rest: Transform::identity(),
+                inverse_bind: Mat4::identity(),
+            }],
+            Mat4::identity(),
+        )
+        .unwrap();
+        let clip = Clip::new(
+            String::from("slide"),
+            vec![Channel {
+                joint: 0,
+                path: Path::Translation,
+                interpolation: Interpolation::Linear,
+                times: vec![0.0, 2.0],
+                values: vec![Vec4(0.0, 0.0, 0.0, 0.0), Vec4(4.0, 0.0, 0.0, 0.0)],
+            }],
+        );
+        Animator::new(skeleton, vec![clip])
+    }
+
+    #[test]
+    fn play() {
+        let mut a = animator();
+        assert_eq!(None, a.clip());
+        assert!(!a.play(1));
+        assert_eq!(Some(0), a.find("slide"));
+        assert!(a.play(0));
+        assert_eq!(Some(0), a.clip());
+
+        a.advance(0.5);
+        assert_eq!(Vec3(1.0, 0.0, 0.0), a.pose()[0].translation);
+        assert_eq!(Mat4::translation(Vec3(1.0, 0.0, 0.0)), a.palette()[0]);
+
+        // The clip loops around its duration
+        a.advance(2.0);
+        assert_eq!(0.5, a.time());
+        assert_eq!(Vec3(1.0, 0.0, 0.0), a.pose()[0].translation);
+
+        a.stop();
+        assert_eq!(Mat4::identity(), a.palette()[0]);
+    }
+}
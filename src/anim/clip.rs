@@ -0,0 +1,246 @@
+use crate::core::{Quat, Transform, Vec3, Vec4};
+
+/// Property of a joint animated by a channel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Path {
+    Translation,
+    Rotation,
+    Scale,
+}
+
+/// Interpolation between the keyframes of a channel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Interpolation {
+    /// Keep the value of the previous keyframe.
+    Step,
+    /// Interpolate linearly, and spherically for rotations.
+    Linear,
+    /// Interpolate with a cubic Hermite spline, in which case each keyframe
+    /// has three values: the in-tangent, the value and the out-tangent.
+    CubicSpline,
+}
+
+/// Channel is a sequence of keyframes of a single joint property. The times
+/// are in seconds and ascending. Values are stored as 4 dimensional vectors
+/// regardless of the path, with the last component unused by translations
+/// and scales.
+pub struct Channel {
+    pub joint: usize,
+    pub path: Path,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: Vec<Vec4>,
+}
+
+/// Clip is a named animation, such as a walk cycle, made of channels.
+pub struct Clip {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl Channel {
+    /// Return the value at the given time, clamped to the first and the last
+    /// keyframes, or `None` if the channel has no keyframes.
+    pub fn sample(&self, time: f32) -> Option<Vec4> {
+        let stride = match self.interpolation {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        };
+        // Value of a keyframe, skipping the in-tangent of splines
+        let value = |i: usize| self.values.get(i * stride + stride / 2).copied();
+
+        let last = self.times.len().checked_sub(1)?;
+        let next = self.times.partition_point(|&t| t <= time);
+        if next == 0 {
+            return value(0);
+        }
+        if next > last {
+            return value(last);
+        }
+        let prev = next - 1;
+        let dt = self.times[next] - self.times[prev];
+        let t = (time - self.times[prev]) / dt;
+
+        match self.interpolation {
+            Interpolation::Step => value(prev),
+            Interpolation::Linear => {
+                let (a, b) = (value(prev)?, value(next)?);
+                Some(match self.path {
+                    Path::Rotation => Quat::from(a).slerp(Quat::from(b), t).into(),
+                    _ => a + (b - a) * t,
+                })
+            }
+            Interpolation::CubicSpline => {
+                let out_tangent = *self.values.get(prev * 3 + 2)?;
+                let in_tangent = *self.values.get(next * 3)?;
+                let (a, b) = (value(prev)?, value(next)?);
+                let (t2, t3) = (t * t, t * t * t);
+                let v = a * (2.0 * t3 - 3.0 * t2 + 1.0)
+                    + out_tangent * ((t3 - 2.0 * t2 + t) * dt)
+                    + b * (-2.0 * t3 + 3.0 * t2)
+                    + in_tangent * ((t3 - t2) * dt);
+                Some(match self.path {
+                    Path::Rotation => v.normalize_precise(),
+                    _ => v,
+                })
+            }
+        }
+    }
+
+    /// Set the animated property of the pose to the value at the given time.
+    pub fn apply(&self, time: f32, pose: &mut [Transform]) {
+        let (Some(v), Some(joint)) = (self.sample(time), pose.get_mut(self.joint)) else {
+            return;
+        };
+        match self.path {
+            Path::Translation => joint.translation = Vec3(v.0, v.1, v.2),
+            Path::Rotation => joint.rotation = Quat::from(v),
+            Path::Scale => joint.scale = Vec3(v.0, v.1, v.2),
+        }
+    }
+}
+
+impl Clip {
+    /// Return a clip lasting until the last keyframe of its channels.
+    pub fn new(name: String, channels: Vec<Channel>) -> Self {
+        let duration = channels
+            .iter()
+            .filter_map(|c| c.times.last())
+            .fold(0.0, |a: f32, &b| a.max(b));
+        Self {
+            name,
+            duration,
+            channels,
+        }
+    }
+
+    /// Set the animated properties of the pose to the values at the given
+    /// time. Properties which are not animated are left intact.
+    pub fn apply(&self, time: f32, pose: &mut [Transform]) {
+        for channel in &self.channels {
+            channel.apply(time, pose);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anim::{Channel, Clip, Interpolation, Path};
+    use crate::core::{Quat, Transform, Vec3, Vec4};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn channel(
+        path: Path,
+        interpolation: Interpolation,
+        times: &[f32],
+        values: &[Vec4],
+    ) -> Channel {
+        Channel {
+            joint: 0,
+            path,
+            interpolation,
+            times: times.to_vec(),
+            values: values.to_vec(),
+        }
+    }
+
+    #[test]
+    fn step() {
+        let c = channel(
+            Path::Translation,
+            Interpolation::Step,
+            &[1.0, 2.0],
+            &[Vec4(1.0, 2.0, 3.0, 0.0), Vec4(4.0, 5.0, 6.0, 0.0)],
+        );
+        assert_eq!(Some(Vec4(1.0, 2.0, 3.0, 0.0)), c.sample(0.0));
+        assert_eq!(Some(Vec4(1.0, 2.0, 3.0, 0.0)), c.sample(1.9));
+        assert_eq!(Some(Vec4(4.0, 5.0, 6.0, 0.0)), c.sample(2.0));
+        assert_eq!(Some(Vec4(4.0, 5.0, 6.0, 0.0)), c.sample(3.0));
+        assert_eq!(
+            None,
+            channel(Path::Scale, Interpolation::Step, &[], &[]).sample(0.0)
+        );
+    }
+
+    #[test]
+    fn linear() {
+        let c = channel(
+            Path::Translation,
+            Interpolation::Linear,
+            &[0.0, 1.0, 3.0],
+            &[
+                Vec4(0.0, 0.0, 0.0, 0.0),
+                Vec4(2.0, 4.0, 6.0, 0.0),
+                Vec4(0.0, 0.0, 0.0, 0.0),
+            ],
+        );
+        assert_eq!(Some(Vec4(1.0, 2.0, 3.0, 0.0)), c.sample(0.5));
+        assert_eq!(Some(Vec4(1.0, 2.0, 3.0, 0.0)), c.sample(2.0));
+
+        // Rotations are interpolated along the arc
+        let q = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2);
+        let c = channel(
+            Path::Rotation,
+            Interpolation::Linear,
+            &[0.0, 1.0],
+            &[Quat::identity().into(), q.into()],
+        );
+        let half = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2 / 2.0);
+        assert_eq!(Some(Vec4::from(half)), c.sample(0.5));
+    }
+
+    #[test]
+    fn cubic_spline() {
+        // Zero tangents give a smooth step, and the values themselves are hit
+        let zero = Vec4(0.0, 0.0, 0.0, 0.0);
+        let c = channel(
+            Path::Scale,
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[zero, zero, zero, zero, Vec4(4.0, 4.0, 4.0, 0.0), zero],
+        );
+        assert_eq!(Some(zero), c.sample(0.0));
+        assert_eq!(Some(Vec4(2.0, 2.0, 2.0, 0.0)), c.sample(1.0));
+        assert_eq!(Some(Vec4(0.625, 0.625, 0.625, 0.0)), c.sample(0.5));
+        assert_eq!(Some(Vec4(4.0, 4.0, 4.0, 0.0)), c.sample(2.0));
+
+        // Tangents are per second, so they are scaled by the keyframe delta
+        let one = Vec4(1.0, 1.0, 1.0, 0.0);
+        let c = channel(
+            Path::Translation,
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[one, zero, one, one, one * 2.0_f32, one],
+        );
+        assert_eq!(Some(one), c.sample(1.0));
+    }
+
+    #[test]
+    fn clip() {
+        let clip = Clip::new(
+            String::from("slide"),
+            vec![
+                channel(
+                    Path::Translation,
+                    Interpolation::Linear,
+                    &[0.0, 2.0],
+                    &[Vec4(0.0, 0.0, 0.0, 0.0), Vec4(2.0, 0.0, 0.0, 0.0)],
+                ),
+                channel(
+                    Path::Scale,
+                    Interpolation::Step,
+                    &[0.5],
+                    &[Vec4(2.0, 2.0, 2.0, 0.0)],
+                ),
+            ],
+        );
+        assert_eq!(2.0, clip.duration);
+
+        let mut pose = [Transform::identity()];
+        clip.apply(1.0, &mut pose);
+        assert_eq!(Vec3(1.0, 0.0, 0.0), pose[0].translation);
+        assert_eq!(Quat::identity(), pose[0].rotation);
+        assert_eq!(Vec3(2.0, 2.0, 2.0), pose[0].scale);
+    }
+}
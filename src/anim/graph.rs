@@ -0,0 +1,567 @@
+use crate::anim::{Clip, Skeleton};
+use crate::core::Transform;
+
+/// Maximum number of graph parameters, which bounds the memory a parameter
+/// index set by the host can take.
+pub const MAX_PARAMETERS: usize = 256;
+
+/// Mask is a weight per joint, which limits a layer to a part of the
+/// skeleton, such as the upper body. Joints outside of the mask have zero
+/// weight.
+#[derive(Clone)]
+pub struct Mask(pub Vec<f32>);
+
+/// Motion is what a state plays: either a single clip, or a blend of clips
+/// picked by a parameter, with each clip placed at a parameter value.
+pub enum Motion {
+    Clip(usize),
+    /// Clips sorted by their parameter values, which are blended pairwise.
+    /// Clips are synchronized by their phase, so cycles of a different
+    /// length, such as a walk and a run, keep their feet in step.
+    Blend {
+        parameter: usize,
+        clips: Vec<(f32, usize)>,
+    },
+}
+
+/// State of a layer state machine.
+pub struct State {
+    pub name: String,
+    pub motion: Motion,
+    pub speed: f32,
+}
+
+/// Transition is a crossfade to another state that starts once a parameter
+/// is in the `[min, max)` range. Transitions without the source state can be
+/// taken from any state.
+pub struct Transition {
+    pub from: Option<usize>,
+    pub to: usize,
+    pub parameter: usize,
+    pub min: f32,
+    pub max: f32,
+    pub duration: f32,
+}
+
+/// How a layer is combined with the layers below it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    /// Blend towards the pose of the layer by its weight.
+    Override,
+    /// Add the difference between the pose of the layer and the first frame
+    /// of its motion, scaled by the weight.
+    Additive,
+}
+
+/// Layer is a state machine playing one state at a time, crossfading to
+/// another one when a transition is taken.
+pub struct Layer {
+    pub states: Vec<State>,
+    pub transitions: Vec<Transition>,
+    pub mode: BlendMode,
+    pub weight: f32,
+    pub mask: Option<Mask>,
+    state: usize,
+    phase: f32,
+    fade: Option<Fade>,
+}
+
+/// State being faded out, and the progress of the crossfade in seconds.
+struct Fade {
+    state: usize,
+    phase: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Graph is a stack of layers, evaluated from the bottom, and parameters
+/// driving them.
+#[derive(Default)]
+pub struct Graph {
+    pub parameters: Vec<f32>,
+    pub layers: Vec<Layer>,
+    poses: Poses,
+}
+
+/// Poses the layers are sampled into, which are kept between the steps so
+/// that sampling doesn't allocate.
+#[derive(Default)]
+struct Poses {
+    layer: Vec<Transform>,
+    fade: Vec<Transform>,
+    blend: Vec<Transform>,
+    reference: Vec<Transform>,
+}
+
+impl Mask {
+    /// Return a mask of a joint and all of its descendants.
+    pub fn new(skeleton: &Skeleton, joint: usize) -> Self {
+        let joints = &skeleton.joints;
+        Self(
+            (0..joints.len())
+                .map(|mut i| {
+                    // Parents don't form cycles, so the depth is bounded
+                    for _ in 0..joints.len() {
+                        if i == joint {
+                            return 1.0;
+                        }
+                        match joints[i].parent {
+                            Some(parent) => i = parent,
+                            None => break,
+                        }
+                    }
+                    0.0
+                })
+                .collect(),
+        )
+    }
+
+    /// Return the weight of a joint.
+    #[inline]
+    pub fn weight(&self, joint: usize) -> f32 {
+        self.0.get(joint).copied().unwrap_or(0.0)
+    }
+}
+
+impl Motion {
+    /// Return the duration of a motion cycle in seconds.
+    fn duration(&self, clips: &[Clip], parameters: &[f32]) -> f32 {
+        match self {
+            Motion::Clip(clip) => clips.get(*clip).map_or(0.0, |c| c.duration),
+            Motion::Blend { .. } => {
+                let (a, b, t) = self.weights(parameters);
+                let duration = |i| clips.get(i).map_or(0.0, |c: &Clip| c.duration);
+                duration(a) + (duration(b) - duration(a)) * t
+            }
+        }
+    }
+
+    /// Return the pair of blended clips and the weight of the second one.
+    fn weights(&self, parameters: &[f32]) -> (usize, usize, f32) {
+        match self {
+            Motion::Clip(clip) => (*clip, *clip, 0.0),
+            Motion::Blend { parameter, clips } => {
+                let Some((first, last)) = clips.first().zip(clips.last()) else {
+                    return (usize::MAX, usize::MAX, 0.0);
+                };
+                let p = parameters.get(*parameter).copied().unwrap_or(0.0);
+                if p <= first.0 {
+                    return (first.1, first.1, 0.0);
+                }
+                let i = clips.partition_point(|&(v, _)| v <= p);
+                if i == clips.len() {
+                    return (last.1, last.1, 0.0);
+                }
+                let (a, b) = (clips[i - 1], clips[i]);
+                (a.1, b.1, (p - a.0) / (b.0 - a.0))
+            }
+        }
+    }
+
+    /// Write the pose of the motion at the phase, which goes from 0 to 1
+    /// over a cycle. Joints which are not animated are in the rest pose. The
+    /// second clip of a blend is sampled into the other pose.
+    fn sample(
+        &self,
+        clips: &[Clip],
+        parameters: &[f32],
+        rest: &[Transform],
+        phase: f32,
+        pose: &mut Vec<Transform>,
+        blend: &mut Vec<Transform>,
+    ) {
+        let (a, b, t) = self.weights(parameters);
+        let sample = |i: usize, pose: &mut Vec<Transform>| {
+            pose.clear();
+            pose.extend_from_slice(rest);
+            if let Some(clip) = clips.get(i) {
+                clip.apply(phase * clip.duration, pose);
+            }
+        };
+
+        sample(a, pose);
+        if a != b {
+            sample(b, blend);
+            for (p, q) in pose.iter_mut().zip(blend.iter()) {
+                *p = p.lerp(*q, t);
+            }
+        }
+    }
+}
+
+impl Layer {
+    /// Return a layer starting at its first state.
+    pub fn new(states: Vec<State>, mode: BlendMode) -> Self {
+        Self {
+            states,
+            transitions: Vec::new(),
+            mode,
+            weight: 1.0,
+            mask: None,
+            state: 0,
+            phase: 0.0,
+            fade: None,
+        }
+    }
+
+    /// Return a layer with a state per clip, each playing the clip alone.
+    pub fn from_clips(clips: &[Clip], mode: BlendMode) -> Self {
+        Self::new(
+            clips
+                .iter()
+                .enumerate()
+                .map(|(i, c)| State {
+                    name: c.name.clone(),
+                    motion: Motion::Clip(i),
+                    speed: 1.0,
+                })
+                .collect(),
+            mode,
+        )
+    }
+
+    /// Return an index of the current state.
+    #[inline]
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    /// Return the progress of the current state cycle, from 0 to 1.
+    #[inline]
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Return whether the layer is crossfading between two states.
+    #[inline]
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Start crossfading to a state over the given number of seconds, or
+    /// switch to it at once if the duration is not positive. Return false
+    /// if there is no such state.
+    pub fn crossfade(&mut self, state: usize, duration: f32) -> bool {
+        if state >= self.states.len() {
+            return false;
+        }
+        if state == self.state {
+            return true;
+        }
+        self.fade = (duration > 0.0).then_some(Fade {
+            state: self.state,
+            phase: self.phase,
+            elapsed: 0.0,
+            duration,
+        });
+        self.state = state;
+        self.phase = 0.0;
+        true
+    }
+
+    /// Take the first transition whose condition holds, then move the
+    /// playback and the crossfade by the given number of seconds.
+    fn advance(&mut self, clips: &[Clip], parameters: &[f32], dt: f32) {
+        let transition = self.transitions.iter().find(|t| {
+            t.from.is_none_or(|from| from == self.state)
+                && t.to != self.state
+                && parameters
+                    .get(t.parameter)
+                    .is_some_and(|&p| p >= t.min && p < t.max)
+        });
+        if let Some(&Transition { to, duration, .. }) = transition {
+            self.crossfade(to, duration);
+        }
+
+        let advance = |state: usize, phase: f32| {
+            let state = &self.states[state];
+            let duration = state.motion.duration(clips, parameters);
+            if duration > 0.0 {
+                (phase + dt * state.speed / duration).rem_euclid(1.0)
+            } else {
+                0.0
+            }
+        };
+        self.phase = advance(self.state, self.phase);
+        if let Some(mut fade) = self.fade.take() {
+            fade.phase = advance(fade.state, fade.phase);
+            fade.elapsed += dt;
+            if fade.elapsed < fade.duration {
+                self.fade = Some(fade);
+            }
+        }
+    }
+
+    /// Write the pose of the layer into `poses.layer`, which is a difference
+    /// from the first frame of its motions for additive layers.
+    fn sample(&self, clips: &[Clip], parameters: &[f32], rest: &[Transform], poses: &mut Poses) {
+        let Poses {
+            layer,
+            fade,
+            blend,
+            reference,
+        } = poses;
+        let mut sample = |state: usize, phase: f32, pose: &mut Vec<Transform>| {
+            let motion = &self.states[state].motion;
+            motion.sample(clips, parameters, rest, phase, pose, blend);
+            if self.mode == BlendMode::Additive {
+                motion.sample(clips, parameters, rest, 0.0, reference, blend);
+                for (p, r) in pose.iter_mut().zip(reference.iter()) {
+                    *p = p.delta(*r);
+                }
+            }
+        };
+
+        sample(self.state, self.phase, layer);
+        if let Some(f) = &self.fade {
+            sample(f.state, f.phase, fade);
+            let t = f.elapsed / f.duration;
+            for (p, q) in layer.iter_mut().zip(fade.iter()) {
+                *p = q.lerp(*p, t);
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Set a parameter, adding the missing ones with zero values. Return
+    /// false if the index is not below `MAX_PARAMETERS`.
+    pub fn set_parameter(&mut self, index: usize, value: f32) -> bool {
+        if index >= MAX_PARAMETERS {
+            return false;
+        }
+        if index >= self.parameters.len() {
+            self.parameters.resize(index + 1, 0.0);
+        }
+        self.parameters[index] = value;
+        true
+    }
+
+    /// Move the layers by the given number of seconds and blend their poses
+    /// on top of the given one.
+    pub fn advance(&mut self, clips: &[Clip], rest: &[Transform], dt: f32, pose: &mut [Transform]) {
+        for layer in &mut self.layers {
+            if layer.state >= layer.states.len() {
+                continue;
+            }
+            layer.advance(clips, &self.parameters, dt);
+            if layer.weight <= 0.0 {
+                continue;
+            }
+
+            layer.sample(clips, &self.parameters, rest, &mut self.poses);
+            for (i, (p, &s)) in pose.iter_mut().zip(&self.poses.layer).enumerate() {
+                let weight = layer.weight * layer.mask.as_ref().map_or(1.0, |m| m.weight(i));
+                *p = match layer.mode {
+                    BlendMode::Override => p.lerp(s, weight),
+                    BlendMode::Additive => p.add_weighted(s, weight),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anim::{
+        BlendMode, Channel, Clip, Graph, Interpolation, Joint, Layer, Mask, Motion, Path, Skeleton,
+        State, Transition, MAX_PARAMETERS,
+    };
+    use crate::core::{Mat4, Quat, Transform, Vec3, Vec4};
+    use std::f32::consts::FRAC_PI_2;
+
+    /// Clip moving a joint linearly from the origin to the target.
+    fn slide(joint: usize, duration: f32, target: Vec3) -> Clip {
+        Clip::new(
+            String::new(),
+            vec![Channel {
+                joint,
+                path: Path::Translation,
+                interpolation: Interpolation::Linear,
+                times: vec![0.0, duration],
+                values: vec![
+                    Vec4(0.0, 0.0, 0.0, 0.0),
+                    Vec4(target.0, target.1, target.2, 0.0),
+                ],
+            }],
+        )
+    }
+
+    fn state(motion: Motion) -> State {
+        State {
+            name: String::new(),
+            motion,
+            speed: 1.0,
+        }
+    }
+
+    #[test]
+    fn mask() {
+        let joint = |parent| Joint {
+            name: String::new(),
+            parent,
+            rest: Transform::identity(),
+            inverse_bind: Mat4::identity(),
+        };
+        let skeleton = Skeleton::new(
+            vec![joint(None), joint(Some(0)), joint(Some(1)), joint(Some(0))],
+            Mat4::identity(),
+        )
+        .unwrap();
+        assert_eq!(vec![0.0, 1.0, 1.0, 0.0], Mask::new(&skeleton, 1).0);
+        assert_eq!(vec![1.0; 4], Mask::new(&skeleton, 0).0);
+        assert_eq!(0.0, Mask::new(&skeleton, 1).weight(4));
+    }
+
+    #[test]
+    fn crossfade() {
+        let clips = [
+            slide(0, 1.0, Vec3(0.0, 0.0, 0.0)),
+            slide(0, 1.0, Vec3(0.0, 0.0, 8.0)),
+        ];
+        let rest = [Transform::identity()];
+        let mut graph = Graph::default();
+        graph
+            .layers
+            .push(Layer::from_clips(&clips, BlendMode::Override));
+
+        let layer = &mut graph.layers[0];
+        assert!(!layer.crossfade(2, 1.0));
+        assert!(layer.crossfade(1, 0.5));
+        assert!(layer.is_fading());
+
+        // Halfway through the crossfade to a clip at a quarter of its cycle
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 0.25, &mut pose);
+        assert_eq!(Vec3(0.0, 0.0, 1.0), pose[0].translation);
+
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 0.25, &mut pose);
+        assert!(!graph.layers[0].is_fading());
+        assert_eq!(Vec3(0.0, 0.0, 4.0), pose[0].translation);
+    }
+
+    #[test]
+    fn transitions() {
+        let clips = [slide(0, 1.0, Vec3(0.0, 0.0, 0.0))];
+        let rest = [Transform::identity()];
+        let mut graph = Graph::default();
+        let mut layer = Layer::new(
+            vec![state(Motion::Clip(0)), state(Motion::Clip(0))],
+            BlendMode::Override,
+        );
+        layer.transitions.push(Transition {
+            from: Some(0),
+            to: 1,
+            parameter: 1,
+            min: 0.5,
+            max: f32::INFINITY,
+            duration: 0.2,
+        });
+        layer.transitions.push(Transition {
+            from: None,
+            to: 0,
+            parameter: 1,
+            min: f32::NEG_INFINITY,
+            max: 0.5,
+            duration: 0.0,
+        });
+        graph.layers.push(layer);
+
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 0.1, &mut pose);
+        assert_eq!(0, graph.layers[0].state());
+
+        assert!(graph.set_parameter(1, 1.0));
+        assert_eq!(vec![0.0, 1.0], graph.parameters);
+        assert!(!graph.set_parameter(MAX_PARAMETERS, 1.0));
+        assert_eq!(2, graph.parameters.len());
+        graph.advance(&clips, &rest, 0.1, &mut pose);
+        assert_eq!(1, graph.layers[0].state());
+        assert!(graph.layers[0].is_fading());
+
+        graph.set_parameter(1, 0.0);
+        graph.advance(&clips, &rest, 0.1, &mut pose);
+        assert_eq!(0, graph.layers[0].state());
+        assert!(!graph.layers[0].is_fading());
+    }
+
+    #[test]
+    fn blend() {
+        // A walk and a run of different lengths stay in phase
+        let clips = [
+            slide(0, 1.0, Vec3(2.0, 0.0, 0.0)),
+            slide(0, 3.0, Vec3(6.0, 0.0, 0.0)),
+        ];
+        let rest = [Transform::identity()];
+        let mut graph = Graph::default();
+        graph.layers.push(Layer::new(
+            vec![state(Motion::Blend {
+                parameter: 0,
+                clips: vec![(1.0, 0), (3.0, 1)],
+            })],
+            BlendMode::Override,
+        ));
+        graph.set_parameter(0, 2.0);
+
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 1.0, &mut pose);
+        assert_eq!(0.5, graph.layers[0].phase());
+        assert_eq!(Vec3(2.0, 0.0, 0.0), pose[0].translation);
+
+        graph.set_parameter(0, 5.0);
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 0.0, &mut pose);
+        assert_eq!(Vec3(3.0, 0.0, 0.0), pose[0].translation);
+    }
+
+    #[test]
+    fn layers() {
+        let mut both = slide(1, 1.0, Vec3(4.0, 0.0, 0.0));
+        both.channels
+            .extend(slide(0, 1.0, Vec3(100.0, 0.0, 0.0)).channels);
+        let quarter = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2 / 2.0);
+        let half = quarter * quarter;
+        let clips = [
+            slide(0, 1.0, Vec3(4.0, 0.0, 0.0)),
+            both,
+            Clip::new(
+                String::new(),
+                vec![Channel {
+                    joint: 1,
+                    path: Path::Rotation,
+                    interpolation: Interpolation::Step,
+                    times: vec![0.0, 0.5, 1.0],
+                    values: vec![Quat::identity().into(), half.into(), half.into()],
+                }],
+            ),
+        ];
+        let turned = Quat::from_axis_angle(Vec3(1.0, 0.0, 0.0), FRAC_PI_2);
+        let rest = [
+            Transform::identity(),
+            Transform::new(Vec3(0.0, 0.0, 1.0), turned, Vec3(1.0, 1.0, 1.0)),
+        ];
+
+        let mut graph = Graph::default();
+        graph.layers.push(Layer::new(
+            vec![state(Motion::Clip(0))],
+            BlendMode::Override,
+        ));
+        let mut masked = Layer::new(vec![state(Motion::Clip(1))], BlendMode::Override);
+        masked.mask = Some(Mask(vec![0.0, 1.0]));
+        graph.layers.push(masked);
+        let mut additive = Layer::new(vec![state(Motion::Clip(2))], BlendMode::Additive);
+        additive.weight = 0.5;
+        graph.layers.push(additive);
+
+        // The masked layer moves only the second joint
+        let mut pose = rest;
+        graph.advance(&clips, &rest, 0.5, &mut pose);
+        assert_eq!(Vec3(2.0, 0.0, 0.0), pose[0].translation);
+        assert_eq!(Vec3(2.0, 0.0, 0.0), pose[1].translation);
+
+        // Half of the difference from the first frame is added on top
+        assert_eq!(Quat::identity(), pose[0].rotation);
+        assert_eq!(turned * quarter, pose[1].rotation);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod animator;
+pub mod clip;
+pub mod graph;
+pub mod skeleton;
+
+pub use animator::Animator;
+pub use clip::{Channel, Clip, Interpolation, Path};
+pub use graph::{BlendMode, Graph, Layer, Mask, Motion, State, Transition, MAX_PARAMETERS};
+pub use skeleton::{Joint, Skeleton};
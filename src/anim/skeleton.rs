@@ -0,0 +1,148 @@
+use crate::core::{Mat4, Transform};
+
+/// Joint is a bone of a skeleton with its rest pose relative to the parent
+/// joint, and a matrix that brings the mesh vertices into its local space.
+pub struct Joint {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub rest: Transform,
+    pub inverse_bind: Mat4,
+}
+
+/// Skeleton is a hierarchy of joints a mesh is skinned to. The root matrix
+/// is the transformation of the nodes above the root joints, which are not
+/// part of the skeleton, but still affect it.
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    pub root: Mat4,
+    order: Vec<usize>,
+}
+
+impl Skeleton {
+    /// Return a skeleton of the joints, or `None` if their parents are out
+    /// of bounds or form a cycle.
+    pub fn new(joints: Vec<Joint>, root: Mat4) -> Option<Self> {
+        if joints
+            .iter()
+            .any(|j| j.parent.is_some_and(|p| p >= joints.len()))
+        {
+            return None;
+        }
+
+        // Order the joints so that every parent goes before its children,
+        // which lets the palette be computed in a single pass.
+        let mut order = Vec::with_capacity(joints.len());
+        let mut visited = vec![false; joints.len()];
+        order.extend((0..joints.len()).filter(|&i| joints[i].parent.is_none()));
+        order.iter().for_each(|&i| visited[i] = true);
+        let mut i = 0;
+        while i < order.len() {
+            let parent = order[i];
+            for (child, joint) in joints.iter().enumerate() {
+                if joint.parent == Some(parent) && !visited[child] {
+                    visited[child] = true;
+                    order.push(child);
+                }
+            }
+            i += 1;
+        }
+        if order.len() != joints.len() {
+            return None;
+        }
+
+        Some(Self {
+            joints,
+            root,
+            order,
+        })
+    }
+
+    /// Return local transformations of the joints in the rest pose.
+    pub fn rest_pose(&self) -> Vec<Transform> {
+        self.joints.iter().map(|j| j.rest).collect()
+    }
+
+    /// Compute skinning matrices of the pose, one per joint, which move the
+    /// mesh vertices from the bind pose to the posed one. Joints missing in
+    /// the pose are in the rest one.
+    pub fn palette(&self, pose: &[Transform], out: &mut Vec<Mat4>) {
+        out.clear();
+        out.resize(self.joints.len(), Mat4::identity());
+
+        for &i in &self.order {
+            let joint = &self.joints[i];
+            let local = pose.get(i).unwrap_or(&joint.rest).to_mat4();
+            out[i] = local
+                * match joint.parent {
+                    Some(parent) => out[parent],
+                    None => self.root,
+                };
+        }
+        for (m, joint) in out.iter_mut().zip(&self.joints) {
+            *m = joint.inverse_bind * *m;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anim::{Joint, Skeleton};
+    use crate::core::{Mat4, Quat, Transform, Vec3};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn joint(parent: Option<usize>, translation: Vec3, inverse_bind: Mat4) -> Joint {
+        Joint {
+            name: String::new(),
+            parent,
+            rest: Transform::new(translation, Quat::identity(), Vec3(1.0, 1.0, 1.0)),
+            inverse_bind,
+        }
+    }
+
+    #[test]
+    fn new() {
+        let id = Mat4::identity();
+        let v = Vec3(0.0, 0.0, 0.0);
+        assert!(Skeleton::new(vec![joint(Some(1), v, id), joint(None, v, id)], id).is_some());
+        assert!(Skeleton::new(vec![joint(Some(2), v, id), joint(None, v, id)], id).is_none());
+        assert!(Skeleton::new(vec![joint(Some(1), v, id), joint(Some(0), v, id)], id).is_none());
+    }
+
+    #[test]
+    fn palette() {
+        // A chain of two bones along the z axis, with the child listed first
+        let skeleton = Skeleton::new(
+            vec![
+                joint(
+                    Some(1),
+                    Vec3(0.0, 0.0, 1.0),
+                    Mat4::translation(Vec3(0.0, 0.0, -1.0)),
+                ),
+                joint(None, Vec3(0.0, 0.0, 0.0), Mat4::identity()),
+            ],
+            Mat4::translation(Vec3(5.0, 0.0, 0.0)),
+        )
+        .unwrap();
+
+        // The rest pose moves vertices only by the root
+        let mut palette = Vec::new();
+        skeleton.palette(&skeleton.rest_pose(), &mut palette);
+        assert_eq!(2, palette.len());
+        for m in &palette {
+            assert_eq!(Vec3(5.0, 0.0, 2.0), m.transform_point(Vec3(0.0, 0.0, 2.0)));
+        }
+
+        // Bending the root bends the child around the origin as well
+        let mut pose = skeleton.rest_pose();
+        pose[1].rotation = Quat::from_axis_angle(Vec3(1.0, 0.0, 0.0), FRAC_PI_2);
+        skeleton.palette(&pose, &mut palette);
+        assert_eq!(
+            Vec3(5.0, -2.0, 0.0),
+            palette[0].transform_point(Vec3(0.0, 0.0, 2.0))
+        );
+        assert_eq!(
+            Vec3(5.0, -2.0, 0.0),
+            palette[1].transform_point(Vec3(0.0, 0.0, 2.0))
+        );
+    }
+}
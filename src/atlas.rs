@@ -0,0 +1,524 @@
+use crate::core::Vec2;
+use crate::sprite::Rect;
+use std::mem;
+
+/// Size an atlas starts with before growing, unless its maximum is smaller.
+pub const INITIAL_SIZE: u32 = 256;
+
+/// Options of packing images into an atlas.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Options {
+    /// Largest width and height the atlas may grow to, which is usually the
+    /// `MAX_TEXTURE_SIZE` of the host. It is rounded down to a power of two
+    /// when the atlas is created, if the sizes have to be ones.
+    pub max_size: u32,
+    /// Empty pixels between images.
+    pub padding: u32,
+    /// Pixels around every image the host fills with copies of its edges,
+    /// so that filtering at the edges doesn't blend in other images.
+    pub extrude: u32,
+    /// Whether the atlas is kept at power of two sizes, which WebGL 1
+    /// requires for mipmaps and repeating.
+    pub power_of_two: bool,
+}
+
+/// Slot is the place of an image in an atlas in pixels, without the padding
+/// and the extruded edges around it.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Slot {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Segment of the skyline, which is the top edge of the packed images.
+#[derive(Copy, Clone, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Atlas packs rectangles into a texture with the skyline bottom-left
+/// algorithm: every image is put on the skyline where its top ends the
+/// lowest. Images can be added one at a time, such as glyphs rendered at
+/// runtime, in which case the atlas grows to the right and up as needed,
+/// without moving the images already placed. Growing changes the texture
+/// coordinates of all the slots, which have to be taken again.
+pub struct Atlas {
+    options: Options,
+    width: u32,
+    height: u32,
+    skyline: Vec<Segment>,
+    slots: Vec<Slot>,
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_size: 4096,
+            padding: 1,
+            extrude: 0,
+            power_of_two: true,
+        }
+    }
+}
+
+impl Atlas {
+    /// Return an empty atlas of the initial size.
+    #[inline]
+    pub fn new(options: Options) -> Self {
+        let size = INITIAL_SIZE.min(options.max_size).max(1);
+        Self::with_size(size, size, options)
+    }
+
+    /// Return an empty atlas of a size, which is rounded up to a power of
+    /// two if the options require, and clamped to the maximum size. The
+    /// maximum size is rounded down to a power of two first in that case,
+    /// so that growing never leaves the power of two sizes.
+    pub fn with_size(width: u32, height: u32, mut options: Options) -> Self {
+        let max = options.max_size.max(1);
+        options.max_size = if options.power_of_two {
+            1 << max.ilog2()
+        } else {
+            max
+        };
+        let round = |s: u32| {
+            let s = s.clamp(1, options.max_size);
+            if options.power_of_two {
+                s.next_power_of_two()
+            } else {
+                s
+            }
+        };
+        let width = round(width);
+        Self {
+            options,
+            width,
+            height: round(height),
+            skyline: vec![Segment { x: 0, y: 0, width }],
+            slots: Vec::new(),
+        }
+    }
+
+    /// Return an atlas of all the sizes, which are sorted first so that they
+    /// are packed tighter, with the slots in the order of the sizes. The
+    /// atlas is as narrow as fits them with little waste, and as high as the
+    /// highest image. Return `None` if they don't fit into the maximum size.
+    pub fn pack(sizes: &[(u32, u32)], options: Options) -> Option<Self> {
+        let mut atlas = Self::with_size(1, 1, options);
+        let max = atlas.options.max_size;
+        let padded: Vec<(u32, u32)> = sizes
+            .iter()
+            .map(|&(w, h)| atlas.padded(w, h))
+            .collect::<Option<_>>()?;
+        let area: u64 = padded.iter().map(|&(w, h)| w as u64 * h as u64).sum();
+        let widest = padded.iter().map(|&(w, _)| w).max().unwrap_or(1);
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by_key(|&i| (u32::MAX - padded[i].1, u32::MAX - padded[i].0));
+
+        let mut width = ((area as f64).sqrt().ceil() as u32).max(widest);
+        loop {
+            atlas = Self::with_size(width, max, options);
+            atlas.slots = vec![Slot::default(); sizes.len()];
+            let fits = order.iter().all(|&i| {
+                let (w, h) = sizes[i];
+                atlas
+                    .place(w, h)
+                    .map(|slot| atlas.slots[i] = slot)
+                    .is_some()
+            });
+            if fits {
+                break;
+            }
+            if atlas.width >= max {
+                return None;
+            }
+            width = atlas.width.saturating_add((atlas.width / 2).max(1));
+        }
+
+        let e = options.extrude;
+        let used = atlas.slots.iter().map(|s| s.y + s.height + e).max();
+        atlas.height = used.unwrap_or(0).max(1);
+        if options.power_of_two {
+            atlas.height = atlas.height.next_power_of_two();
+        }
+        Some(atlas)
+    }
+
+    #[inline]
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Return the number of images.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    #[inline]
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    /// Return the texture coordinates of a slot at the current size.
+    #[inline]
+    pub fn uv(&self, slot: usize) -> Option<Rect> {
+        let s = self.slots.get(slot)?;
+        let (w, h) = (self.width as f32, self.height as f32);
+        Some(Rect::new(
+            Vec2(s.x as f32 / w, s.y as f32 / h),
+            Vec2(
+                (s.x as f32 + s.width as f32) / w,
+                (s.y as f32 + s.height as f32) / h,
+            ),
+        ))
+    }
+
+    /// Remove all the images, keeping the size.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.skyline.clear();
+        self.skyline.push(Segment {
+            x: 0,
+            y: 0,
+            width: self.width,
+        });
+    }
+
+    /// Add an image, growing the atlas if it doesn't fit, and return the
+    /// index of its slot. Return `None` if it doesn't fit into the maximum
+    /// size, in which case the atlas is kept intact.
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<usize> {
+        if width > self.options.max_size || height > self.options.max_size {
+            return None;
+        }
+        let mut saved = None;
+        loop {
+            if let Some(slot) = self.place(width, height) {
+                self.slots.push(slot);
+                return Some(self.slots.len() - 1);
+            }
+            let saved =
+                saved.get_or_insert_with(|| (self.width, self.height, self.skyline.clone()));
+            if !self.grow() {
+                (self.width, self.height) = (saved.0, saved.1);
+                self.skyline = mem::take(&mut saved.2);
+                return None;
+            }
+        }
+    }
+
+    /// Return the size an image takes with the padding and extruded edges,
+    /// or `None` if it overflows. The padding is on the right and the top,
+    /// where it may be cut off by the edges of the atlas.
+    #[inline]
+    fn padded(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let border = self
+            .options
+            .extrude
+            .checked_mul(2)?
+            .checked_add(self.options.padding)?;
+        Some((width.checked_add(border)?, height.checked_add(border)?))
+    }
+
+    /// Put an image on the skyline and return its slot, without growing.
+    fn place(&mut self, width: u32, height: u32) -> Option<Slot> {
+        let (w, h) = self.padded(width, height)?;
+        let (i, x, y) = self.find(w, h)?;
+        self.raise(i, x, y + h, w);
+        let e = self.options.extrude;
+        Some(Slot {
+            x: x + e,
+            y: y + e,
+            width,
+            height,
+        })
+    }
+
+    /// Return the segment, the left and the bottom of the lowest place a
+    /// rectangle fits at, the leftmost of them if there are several.
+    fn find(&self, w: u32, h: u32) -> Option<(usize, u32, u32)> {
+        let pad = self.options.padding;
+        let fits = |start: u32, len: u32, size: u32| {
+            start
+                .checked_add(len)
+                .is_some_and(|end| end <= size.saturating_add(pad))
+        };
+        let mut best: Option<(usize, u32, u32)> = None;
+        for (i, s) in self.skyline.iter().enumerate() {
+            if !fits(s.x, w, self.width) {
+                break;
+            }
+            let y = self.skyline[i..]
+                .iter()
+                .take_while(|t| t.x < s.x + w)
+                .map(|t| t.y)
+                .max()
+                .unwrap_or(s.y);
+            if fits(y, h, self.height) && best.is_none_or(|(_, _, b)| y < b) {
+                best = Some((i, s.x, y));
+            }
+        }
+        best
+    }
+
+    /// Raise the skyline to a height over a width from a segment on.
+    fn raise(&mut self, i: usize, x: u32, y: u32, width: u32) {
+        self.skyline.insert(i, Segment { x, y, width });
+        let right = x + width;
+        while let Some(next) = self.skyline.get_mut(i + 1) {
+            if next.x >= right {
+                break;
+            }
+            let overlap = right - next.x;
+            if overlap < next.width {
+                next.x = right;
+                next.width -= overlap;
+                break;
+            }
+            self.skyline.remove(i + 1);
+        }
+        self.merge();
+    }
+
+    /// Join the neighbouring segments of the same height.
+    fn merge(&mut self) {
+        let mut i = 1;
+        while i < self.skyline.len() {
+            if self.skyline[i - 1].y == self.skyline[i].y {
+                self.skyline[i - 1].width += self.skyline[i].width;
+                self.skyline.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Grow the smaller side, or the other one if it is at the maximum
+    /// already. Return false if both are.
+    fn grow(&mut self) -> bool {
+        let max = self.options.max_size;
+        let next = |s: u32| {
+            let s = if self.options.power_of_two {
+                s.saturating_mul(2)
+            } else {
+                s.saturating_add((s / 2).max(1))
+            };
+            s.min(max)
+        };
+        match (self.width < max, self.height < max) {
+            (true, true) if self.width <= self.height => self.resize(next(self.width), self.height),
+            (_, true) => self.resize(self.width, next(self.height)),
+            (true, false) => self.resize(next(self.width), self.height),
+            (false, false) => return false,
+        }
+        true
+    }
+
+    /// Enlarge the atlas, keeping the images in place. The skyline may
+    /// reach past the right edge by the padding of the last image already.
+    fn resize(&mut self, width: u32, height: u32) {
+        let end = self.skyline.last().map_or(0, |s| s.x + s.width);
+        if width > end {
+            self.skyline.push(Segment {
+                x: end,
+                y: 0,
+                width: width - end,
+            });
+            self.merge();
+        }
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::atlas::{Atlas, Options, Slot};
+    use crate::core::Vec2;
+
+    fn overlap(a: &Slot, b: &Slot) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    fn assert_disjoint(atlas: &Atlas) {
+        for (i, a) in atlas.slots().iter().enumerate() {
+            assert!(a.x + a.width <= atlas.width() && a.y + a.height <= atlas.height());
+            for b in &atlas.slots()[i + 1..] {
+                assert!(!overlap(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn pack() {
+        let sizes = [(32, 32), (64, 16), (16, 64), (32, 32), (8, 8), (100, 20)];
+        let atlas = Atlas::pack(&sizes, Options::default()).unwrap();
+        assert_eq!(6, atlas.len());
+        assert_disjoint(&atlas);
+        assert!(atlas.width().is_power_of_two() && atlas.height().is_power_of_two());
+        for (slot, &(w, h)) in atlas.slots().iter().zip(&sizes) {
+            assert_eq!((w, h), (slot.width, slot.height));
+        }
+
+        // Tight sizes without padding
+        let options = Options {
+            padding: 0,
+            power_of_two: false,
+            ..Options::default()
+        };
+        let atlas = Atlas::pack(&[(10, 10); 4], options).unwrap();
+        assert_eq!((20, 20), (atlas.width(), atlas.height()));
+        assert_disjoint(&atlas);
+        let uv = atlas.uv(3).unwrap();
+        assert_eq!(Vec2(0.5, 0.5), uv.size());
+
+        let small = Options {
+            max_size: 64,
+            ..Options::default()
+        };
+        assert!(Atlas::pack(&[(64, 64), (1, 1)], small).is_none());
+
+        // Padding is cut off by the edges
+        let atlas = Atlas::pack(&[(64, 64)], small).unwrap();
+        assert_eq!((64, 64), (atlas.width(), atlas.height()));
+        assert!(Atlas::pack(&[], small).is_some());
+    }
+
+    #[test]
+    fn overflow() {
+        // Sizes that overflow with the padding don't fit rather than wrap
+        let options = Options {
+            max_size: u32::MAX,
+            padding: 2,
+            extrude: u32::MAX / 2,
+            power_of_two: false,
+        };
+        assert!(Atlas::pack(&[(1, 1)], options).is_none());
+        let mut atlas = Atlas::new(options);
+        assert_eq!(None, atlas.insert(1, 1));
+        assert!(atlas.is_empty());
+
+        let options = Options {
+            max_size: u32::MAX,
+            ..Options::default()
+        };
+        assert!(Atlas::pack(&[(u32::MAX, 1)], options).is_none());
+        let mut atlas = Atlas::with_size(16, 16, options);
+        atlas.insert(8, 8);
+        assert_eq!(None, atlas.place(u32::MAX - 4, 1));
+        assert_eq!(1, atlas.len());
+    }
+
+    #[test]
+    fn max_size() {
+        // A maximum size that isn't a power of two is rounded down
+        let options = Options {
+            max_size: 100,
+            ..Options::default()
+        };
+        let atlas = Atlas::with_size(100, 100, options);
+        assert_eq!((64, 64), (atlas.width(), atlas.height()));
+        assert_eq!(64, atlas.options().max_size);
+        assert_eq!(64, Atlas::new(options).width());
+        assert!(Atlas::pack(&[(70, 1)], options).is_none());
+        assert!(Atlas::pack(&[(60, 60), (30, 30)], options).is_none());
+
+        let mut atlas = Atlas::with_size(8, 8, options);
+        while atlas.insert(8, 8).is_some() {}
+        assert_eq!((64, 64), (atlas.width(), atlas.height()));
+        assert_disjoint(&atlas);
+
+        // Other sizes are only clamped
+        let options = Options {
+            power_of_two: false,
+            ..options
+        };
+        let atlas = Atlas::with_size(100, 200, options);
+        assert_eq!((100, 100), (atlas.width(), atlas.height()));
+    }
+
+    #[test]
+    fn padding() {
+        let options = Options {
+            padding: 2,
+            extrude: 1,
+            power_of_two: false,
+            ..Options::default()
+        };
+        let atlas = Atlas::pack(&[(10, 10), (10, 10)], options).unwrap();
+        let [a, b] = atlas.slots() else { panic!() };
+        assert_eq!((1, 1), (a.x, a.y));
+
+        // Extruded edges of both and the padding between them
+        let gap = if a.x == b.x { b.y - a.y } else { b.x - a.x };
+        assert_eq!(10 + 1 + 2 + 1, gap);
+    }
+
+    #[test]
+    fn insert() {
+        let options = Options {
+            max_size: 128,
+            padding: 0,
+            ..Options::default()
+        };
+        let mut atlas = Atlas::with_size(32, 32, options);
+        assert_eq!(Some(0), atlas.insert(16, 16));
+        assert_eq!(Some(1), atlas.insert(16, 8));
+        assert_eq!(Some(2), atlas.insert(16, 8));
+        assert_eq!((32, 32), (atlas.width(), atlas.height()));
+        assert_eq!(
+            Slot {
+                x: 16,
+                y: 8,
+                width: 16,
+                height: 8
+            },
+            atlas.slots()[2]
+        );
+
+        // Images that don't fit grow the atlas, keeping the others in place
+        let slots = atlas.slots().to_vec();
+        assert_eq!(Some(3), atlas.insert(40, 20));
+        assert_eq!(&slots[..], &atlas.slots()[..3]);
+        assert!(atlas.width() > 32 || atlas.height() > 32);
+        assert!(atlas.width().is_power_of_two() && atlas.height().is_power_of_two());
+        assert_disjoint(&atlas);
+
+        // Too large an image keeps the atlas intact
+        let size = (atlas.width(), atlas.height());
+        assert_eq!(None, atlas.insert(129, 1));
+        assert_eq!(None, atlas.insert(u32::MAX, 1));
+        assert_eq!(size, (atlas.width(), atlas.height()));
+        for _ in 0..40 {
+            atlas.insert(16, 16);
+        }
+        assert_eq!((128, 128), (atlas.width(), atlas.height()));
+        assert_disjoint(&atlas);
+
+        atlas.clear();
+        assert!(atlas.is_empty());
+        assert_eq!(Some(0), atlas.insert(128, 128));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::camera::{angles, direction, Camera, UP};
+use crate::core::Vec3;
+use std::f32::consts::FRAC_PI_2;
+
+/// Fly moves the camera freely, turned by the pointer and moved by the keys
+/// relative to where it looks, like in the first person view.
+#[derive(Copy, Clone)]
+pub struct Fly {
+    /// Heading around the world up, in radians.
+    pub yaw: f32,
+    /// Angle above the horizon, in radians.
+    pub pitch: f32,
+    /// Movement along the right, the world up and the forward directions,
+    /// each from -1 to 1, as held by the keys.
+    pub movement: Vec3,
+    /// Units moved per second.
+    pub speed: f32,
+    /// Radians turned per pixel of the pointer movement.
+    pub sensitivity: f32,
+}
+
+impl Fly {
+    /// Return a controller looking in the direction of the given angles.
+    #[inline]
+    pub const fn new(yaw: f32, pitch: f32) -> Self {
+        Self {
+            yaw,
+            pitch,
+            movement: Vec3(0.0, 0.0, 0.0),
+            speed: 5.0,
+            sensitivity: 0.003,
+        }
+    }
+
+    /// Return a controller looking where the camera does.
+    #[inline]
+    pub fn from_camera(camera: &Camera) -> Self {
+        let (yaw, pitch) = angles(camera.forward());
+        let mut fly = Self::new(yaw, pitch);
+        fly.look(0.0, 0.0);
+        fly
+    }
+
+    /// Turn by the pointer movement in pixels, short of looking straight up
+    /// or down.
+    #[inline]
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        const LIMIT: f32 = FRAC_PI_2 - 0.01;
+        self.yaw -= dx * self.sensitivity;
+        self.pitch = (self.pitch - dy * self.sensitivity).clamp(-LIMIT, LIMIT);
+    }
+
+    pub fn update(&self, camera: &mut Camera, dt: f32) {
+        let forward = direction(self.yaw, self.pitch);
+        let right = forward.cross(UP).normalize_precise();
+        let m = self.movement;
+        camera.position += (right * m.0 + UP * m.1 + forward * m.2) * (self.speed * dt);
+        camera.look_at(camera.position, camera.position + forward, UP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{Camera, Fly, UP};
+    use crate::core::Vec3;
+
+    #[test]
+    fn fly() {
+        let mut camera = Camera::new();
+        camera.look_at(Vec3(0.0, 0.0, 1.0), Vec3(0.0, 5.0, 1.0), UP);
+        let mut fly = Fly::from_camera(&camera);
+        fly.movement = Vec3(1.0, 0.0, 1.0);
+        fly.update(&mut camera, 0.5);
+        assert_eq!(Vec3(2.5, 2.5, 1.0), camera.position);
+        assert_eq!(Vec3(0.0, 1.0, 0.0), camera.forward());
+
+        // Moving the pointer up looks up, and the rising is along the world up
+        fly.look(0.0, -1000.0);
+        fly.movement = Vec3(0.0, 1.0, 0.0);
+        fly.update(&mut camera, 1.0);
+        assert_eq!(Vec3(2.5, 2.5, 6.0), camera.position);
+        assert!(camera.forward().2 > 0.99);
+        assert!(!camera.view().0 .0.is_nan());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::camera::{Camera, UP};
+use crate::core::{Quat, Vec3};
+
+/// Follow keeps the camera behind a moving target, as in the third person
+/// view. The camera and its focus catch up with the target exponentially,
+/// which smooths out the jitter of the target without depending on the step
+/// duration.
+#[derive(Copy, Clone)]
+pub struct Follow {
+    /// Position of the target, updated as it moves.
+    pub target: Vec3,
+    /// Heading of the target around the world up, in radians, the offset
+    /// turns with.
+    pub heading: f32,
+    /// Offset of the camera from the target when it heads along the x axis.
+    pub offset: Vec3,
+    /// Offset of the point looked at from the target.
+    pub focus: Vec3,
+    /// Rate of catching up with the target per second; the higher, the
+    /// stiffer.
+    pub stiffness: f32,
+    /// Point currently looked at.
+    look: Vec3,
+}
+
+impl Follow {
+    /// Return a controller following a target from behind and above.
+    #[inline]
+    pub const fn new(target: Vec3, heading: f32) -> Self {
+        Self {
+            target,
+            heading,
+            offset: Vec3(-4.0, 0.0, 2.0),
+            focus: Vec3(0.0, 0.0, 1.0),
+            stiffness: 6.0,
+            look: Vec3(target.0, target.1, target.2 + 1.0),
+        }
+    }
+
+    /// Move the target, turned to the given heading.
+    #[inline]
+    pub fn set_target(&mut self, target: Vec3, heading: f32) {
+        self.target = target;
+        self.heading = heading;
+    }
+
+    /// Put the camera where it is headed right away, without catching up.
+    pub fn snap(&mut self, camera: &mut Camera) {
+        self.look = self.target + self.focus;
+        camera.look_at(self.position(), self.look, UP);
+    }
+
+    /// Return the position the camera is headed to.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.target + Quat::from_axis_angle(UP, self.heading) * self.offset
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let t = 1.0 - (-self.stiffness * dt).exp();
+        let position = camera.position + (self.position() - camera.position) * t;
+        self.look += (self.target + self.focus - self.look) * t;
+        camera.look_at(position, self.look, UP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{Camera, Follow};
+    use crate::core::Vec3;
+    use std::f32::consts::{FRAC_PI_2, LN_2};
+
+    #[test]
+    fn follow() {
+        let mut camera = Camera::new();
+        let mut follow = Follow::new(Vec3(0.0, 0.0, 0.0), 0.0);
+        follow.snap(&mut camera);
+        assert_eq!(Vec3(-4.0, 0.0, 2.0), camera.position);
+
+        // The camera turns with the target heading
+        follow.set_target(Vec3(0.0, 0.0, 0.0), FRAC_PI_2);
+        assert_eq!(Vec3(0.0, -4.0, 2.0), follow.position());
+
+        // Half of the way is caught up with per ln 2 / stiffness seconds,
+        // whether in a single step or several
+        let mut other = camera;
+        let mut other_follow = follow;
+        follow.update(&mut camera, LN_2 / follow.stiffness);
+        assert_eq!(Vec3(-2.0, -2.0, 2.0), camera.position);
+        for _ in 0..4 {
+            other_follow.update(&mut other, LN_2 / follow.stiffness / 4.0);
+        }
+        assert_eq!(camera.position, other.position);
+        assert_eq!(camera.forward(), other.forward());
+    }
+}
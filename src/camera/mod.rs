@@ -0,0 +1,197 @@
+pub mod fly;
+pub mod follow;
+pub mod orbit;
+
+pub use fly::Fly;
+pub use follow::Follow;
+pub use orbit::Orbit;
+
+use crate::core::{Mat4, Quat, Vec3};
+use std::f32::consts::FRAC_PI_3;
+
+/// Direction of the world up, which cameras keep their horizon level to.
+pub const UP: Vec3 = Vec3(0.0, 0.0, 1.0);
+
+/// Camera is a point of view with a perspective projection. It looks along
+/// its local negative z axis, with the local y axis being up, the same way
+/// `Mat4::look_at` orients it.
+#[derive(Copy, Clone)]
+pub struct Camera {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Vertical field of view in radians.
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// Controller moves a camera each simulation step, driven by the input.
+pub enum Controller {
+    Orbit(Orbit),
+    Fly(Fly),
+    Follow(Follow),
+}
+
+impl Default for Camera {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Camera {
+    /// Return a camera at the origin looking down the negative z axis.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            position: Vec3(0.0, 0.0, 0.0),
+            rotation: Quat(0.0, 0.0, 0.0, 1.0),
+            fov: FRAC_PI_3,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+
+    /// Place the camera at a position and turn it towards a target.
+    #[inline]
+    pub fn look_at(&mut self, position: Vec3, target: Vec3, up: Vec3) {
+        self.position = position;
+        self.rotation = Mat4::look_at(position, target, up).to_quat();
+    }
+
+    /// Return the direction the camera looks in.
+    #[inline]
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3(0.0, 0.0, -1.0)
+    }
+
+    /// Return the direction to the right of the view.
+    #[inline]
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3(1.0, 0.0, 0.0)
+    }
+
+    /// Return the matrix placing the camera in the world.
+    #[inline]
+    pub fn transform(&self) -> Mat4 {
+        Mat4::compose(self.position, Vec3(1.0, 1.0, 1.0), self.rotation)
+    }
+
+    /// Return the matrix transforming the world into the camera space.
+    #[inline]
+    pub fn view(&self) -> Mat4 {
+        self.transform().inverse_rigid()
+    }
+
+    /// Return the projection for a viewport of the given width to height
+    /// ratio.
+    #[inline]
+    pub fn projection(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective(self.fov, aspect, self.near, self.far)
+    }
+
+    /// Return the matrix transforming the world into the clip space.
+    #[inline]
+    pub fn view_projection(&self, aspect: f32) -> Mat4 {
+        self.view() * self.projection(aspect)
+    }
+
+    /// Return a camera between two others, keeping the projection of the
+    /// latter.
+    #[inline]
+    pub fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        Self {
+            position: self.position + (rhs.position - self.position) * t,
+            rotation: self.rotation.nlerp(rhs.rotation, t),
+            ..*rhs
+        }
+    }
+}
+
+impl Controller {
+    /// Move the camera by the given number of seconds.
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        match self {
+            Controller::Orbit(c) => c.update(camera),
+            Controller::Fly(c) => c.update(camera, dt),
+            Controller::Follow(c) => c.update(camera, dt),
+        }
+    }
+
+    /// Turn the camera by the pointer movement in pixels, if the controller
+    /// is driven by the pointer.
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        match self {
+            Controller::Orbit(c) => c.rotate(dx, dy),
+            Controller::Fly(c) => c.look(dx, dy),
+            Controller::Follow(_) => {}
+        }
+    }
+}
+
+/// Return the direction of the given heading around the world up and the
+/// pitch above the horizon.
+#[inline]
+fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    Vec3(cos_pitch * cos_yaw, cos_pitch * sin_yaw, sin_pitch)
+}
+
+/// Return the heading and the pitch of a direction, the inverse of
+/// `direction`.
+#[inline]
+fn angles(direction: Vec3) -> (f32, f32) {
+    let d = direction.normalize_precise();
+    (d.1.atan2(d.0), d.2.clamp(-1.0, 1.0).asin())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{angles, direction, Camera, UP};
+    use crate::core::{Mat4, Quat, Vec3};
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn look_at() {
+        let mut c = Camera::new();
+        c.look_at(Vec3(0.0, -5.0, 0.0), Vec3(0.0, 0.0, 0.0), UP);
+        assert_eq!(Vec3(0.0, 1.0, 0.0), c.forward());
+        assert_eq!(Vec3(1.0, 0.0, 0.0), c.right());
+        assert_eq!(
+            Mat4::look_at(Vec3(0.0, -5.0, 0.0), Vec3(0.0, 0.0, 0.0), UP),
+            c.transform()
+        );
+
+        // The target is in the middle of the view, in front of the camera
+        let p = c.view_projection(2.0).transform_point(Vec3(0.0, 0.0, 0.0));
+        assert_eq!(Vec3(0.0, 0.0, p.2), p);
+        assert!(p.2 > -1.0 && p.2 < 1.0);
+        assert_eq!(
+            Vec3(0.0, 0.0, -5.0),
+            c.view().transform_point(Vec3(0.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn lerp() {
+        let mut a = Camera::new();
+        a.position = Vec3(2.0, 0.0, 0.0);
+        let mut b = Camera::new();
+        b.rotation = Quat::from_axis_angle(UP, FRAC_PI_2);
+        b.fov = 1.0;
+        let c = a.lerp(&b, 0.5);
+        assert_eq!(Vec3(1.0, 0.0, 0.0), c.position);
+        assert_eq!(Quat::from_axis_angle(UP, FRAC_PI_2 / 2.0), c.rotation);
+        assert_eq!(1.0, c.fov);
+    }
+
+    #[test]
+    fn angles_of_direction() {
+        let d = direction(1.0, 0.5);
+        assert_eq!(1.0, d.len());
+        let (yaw, pitch) = angles(d * 3.0_f32);
+        assert!((yaw - 1.0).abs() < 1e-5);
+        assert!((pitch - 0.5).abs() < 1e-5);
+    }
+}
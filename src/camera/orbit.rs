@@ -0,0 +1,134 @@
+use crate::camera::{angles, direction, Camera, UP};
+use crate::core::Vec3;
+use std::f32::consts::FRAC_PI_2;
+
+/// Orbit keeps the camera on a sphere around a target, turned by dragging
+/// the pointer and zoomed by the wheel. The pitch stays short of the poles,
+/// where the horizon would flip.
+#[derive(Copy, Clone)]
+pub struct Orbit {
+    pub target: Vec3,
+    /// Heading around the world up, in radians.
+    pub yaw: f32,
+    /// Angle above the horizon, in radians.
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Radians turned per pixel of the pointer movement.
+    pub sensitivity: f32,
+    /// Relative change of the distance per unit of the wheel movement.
+    pub zoom_speed: f32,
+}
+
+impl Orbit {
+    /// Return an orbit around a target, at the given angles and distance.
+    #[inline]
+    pub const fn new(target: Vec3, yaw: f32, pitch: f32, distance: f32) -> Self {
+        Self {
+            target,
+            yaw,
+            pitch,
+            distance,
+            min_pitch: -FRAC_PI_2 + 0.01,
+            max_pitch: FRAC_PI_2 - 0.01,
+            min_distance: 0.5,
+            max_distance: 50.0,
+            sensitivity: 0.005,
+            zoom_speed: 0.001,
+        }
+    }
+
+    /// Return an orbit around a target that passes through the camera.
+    #[inline]
+    pub fn around(camera: &Camera, target: Vec3) -> Self {
+        let offset = camera.position - target;
+        let (yaw, pitch) = angles(offset);
+        let mut orbit = Self::new(target, yaw, pitch, offset.len());
+        orbit.clamp();
+        orbit
+    }
+
+    /// Turn around the target by the pointer movement in pixels, dragging
+    /// the world along with the pointer.
+    #[inline]
+    pub fn rotate(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * self.sensitivity;
+        self.pitch += dy * self.sensitivity;
+        self.clamp();
+    }
+
+    /// Move towards the target for negative wheel movement, and away from it
+    /// for positive one.
+    #[inline]
+    pub fn zoom(&mut self, delta: f32) {
+        self.distance *= (delta * self.zoom_speed).exp();
+        self.clamp();
+    }
+
+    /// Keep the angles and the distance within their limits.
+    #[inline]
+    pub fn clamp(&mut self) {
+        self.pitch = self.pitch.clamp(self.min_pitch, self.max_pitch);
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Return the position of the camera on the orbit.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.target + direction(self.yaw, self.pitch) * self.distance
+    }
+
+    pub fn update(&self, camera: &mut Camera) {
+        camera.look_at(self.position(), self.target, UP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{Camera, Orbit};
+    use crate::core::Vec3;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn orbit() {
+        let mut camera = Camera::new();
+        camera.position = Vec3(6.0, -6.0, 8.5);
+        let mut orbit = Orbit::around(&camera, Vec3(0.0, 0.0, 0.5));
+        assert_eq!(Vec3(6.0, -6.0, 8.5), orbit.position());
+        orbit.update(&mut camera);
+        assert_eq!(Vec3(6.0, -6.0, 8.5), camera.position);
+        assert_eq!(Vec3(-6.0, 6.0, -8.0).normalize(), camera.forward());
+
+        // A quarter turn around the target keeps the distance
+        orbit.rotate(-FRAC_PI_2 / orbit.sensitivity, 0.0);
+        orbit.update(&mut camera);
+        assert_eq!(Vec3(6.0, 6.0, 8.5), camera.position);
+    }
+
+    #[test]
+    fn clamp() {
+        let mut orbit = Orbit::new(Vec3(0.0, 0.0, 0.0), 0.0, 0.0, 10.0);
+        orbit.rotate(0.0, 1e6);
+        assert_eq!(orbit.max_pitch, orbit.pitch);
+        orbit.rotate(0.0, -1e6);
+        assert_eq!(orbit.min_pitch, orbit.pitch);
+
+        // The camera stays valid looking straight up at the target
+        let mut camera = Camera::new();
+        orbit.update(&mut camera);
+        assert!(camera.forward().2 > 0.99);
+        assert!(!camera.view().0 .0.is_nan());
+
+        orbit.zoom(1e6);
+        assert_eq!(orbit.max_distance, orbit.distance);
+        orbit.zoom(-1e6);
+        assert_eq!(orbit.min_distance, orbit.distance);
+        orbit.zoom(-693.147);
+        assert_eq!(orbit.min_distance, orbit.distance);
+        orbit.zoom(693.147);
+        assert!((orbit.min_distance * 2.0 - orbit.distance).abs() < 1e-4);
+    }
+}
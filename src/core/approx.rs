@@ -1,17 +1,70 @@
+/// Default absolute tolerance, used by `approx_eq` and thereby by `==` of the
+/// vector and matrix types.
 pub const EPS: f32 = 0.01;
 
-pub trait Approx {
-    fn approx_eq(self, rhs: Self) -> bool;
-    fn isqrt(&self) -> f32;
-    fn asqrt(&self) -> f32;
+/// Approximate comparison of floating point based values. Vectors and
+/// matrices are compared component-wise and are equal only if all of their
+/// components are.
+pub trait Approx: Sized {
+    /// Compare with the default absolute tolerance `EPS`.
+    #[inline]
+    fn approx_eq(self, rhs: Self) -> bool {
+        self.approx_eq_eps(rhs, EPS)
+    }
+
+    /// Compare with an explicit absolute tolerance, which suits values of a
+    /// known magnitude, like normalized vectors or colors.
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool;
+
+    /// Compare with a tolerance relative to the larger of the two values,
+    /// which suits values of any magnitude, like world coordinates.
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool;
+
+    /// Compare by the number of representable values in between, where 0
+    /// means exactly equal and 1 means the values are adjacent.
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool;
+
+    /// Exact comparison of the underlying bits. Unlike `==` on f32 it tells
+    /// 0.0 from -0.0 and treats the same NaN as equal to itself.
+    fn bitwise_eq(self, rhs: Self) -> bool;
 }
 
 impl Approx for f32 {
     #[inline]
-    fn approx_eq(self, rhs: Self) -> bool {
-        (self - rhs).abs() <= EPS
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        (self - rhs).abs() <= eps
     }
 
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        (self - rhs).abs() <= rel * self.abs().max(rhs.abs())
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        if self.is_nan() || rhs.is_nan() {
+            false
+        } else if self.is_sign_negative() != rhs.is_sign_negative() {
+            // Values of different signs are only equal if both are zeros.
+            self == rhs
+        } else {
+            self.to_bits().abs_diff(rhs.to_bits()) <= ulps
+        }
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.to_bits() == rhs.to_bits()
+    }
+}
+
+/// Fast approximations of square roots, which trade accuracy for speed.
+pub trait FastSqrt {
+    fn isqrt(&self) -> f32;
+    fn asqrt(&self) -> f32;
+}
+
+impl FastSqrt for f32 {
     #[inline]
     fn isqrt(&self) -> Self {
         let mut y = *self;
@@ -31,13 +84,55 @@ impl Approx for f32 {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Approx, EPS};
+    use crate::core::{Approx, FastSqrt, EPS};
 
     #[test]
     fn approx_eq() {
         let a = 3.14;
         let b = 3.14 + EPS;
-        assert_eq!(true, a.approx_eq(b));
+        assert!(a.approx_eq(b));
+    }
+
+    #[test]
+    fn approx_eq_eps() {
+        let a: f32 = 0.5;
+        assert!(a.approx_eq_eps(0.5001, 0.001));
+        assert!(!a.approx_eq_eps(0.502, 0.001));
+    }
+
+    #[test]
+    fn approx_eq_rel() {
+        // Absolute tolerance is too strict for large values
+        let a: f32 = 100000.0;
+        assert!(!a.approx_eq(100000.1));
+        assert!(a.approx_eq_rel(100000.1, 0.00001));
+
+        // and too loose for small ones
+        let a: f32 = 0.001;
+        assert!(a.approx_eq(0.002));
+        assert!(!a.approx_eq_rel(0.002, 0.00001));
+        assert!(0.0.approx_eq_rel(0.0, 0.00001));
+    }
+
+    #[test]
+    fn approx_eq_ulps() {
+        let a: f32 = 1.0;
+        let b = f32::from_bits(a.to_bits() + 2);
+        assert!(a.approx_eq_ulps(a, 0));
+        assert!(a.approx_eq_ulps(b, 2));
+        assert!(!a.approx_eq_ulps(b, 1));
+        assert!(0.0.approx_eq_ulps(-0.0, 0));
+        assert!(!f32::NAN.approx_eq_ulps(f32::NAN, 4));
+        assert!(!f32::MIN_POSITIVE.approx_eq_ulps(-f32::MIN_POSITIVE, 4));
+    }
+
+    #[test]
+    fn bitwise_eq() {
+        let a: f32 = 0.1 + 0.2;
+        assert!(a.bitwise_eq(0.1 + 0.2));
+        assert!(!a.bitwise_eq(0.3 + EPS));
+        assert!(!0.0.bitwise_eq(-0.0));
+        assert!(f32::NAN.bitwise_eq(f32::NAN));
     }
 
     #[test]
@@ -45,13 +140,13 @@ mod tests {
         let a: f32 = 4.0;
         let b: f32 = 3.0;
         let len = 1.0 / (a * a + b * b).isqrt();
-        assert_eq!(true, 5.0.approx_eq(len));
+        assert!(5.0.approx_eq(len));
     }
 
     #[test]
     fn asqrt() {
         let a = 9.86902225;
-        assert_eq!(true, a.asqrt().approx_eq(3.1415));
+        assert!(a.asqrt().approx_eq(3.1415));
     }
 
     #[test]
@@ -0,0 +1,467 @@
+use crate::core::{Approx, Mat4, Vec3};
+
+/// Aabb is an axis-aligned bounding box, stored as its minimum and maximum
+/// corners. It is the cheapest volume to test against and to merge, which
+/// makes it a good fit for culling and broad-phase picking.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Aabb(pub Vec3, pub Vec3);
+
+impl PartialEq for Aabb {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1 == rhs.1
+    }
+}
+
+impl Default for Aabb {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Aabb {
+    #[inline]
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self(min, max)
+    }
+
+    /// Return a box that contains nothing. Its corners are inverted
+    /// infinities, so that it is an identity of `union` and `expand`.
+    #[inline]
+    pub fn empty() -> Self {
+        Self(
+            Vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            Vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        )
+    }
+
+    /// Return the smallest box enclosing all the points, or an empty box
+    /// if there are none.
+    #[inline]
+    pub fn from_points(points: &[Vec3]) -> Self {
+        Self::fit(points.iter().copied())
+    }
+
+    /// Return the smallest box enclosing all the points of an iterator,
+    /// such as the positions of vertices.
+    #[inline]
+    pub fn fit<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        points.into_iter().fold(Self::empty(), Self::expand)
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 .0 > self.1 .0 || self.0 .1 > self.1 .1 || self.0 .2 > self.1 .2
+    }
+
+    #[inline]
+    pub fn center(self) -> Vec3 {
+        (self.0 + self.1) * 0.5_f32
+    }
+
+    /// Return half of the box size along each axis.
+    #[inline]
+    pub fn extents(self) -> Vec3 {
+        (self.1 - self.0) * 0.5_f32
+    }
+
+    /// Return the smallest box enclosing both boxes.
+    #[inline]
+    pub fn union(self, rhs: Self) -> Self {
+        Self(self.0.min(rhs.0), self.1.max(rhs.1))
+    }
+
+    /// Return the smallest box enclosing both the box and the point.
+    #[inline]
+    pub fn expand(self, p: Vec3) -> Self {
+        Self(self.0.min(p), self.1.max(p))
+    }
+
+    /// Return the box grown by a margin in every direction.
+    #[inline]
+    pub fn inflate(self, margin: f32) -> Self {
+        let m = Vec3(margin, margin, margin);
+        Self(self.0 - m, self.1 + m)
+    }
+
+    /// Check if the point is inside the box or on its boundary.
+    #[inline]
+    pub fn contains(self, p: Vec3) -> bool {
+        p.0 >= self.0 .0
+            && p.0 <= self.1 .0
+            && p.1 >= self.0 .1
+            && p.1 <= self.1 .1
+            && p.2 >= self.0 .2
+            && p.2 <= self.1 .2
+    }
+
+    /// Check if the other box is entirely inside the box.
+    #[inline]
+    pub fn contains_aabb(self, rhs: Self) -> bool {
+        rhs.is_empty() || (self.contains(rhs.0) && self.contains(rhs.1))
+    }
+
+    /// Check if the boxes have at least one common point.
+    #[inline]
+    pub fn overlaps(self, rhs: Self) -> bool {
+        self.0 .0 <= rhs.1 .0
+            && self.1 .0 >= rhs.0 .0
+            && self.0 .1 <= rhs.1 .1
+            && self.1 .1 >= rhs.0 .1
+            && self.0 .2 <= rhs.1 .2
+            && self.1 .2 >= rhs.0 .2
+    }
+
+    /// Return the smallest axis-aligned box enclosing the box transformed
+    /// by an affine matrix. Each of the matrix axes stretches the box on
+    /// its own, so the result is found without transforming the 8 corners.
+    #[inline]
+    pub fn transform(self, m: Mat4) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        let t = Vec3(m.3 .0, m.3 .1, m.3 .2);
+        let (mut min, mut max) = (t, t);
+        for (axis, lo, hi) in [
+            (Vec3(m.0 .0, m.0 .1, m.0 .2), self.0 .0, self.1 .0),
+            (Vec3(m.1 .0, m.1 .1, m.1 .2), self.0 .1, self.1 .1),
+            (Vec3(m.2 .0, m.2 .1, m.2 .2), self.0 .2, self.1 .2),
+        ] {
+            let (a, b) = (axis * lo, axis * hi);
+            min += a.min(b);
+            max += a.max(b);
+        }
+        Self(min, max)
+    }
+}
+
+/// Sphere is a bounding sphere, stored as its center and radius. Unlike a
+/// box it does not change its size when rotated, which makes it a good fit
+/// for moving objects.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Sphere(pub Vec3, pub f32);
+
+impl PartialEq for Sphere {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1.approx_eq(rhs.1)
+    }
+}
+
+impl Default for Sphere {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Sphere {
+    #[inline]
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self(center, radius)
+    }
+
+    /// Return a sphere that contains nothing. It has a negative radius and
+    /// is an identity of `union` and `expand`.
+    #[inline]
+    pub fn empty() -> Self {
+        Self(Vec3(0.0, 0.0, 0.0), -1.0)
+    }
+
+    /// Return a sphere enclosing all the points, or an empty sphere if there
+    /// are none. The sphere is centered in the bounding box of the points,
+    /// which is not the smallest one, but is close to it and cheap to find.
+    #[inline]
+    pub fn from_points(points: &[Vec3]) -> Self {
+        Self::fit(points.iter().copied())
+    }
+
+    /// Return a sphere enclosing all the points of an iterator, which is
+    /// iterated twice, first for the center and then for the radius.
+    #[inline]
+    pub fn fit<I: IntoIterator<Item = Vec3> + Clone>(points: I) -> Self {
+        let aabb = Aabb::fit(points.clone());
+        if aabb.is_empty() {
+            return Self::empty();
+        }
+        let center = aabb.center();
+        let radius2 = points
+            .into_iter()
+            .fold(0.0_f32, |r, p| r.max((p - center).len2()));
+        Self(center, radius2.sqrt())
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.1 < 0.0
+    }
+
+    /// Return the smallest sphere enclosing both spheres.
+    #[inline]
+    pub fn union(self, rhs: Self) -> Self {
+        if rhs.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return rhs;
+        }
+        let d = (rhs.0 - self.0).len();
+        if d + rhs.1 <= self.1 {
+            self
+        } else if d + self.1 <= rhs.1 {
+            rhs
+        } else {
+            let radius = (d + self.1 + rhs.1) * 0.5;
+            Self(self.0 + (rhs.0 - self.0) * ((radius - self.1) / d), radius)
+        }
+    }
+
+    /// Return the smallest sphere enclosing both the sphere and the point.
+    #[inline]
+    pub fn expand(self, p: Vec3) -> Self {
+        self.union(Self(p, 0.0))
+    }
+
+    /// Return the sphere grown by a margin in every direction.
+    #[inline]
+    pub fn inflate(self, margin: f32) -> Self {
+        Self(self.0, self.1 + margin)
+    }
+
+    /// Check if the point is inside the sphere or on its boundary.
+    #[inline]
+    pub fn contains(self, p: Vec3) -> bool {
+        (p - self.0).len2() <= self.1 * self.1 && !self.is_empty()
+    }
+
+    /// Check if the other sphere is entirely inside the sphere.
+    #[inline]
+    pub fn contains_sphere(self, rhs: Self) -> bool {
+        rhs.is_empty() || (rhs.0 - self.0).len() + rhs.1 <= self.1
+    }
+
+    /// Check if the spheres have at least one common point.
+    #[inline]
+    pub fn overlaps(self, rhs: Self) -> bool {
+        let r = self.1 + rhs.1;
+        !self.is_empty() && !rhs.is_empty() && (rhs.0 - self.0).len2() <= r * r
+    }
+
+    /// Check if the sphere and the box have at least one common point.
+    #[inline]
+    pub fn overlaps_aabb(self, rhs: Aabb) -> bool {
+        let closest = self.0.max(rhs.0).min(rhs.1);
+        !self.is_empty() && !rhs.is_empty() && (closest - self.0).len2() <= self.1 * self.1
+    }
+
+    /// Return a sphere enclosing the sphere transformed by an affine matrix.
+    /// The radius is scaled by the largest scale of the matrix axes, so the
+    /// sphere stays a sphere under non-uniform scaling.
+    #[inline]
+    pub fn transform(self, m: Mat4) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        let scale2 = Vec3(m.0 .0, m.0 .1, m.0 .2)
+            .len2()
+            .max(Vec3(m.1 .0, m.1 .1, m.1 .2).len2())
+            .max(Vec3(m.2 .0, m.2 .1, m.2 .2).len2());
+        Self(m.transform_point(self.0), self.1 * scale2.sqrt())
+    }
+}
+
+impl From<Aabb> for Sphere {
+    /// Return a sphere circumscribed around the box.
+    #[inline]
+    fn from(b: Aabb) -> Self {
+        if b.is_empty() {
+            Self::empty()
+        } else {
+            Self(b.center(), b.extents().len())
+        }
+    }
+}
+
+impl From<Sphere> for Aabb {
+    /// Return a box circumscribed around the sphere.
+    #[inline]
+    fn from(s: Sphere) -> Self {
+        if s.is_empty() {
+            Self::empty()
+        } else {
+            Aabb(s.0, s.0).inflate(s.1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Mat4, Quat, Sphere, Vec3};
+    use std::f32::consts::FRAC_PI_2;
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Aabb {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Aabb({:?}, {:?})", self.0, self.1)
+        }
+    }
+
+    impl Debug for Sphere {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Sphere({:?}, {:.4})", self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn from_points() {
+        let a = Aabb::from_points(&[
+            Vec3(1.0, -2.0, 0.5),
+            Vec3(-1.0, 3.0, 0.0),
+            Vec3(0.5, 0.0, 2.0),
+        ]);
+        assert_eq!(Aabb(Vec3(-1.0, -2.0, 0.0), Vec3(1.0, 3.0, 2.0)), a);
+        assert!(Aabb::from_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn union() {
+        let a = Aabb(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0));
+        let b = Aabb(Vec3(-1.0, 0.5, 0.5), Vec3(0.5, 2.0, 0.5));
+        let c = Aabb(Vec3(-1.0, 0.0, 0.0), Vec3(1.0, 2.0, 1.0));
+        assert_eq!(c, a.union(b));
+        assert_eq!(a, a.union(Aabb::empty()));
+        assert_eq!(a, Aabb::empty().union(a));
+    }
+
+    #[test]
+    fn expand() {
+        let a = Aabb::empty().expand(Vec3(1.0, 2.0, 3.0));
+        assert_eq!(Aabb(Vec3(1.0, 2.0, 3.0), Vec3(1.0, 2.0, 3.0)), a);
+        let b = a.expand(Vec3(0.0, 4.0, 3.0));
+        assert_eq!(Aabb(Vec3(0.0, 2.0, 3.0), Vec3(1.0, 4.0, 3.0)), b);
+        let c = Aabb(Vec3(-0.5, 1.5, 2.5), Vec3(1.5, 4.5, 3.5));
+        assert_eq!(c, b.inflate(0.5));
+    }
+
+    #[test]
+    fn contains() {
+        let a = Aabb(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0));
+        assert!(a.contains(Vec3(0.5, 0.5, 0.5)));
+        assert!(a.contains(Vec3(1.0, 0.0, 1.0)));
+        assert!(!a.contains(Vec3(0.5, 1.5, 0.5)));
+        assert!(!Aabb::empty().contains(Vec3(0.0, 0.0, 0.0)));
+        assert!(a.contains_aabb(Aabb(Vec3(0.2, 0.2, 0.2), Vec3(0.8, 0.8, 0.8))));
+        assert!(!a.contains_aabb(Aabb(Vec3(0.2, 0.2, 0.2), Vec3(1.8, 0.8, 0.8))));
+    }
+
+    #[test]
+    fn overlaps() {
+        let a = Aabb(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0));
+        assert!(a.overlaps(Aabb(Vec3(0.5, 0.5, 0.5), Vec3(2.0, 2.0, 2.0))));
+        assert!(a.overlaps(Aabb(Vec3(1.0, 1.0, 1.0), Vec3(2.0, 2.0, 2.0))));
+        assert!(!a.overlaps(Aabb(Vec3(1.5, 0.0, 0.0), Vec3(2.0, 1.0, 1.0))));
+        assert!(!a.overlaps(Aabb::empty()));
+    }
+
+    #[test]
+    fn transform() {
+        let a = Aabb(Vec3(0.0, 0.0, 0.0), Vec3(2.0, 1.0, 1.0));
+
+        // Rotation of a box swaps its dimensions
+        let m = Mat4::compose(
+            Vec3(1.0, 2.0, 3.0),
+            Vec3(1.0, 1.0, 1.0),
+            Quat::from_euler(0.0, 0.0, FRAC_PI_2),
+        );
+        let b = Aabb(Vec3(0.0, 2.0, 3.0), Vec3(1.0, 4.0, 4.0));
+        assert_eq!(b, a.transform(m));
+
+        // The result encloses all the transformed corners
+        let q = Quat::from_axis_angle(Vec3(0.4570, 0.8437, 0.2812), 0.7);
+        let m = Mat4::compose(Vec3(1.0, 2.0, 3.0), Vec3(1.5, 0.5, 2.0), q);
+        let b = a.transform(m);
+        let corners = [
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(2.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(2.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+            Vec3(2.0, 0.0, 1.0),
+            Vec3(0.0, 1.0, 1.0),
+            Vec3(2.0, 1.0, 1.0),
+        ];
+        let c = Aabb::from_points(&corners.map(|p| m.transform_point(p)));
+        assert_eq!(c, b);
+    }
+
+    #[test]
+    fn sphere_from_points() {
+        let a = Sphere::from_points(&[
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(-1.0, 0.0, 0.0),
+            Vec3(0.0, 0.5, 0.0),
+        ]);
+        assert_eq!(Sphere(Vec3(0.0, 0.25, 0.0), 1.0308), a);
+        assert!(Sphere::from_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn sphere_union() {
+        let a = Sphere(Vec3(0.0, 0.0, 0.0), 1.0);
+        let b = Sphere(Vec3(4.0, 0.0, 0.0), 1.0);
+        assert_eq!(Sphere(Vec3(2.0, 0.0, 0.0), 3.0), a.union(b));
+        assert_eq!(a, a.union(Sphere(Vec3(0.5, 0.0, 0.0), 0.5)));
+        assert_eq!(b, Sphere::empty().union(b));
+        assert_eq!(
+            Sphere(Vec3(1.0, 0.0, 0.0), 2.0),
+            a.expand(Vec3(3.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            Sphere(Vec3(3.0, 0.0, 0.0), 0.0),
+            Sphere::empty().expand(Vec3(3.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn sphere_contains() {
+        let a = Sphere(Vec3(1.0, 1.0, 1.0), 1.0);
+        assert!(a.contains(Vec3(1.5, 1.5, 1.5)));
+        assert!(!a.contains(Vec3(1.6, 1.6, 1.6)));
+        assert!(a.contains_sphere(Sphere(Vec3(1.5, 1.0, 1.0), 0.5)));
+        assert!(!a.contains_sphere(Sphere(Vec3(1.5, 1.0, 1.0), 0.6)));
+        assert!(!Sphere::empty().contains(Vec3(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn sphere_overlaps() {
+        let a = Sphere(Vec3(0.0, 0.0, 0.0), 1.0);
+        assert!(a.overlaps(Sphere(Vec3(1.5, 0.0, 0.0), 1.0)));
+        assert!(!a.overlaps(Sphere(Vec3(1.5, 1.5, 0.0), 1.0)));
+        assert!(a.overlaps_aabb(Aabb(Vec3(0.5, 0.5, -1.0), Vec3(2.0, 2.0, 1.0))));
+        assert!(!a.overlaps_aabb(Aabb(Vec3(0.75, 0.75, -1.0), Vec3(2.0, 2.0, 1.0))));
+        assert!(!a.overlaps_aabb(Aabb::empty()));
+    }
+
+    #[test]
+    fn sphere_transform() {
+        let a = Sphere(Vec3(1.0, 0.0, 0.0), 1.0);
+        let m = Mat4::compose(
+            Vec3(0.0, 0.0, 1.0),
+            Vec3(1.0, 3.0, 2.0),
+            Quat::from_euler(0.0, 0.0, FRAC_PI_2),
+        );
+        assert_eq!(Sphere(Vec3(0.0, 1.0, 1.0), 3.0), a.transform(m));
+    }
+
+    #[test]
+    fn conversion() {
+        let a = Aabb(Vec3(-1.0, -2.0, -2.0), Vec3(1.0, 2.0, 2.0));
+        assert_eq!(Sphere(Vec3(0.0, 0.0, 0.0), 3.0), a.into());
+        let b = Sphere(Vec3(1.0, 2.0, 3.0), 0.5);
+        assert_eq!(Aabb(Vec3(0.5, 1.5, 2.5), Vec3(1.5, 2.5, 3.5)), b.into());
+        assert!(Aabb::from(Sphere::empty()).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::Vec4;
+use crate::core::{Approx, Vec4};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -11,6 +11,38 @@ impl PartialEq for Color {
     }
 }
 
+impl Approx for Color {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        f32::from(self.0).approx_eq_eps(f32::from(rhs.0), eps)
+            && f32::from(self.1).approx_eq_eps(f32::from(rhs.1), eps)
+            && f32::from(self.2).approx_eq_eps(f32::from(rhs.2), eps)
+            && f32::from(self.3).approx_eq_eps(f32::from(rhs.3), eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        f32::from(self.0).approx_eq_rel(f32::from(rhs.0), rel)
+            && f32::from(self.1).approx_eq_rel(f32::from(rhs.1), rel)
+            && f32::from(self.2).approx_eq_rel(f32::from(rhs.2), rel)
+            && f32::from(self.3).approx_eq_rel(f32::from(rhs.3), rel)
+    }
+
+    /// Components are integers, so a unit in the last place is just 1.
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        u32::from(self.0.abs_diff(rhs.0)) <= ulps
+            && u32::from(self.1.abs_diff(rhs.1)) <= ulps
+            && u32::from(self.2.abs_diff(rhs.2)) <= ulps
+            && u32::from(self.3.abs_diff(rhs.3)) <= ulps
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self == rhs
+    }
+}
+
 impl From<Vec4> for Color {
     #[inline]
     fn from(vec: Vec4) -> Self {
@@ -18,16 +50,16 @@ impl From<Vec4> for Color {
     }
 }
 
-impl Into<Vec4> for Color {
+impl From<Color> for Vec4 {
     #[inline]
-    fn into(self) -> Vec4 {
-        Vec4(self.0 as f32, self.1 as f32, self.2 as f32, self.3 as f32)
+    fn from(c: Color) -> Self {
+        Vec4(c.0 as f32, c.1 as f32, c.2 as f32, c.3 as f32)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Color, Vec4};
+    use crate::core::{Approx, Color, Vec4};
     use std::fmt::{Debug, Formatter, Result};
 
     impl Debug for Color {
@@ -43,6 +75,20 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn approx() {
+        let a = Color(100, 200, 0, 255);
+        let b = Color(101, 199, 0, 255);
+        assert!(a != b);
+        assert!(a.approx_eq_eps(b, 1.0));
+        assert!(!a.approx_eq_eps(b, 0.5));
+        assert!(a.approx_eq_rel(b, 0.01));
+        assert!(a.approx_eq_ulps(b, 1));
+        assert!(!a.approx_eq_ulps(b, 0));
+        assert!(a.bitwise_eq(Color(100, 200, 0, 255)));
+        assert!(!a.bitwise_eq(b));
+    }
+
     #[test]
     fn copy() {
         let a = Color(20, 20, 20, 255);
@@ -75,7 +121,7 @@ mod tests {
     #[test]
     fn mul() {
         let a = Color(20, 10, 0, 255);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Color(40, 20, 0, 255);
         assert_eq!(c, Color::from(<Color as Into<Vec4>>::into(a) * b));
     }
@@ -83,7 +129,7 @@ mod tests {
     #[test]
     fn div() {
         let a = Color(20, 10, 0, 255);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Color(10, 5, 0, 127);
         assert_eq!(c, Color::from(<Color as Into<Vec4>>::into(a) / b));
     }
@@ -0,0 +1,202 @@
+use crate::core::{Aabb, Mat4, Plane, Sphere, Vec3, Vec4};
+
+/// Relation of a bounding volume to a frustum.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Intersection {
+    /// The volume is entirely inside.
+    Inside,
+    /// The volume is entirely outside and can be culled.
+    Outside,
+    /// The volume crosses the boundary, so it is partially visible. The
+    /// volume may also be reported so while being outside near a corner,
+    /// as the planes are tested one by one.
+    Intersecting,
+}
+
+/// Frustum is a viewing volume bounded by six planes facing inwards, in
+/// the order of left, right, bottom, top, near and far.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Frustum(pub [Plane; 6]);
+
+impl PartialEq for Frustum {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0
+    }
+}
+
+impl From<Mat4> for Frustum {
+    #[inline]
+    fn from(m: Mat4) -> Self {
+        Self::from_matrix(m)
+    }
+}
+
+impl Frustum {
+    /// Return a frustum of the projection matrix, or of the combined view
+    /// and projection matrix, in which case the planes are in the World
+    /// space. The planes are extracted with the Gribb/Hartmann method, as
+    /// sums and differences of the matrix columns that produce the clip
+    /// space coordinates, and are normalized.
+    #[inline]
+    pub fn from_matrix(m: Mat4) -> Self {
+        let x = Vec4(m.0 .0, m.1 .0, m.2 .0, m.3 .0);
+        let y = Vec4(m.0 .1, m.1 .1, m.2 .1, m.3 .1);
+        let z = Vec4(m.0 .2, m.1 .2, m.2 .2, m.3 .2);
+        let w = Vec4(m.0 .3, m.1 .3, m.2 .3, m.3 .3);
+        Self([
+            Plane::from(w + x).normalize(),
+            Plane::from(w - x).normalize(),
+            Plane::from(w + y).normalize(),
+            Plane::from(w - y).normalize(),
+            Plane::from(w + z).normalize(),
+            Plane::from(w - z).normalize(),
+        ])
+    }
+
+    /// Check if the point is inside the frustum or on its boundary.
+    #[inline]
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.0.iter().all(|plane| plane.distance(p) >= 0.0)
+    }
+
+    /// Return where the box is relative to the frustum.
+    #[inline]
+    pub fn intersects_aabb(&self, b: Aabb) -> Intersection {
+        if b.is_empty() {
+            return Intersection::Outside;
+        }
+        let c = b.center();
+        let e = b.extents();
+        self.test(|plane| {
+            let n = plane.0;
+            let r = e.0 * n.0.abs() + e.1 * n.1.abs() + e.2 * n.2.abs();
+            (plane.distance(c), r)
+        })
+    }
+
+    /// Return where the sphere is relative to the frustum.
+    #[inline]
+    pub fn intersects_sphere(&self, s: Sphere) -> Intersection {
+        if s.is_empty() {
+            return Intersection::Outside;
+        }
+        self.test(|plane| (plane.distance(s.0), s.1))
+    }
+
+    /// Classify a volume by the signed distance from each plane to its
+    /// center, and its radius along the plane normal.
+    #[inline]
+    fn test<F: Fn(Plane) -> (f32, f32)>(&self, f: F) -> Intersection {
+        let mut result = Intersection::Inside;
+        for plane in self.0 {
+            let (d, r) = f(plane);
+            if d < -r {
+                return Intersection::Outside;
+            }
+            if d < r {
+                result = Intersection::Intersecting;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Frustum, Intersection, Mat4, Plane, Sphere, Vec3};
+    use std::f32::consts::FRAC_PI_2;
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Frustum {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            f.debug_tuple("Frustum").field(&self.0).finish()
+        }
+    }
+
+    #[test]
+    fn from_matrix() {
+        let a = Frustum::from_matrix(Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0));
+        let b = Frustum([
+            Plane(Vec3(0.7071, 0.0, -0.7071), 0.0),
+            Plane(Vec3(-0.7071, 0.0, -0.7071), 0.0),
+            Plane(Vec3(0.0, 0.7071, -0.7071), 0.0),
+            Plane(Vec3(0.0, -0.7071, -0.7071), 0.0),
+            Plane(Vec3(0.0, 0.0, -1.0), -1.0),
+            Plane(Vec3(0.0, 0.0, 1.0), 10.0),
+        ]);
+        assert_eq!(a, b);
+
+        let a = Frustum::from_matrix(Mat4::orthographic(-2.0, 2.0, -1.0, 1.0, 0.5, 5.0));
+        let b = Frustum([
+            Plane(Vec3(1.0, 0.0, 0.0), 2.0),
+            Plane(Vec3(-1.0, 0.0, 0.0), 2.0),
+            Plane(Vec3(0.0, 1.0, 0.0), 1.0),
+            Plane(Vec3(0.0, -1.0, 0.0), 1.0),
+            Plane(Vec3(0.0, 0.0, -1.0), -0.5),
+            Plane(Vec3(0.0, 0.0, 1.0), 5.0),
+        ]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn contains_point() {
+        let a = Frustum::from_matrix(Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0));
+        assert!(a.contains_point(Vec3(0.0, 0.0, -5.0)));
+        assert!(a.contains_point(Vec3(4.9, -4.9, -5.0)));
+        assert!(!a.contains_point(Vec3(5.1, 0.0, -5.0)));
+        assert!(!a.contains_point(Vec3(0.0, 0.0, 5.0)));
+        assert!(!a.contains_point(Vec3(0.0, 0.0, -0.5)));
+        assert!(!a.contains_point(Vec3(0.0, 0.0, -10.5)));
+    }
+
+    #[test]
+    fn view_projection() {
+        // Camera moved back along z looks at the origin, so the planes are
+        // moved along with it.
+        let view = Mat4::look_at(
+            Vec3(0.0, 0.0, 5.0),
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+        )
+        .inverse_rigid();
+        let a = Frustum::from_matrix(view * Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0));
+        assert!(a.contains_point(Vec3(0.0, 0.0, 0.0)));
+        assert!(a.contains_point(Vec3(0.0, 0.0, -4.9)));
+        assert!(!a.contains_point(Vec3(0.0, 0.0, -5.1)));
+        assert!(!a.contains_point(Vec3(0.0, 0.0, 4.5)));
+    }
+
+    #[test]
+    fn intersects_aabb() {
+        let a = Frustum::from_matrix(Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0));
+        let b = Aabb(Vec3(-1.0, -1.0, -6.0), Vec3(1.0, 1.0, -4.0));
+        assert_eq!(Intersection::Inside, a.intersects_aabb(b));
+        let b = Aabb(Vec3(4.0, -1.0, -6.0), Vec3(6.0, 1.0, -4.0));
+        assert_eq!(Intersection::Intersecting, a.intersects_aabb(b));
+        let b = Aabb(Vec3(-1.0, -1.0, -12.0), Vec3(1.0, 1.0, -9.0));
+        assert_eq!(Intersection::Intersecting, a.intersects_aabb(b));
+        let b = Aabb(Vec3(7.0, -1.0, -6.0), Vec3(9.0, 1.0, -4.0));
+        assert_eq!(Intersection::Outside, a.intersects_aabb(b));
+        let b = Aabb(Vec3(-1.0, -1.0, 1.0), Vec3(1.0, 1.0, 2.0));
+        assert_eq!(Intersection::Outside, a.intersects_aabb(b));
+        assert_eq!(Intersection::Outside, a.intersects_aabb(Aabb::empty()));
+    }
+
+    #[test]
+    fn intersects_sphere() {
+        let a = Frustum::from_matrix(Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0));
+        let b = Sphere(Vec3(0.0, 0.0, -5.0), 1.0);
+        assert_eq!(Intersection::Inside, a.intersects_sphere(b));
+        let b = Sphere(Vec3(5.0, 0.0, -5.0), 1.0);
+        assert_eq!(Intersection::Intersecting, a.intersects_sphere(b));
+        let b = Sphere(Vec3(0.0, 0.0, -0.5), 1.0);
+        assert_eq!(Intersection::Intersecting, a.intersects_sphere(b));
+        let b = Sphere(Vec3(0.0, 7.0, -5.0), 1.0);
+        assert_eq!(Intersection::Outside, a.intersects_sphere(b));
+        let b = Sphere(Vec3(0.0, 0.0, -12.0), 1.0);
+        assert_eq!(Intersection::Outside, a.intersects_sphere(b));
+        assert_eq!(Intersection::Outside, a.intersects_sphere(Sphere::empty()));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::core::{Approx, Mat3, Vec2, Vec3};
+use std::ops::Mul;
+
+/// Mat2x3 is a 2D affine transformation, stored as three columns: images of
+/// the x and y axes followed by a translation. It is a cheaper alternative to
+/// Mat3 for rotating, scaling and moving sprites on the CPU, as the last row
+/// of an affine matrix is always (0, 0, 1) and therefore is not stored.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Mat2x3(pub Vec2, pub Vec2, pub Vec2);
+
+impl PartialEq for Mat2x3 {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1 == rhs.1 && self.2 == rhs.2
+    }
+}
+
+impl Approx for Mat2x3 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0) && self.1.bitwise_eq(rhs.1) && self.2.bitwise_eq(rhs.2)
+    }
+}
+
+impl Default for Mat2x3 {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Mat2x3> for Mat3 {
+    /// Return a 3x3 matrix of the same transformation, suitable to be passed
+    /// to a shader as a `mat3` uniform.
+    #[inline]
+    fn from(m: Mat2x3) -> Self {
+        Mat3(
+            Vec3(m.0 .0, m.0 .1, 0.0),
+            Vec3(m.1 .0, m.1 .1, 0.0),
+            Vec3(m.2 .0, m.2 .1, 1.0),
+        )
+    }
+}
+
+impl Mul<Mat2x3> for Mat2x3 {
+    type Output = Self;
+
+    /// Return a transformation, that applies `self` first and `rhs` after,
+    /// which follows the same order as Mat4 multiplication.
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(
+            rhs.transform_vector(self.0),
+            rhs.transform_vector(self.1),
+            rhs * self.2,
+        )
+    }
+}
+
+impl Mul<Vec2> for Mat2x3 {
+    type Output = Vec2;
+
+    /// Return a position vector transformed by the matrix.
+    #[inline]
+    fn mul(self, rhs: Vec2) -> Vec2 {
+        Vec2(
+            rhs.0 * self.0 .0 + rhs.1 * self.1 .0 + self.2 .0,
+            rhs.0 * self.0 .1 + rhs.1 * self.1 .1 + self.2 .1,
+        )
+    }
+}
+
+impl Mat2x3 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::identity()
+    }
+
+    /// Return a transformation that keeps everything in place.
+    #[inline]
+    pub fn identity() -> Self {
+        Self(Vec2(1.0, 0.0), Vec2(0.0, 1.0), Vec2(0.0, 0.0))
+    }
+
+    /// Return a translation matrix.
+    #[inline]
+    pub fn translation(t: Vec2) -> Self {
+        Self(Vec2(1.0, 0.0), Vec2(0.0, 1.0), t)
+    }
+
+    /// Return a scaling matrix.
+    #[inline]
+    pub fn scaling(s: Vec2) -> Self {
+        Self(Vec2(s.0, 0.0), Vec2(0.0, s.1), Vec2(0.0, 0.0))
+    }
+
+    /// Return a counter-clockwise rotation matrix.
+    #[inline]
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self(Vec2(cos, sin), Vec2(-sin, cos), Vec2(0.0, 0.0))
+    }
+
+    /// Return a resulting matrix of scaling, rotation and translation,
+    /// applied in that order, which is the usual sprite transformation.
+    #[inline]
+    pub fn compose(t: Vec2, s: Vec2, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self(Vec2(cos * s.0, sin * s.0), Vec2(-sin * s.1, cos * s.1), t)
+    }
+
+    /// Return a direction vector transformed by the matrix, which unlike a
+    /// position is not affected by the translation.
+    #[inline]
+    pub fn transform_vector(self, v: Vec2) -> Vec2 {
+        Vec2(
+            v.0 * self.0 .0 + v.1 * self.1 .0,
+            v.0 * self.0 .1 + v.1 * self.1 .1,
+        )
+    }
+
+    /// Determinant of the linear part of the transformation.
+    #[inline]
+    pub fn det(self) -> f32 {
+        self.0.cross(self.1)
+    }
+
+    /// Return an inverse transformation
+    #[inline]
+    pub fn inverse(self) -> Self {
+        let d = self.det();
+        let x = Vec2(self.1 .1, -self.0 .1) / d;
+        let y = Vec2(-self.1 .0, self.0 .0) / d;
+        Self(x, y, Vec2(0.0, 0.0) - x * self.2 .0 - y * self.2 .1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Mat2x3, Mat3, Vec2, Vec3};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_3};
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Mat2x3 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Mat2x3({:?}, {:?}, {:?})", self.0, self.1, self.2)
+        }
+    }
+
+    #[test]
+    fn equal() {
+        let a = Mat2x3(Vec2(1.0, 0.0), Vec2(0.0, 1.0), Vec2(2.0, 3.0));
+        let b = Mat2x3(Vec2(1.0, 0.0), Vec2(0.0, 1.0), Vec2(2.0, 3.0));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn identity() {
+        let a = Mat2x3(Vec2(1.0, 0.0), Vec2(0.0, 1.0), Vec2(0.0, 0.0));
+        assert_eq!(Mat2x3::identity(), a);
+        assert_eq!(Vec2(1.2, 3.4), a * Vec2(1.2, 3.4));
+    }
+
+    #[test]
+    fn translation() {
+        let a = Mat2x3::translation(Vec2(1.0, -2.0));
+        assert_eq!(Vec2(2.2, 1.4), a * Vec2(1.2, 3.4));
+        assert_eq!(Vec2(1.2, 3.4), a.transform_vector(Vec2(1.2, 3.4)));
+    }
+
+    #[test]
+    fn scaling() {
+        let a = Mat2x3::scaling(Vec2(2.0, 0.5));
+        assert_eq!(Vec2(2.4, 1.7), a * Vec2(1.2, 3.4));
+    }
+
+    #[test]
+    fn rotation() {
+        let a = Mat2x3::rotation(FRAC_PI_3);
+        let b = Mat2x3(Vec2(0.5, 0.866), Vec2(-0.866, 0.5), Vec2(0.0, 0.0));
+        assert_eq!(a, b);
+
+        // Rotate a position vector counter-clockwise
+        let a = Mat2x3::rotation(FRAC_PI_2) * Vec2(1.2, 0.2);
+        assert_eq!(Vec2(-0.2, 1.2), a);
+    }
+
+    #[test]
+    fn mul() {
+        let t = Mat2x3::translation(Vec2(1.0, 2.0));
+        let r = Mat2x3::rotation(FRAC_PI_2);
+
+        // Translation is applied first, then the rotation
+        assert_eq!(Vec2(-2.0, 2.0), (t * r) * Vec2(1.0, 0.0));
+        assert_eq!(Vec2(1.0, 3.0), (r * t) * Vec2(1.0, 0.0));
+    }
+
+    #[test]
+    fn compose() {
+        let t = Vec2(1.2, 1.4);
+        let s = Vec2(1.8, 2.0);
+        let a = Mat2x3::compose(t, s, FRAC_PI_3);
+        let b = Mat2x3::scaling(s) * Mat2x3::rotation(FRAC_PI_3) * Mat2x3::translation(t);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn inverse() {
+        let a = Mat2x3::compose(Vec2(1.2, 1.4), Vec2(1.8, 2.0), FRAC_PI_3);
+        assert_eq!(Mat2x3::identity(), a * a.inverse());
+        assert_eq!(Mat2x3::identity(), a.inverse() * a);
+        assert_eq!(Vec2(0.3, -0.7), a.inverse() * (a * Vec2(0.3, -0.7)));
+    }
+
+    #[test]
+    fn into_mat3() {
+        let a = Mat2x3(Vec2(1.0, 2.0), Vec2(3.0, 4.0), Vec2(5.0, 6.0));
+        let b = Mat3(
+            Vec3(1.0, 2.0, 0.0),
+            Vec3(3.0, 4.0, 0.0),
+            Vec3(5.0, 6.0, 1.0),
+        );
+        assert_eq!(b, a.into());
+    }
+}
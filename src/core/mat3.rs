@@ -0,0 +1,326 @@
+use crate::core::{Approx, Mat4, Vec3};
+use std::ops::{Div, Mul};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Mat3(pub Vec3, pub Vec3, pub Vec3);
+
+impl PartialEq for Mat3 {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1 == rhs.1 && self.2 == rhs.2
+    }
+}
+
+impl Approx for Mat3 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0) && self.1.bitwise_eq(rhs.1) && self.2.bitwise_eq(rhs.2)
+    }
+}
+
+impl Default for Mat3 {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Mat4> for Mat3 {
+    /// Return the upper-left 3x3 part of the matrix, dropping translation
+    /// and projection components.
+    #[inline]
+    fn from(m: Mat4) -> Self {
+        Self(
+            Vec3(m.0 .0, m.0 .1, m.0 .2),
+            Vec3(m.1 .0, m.1 .1, m.1 .2),
+            Vec3(m.2 .0, m.2 .1, m.2 .2),
+        )
+    }
+}
+
+impl Mul<Mat3> for Mat3 {
+    type Output = Self;
+
+    /// Return a dot product of two 3 dimensional matrices
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(
+            Vec3(
+                self.0 .0 * rhs.0 .0 + self.0 .1 * rhs.1 .0 + self.0 .2 * rhs.2 .0,
+                self.0 .0 * rhs.0 .1 + self.0 .1 * rhs.1 .1 + self.0 .2 * rhs.2 .1,
+                self.0 .0 * rhs.0 .2 + self.0 .1 * rhs.1 .2 + self.0 .2 * rhs.2 .2,
+            ),
+            Vec3(
+                self.1 .0 * rhs.0 .0 + self.1 .1 * rhs.1 .0 + self.1 .2 * rhs.2 .0,
+                self.1 .0 * rhs.0 .1 + self.1 .1 * rhs.1 .1 + self.1 .2 * rhs.2 .1,
+                self.1 .0 * rhs.0 .2 + self.1 .1 * rhs.1 .2 + self.1 .2 * rhs.2 .2,
+            ),
+            Vec3(
+                self.2 .0 * rhs.0 .0 + self.2 .1 * rhs.1 .0 + self.2 .2 * rhs.2 .0,
+                self.2 .0 * rhs.0 .1 + self.2 .1 * rhs.1 .1 + self.2 .2 * rhs.2 .1,
+                self.2 .0 * rhs.0 .2 + self.2 .1 * rhs.1 .2 + self.2 .2 * rhs.2 .2,
+            ),
+        )
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    /// Return a vector transformed by the matrix.
+    #[inline]
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        Vec3(self.0.dot(rhs), self.1.dot(rhs), self.2.dot(rhs))
+    }
+}
+
+impl Mul<f32> for Mat3 {
+    type Output = Self;
+
+    /// Return a result of multiplication of the matrix by a scalar.
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self(self.0 * rhs, self.1 * rhs, self.2 * rhs)
+    }
+}
+
+impl Div<f32> for Mat3 {
+    type Output = Self;
+
+    /// Return a result of division of the matrix by a scalar.
+    #[inline]
+    fn div(self, rhs: f32) -> Self {
+        Self(self.0 / rhs, self.1 / rhs, self.2 / rhs)
+    }
+}
+
+impl Mat3 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::identity()
+    }
+
+    #[inline]
+    pub fn det(self) -> f32 {
+        self.0.dot(self.1.cross(self.2))
+    }
+
+    /// Return a 3x3 matrix with diagonal elements set to 1 and the rest
+    /// set to 0, which correspond with a math Identity matrix definition.
+    #[inline]
+    pub fn identity() -> Self {
+        Self(
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Return a transposed matrix
+    #[inline]
+    pub fn transpose(self) -> Self {
+        Self(
+            Vec3(self.0 .0, self.1 .0, self.2 .0),
+            Vec3(self.0 .1, self.1 .1, self.2 .1),
+            Vec3(self.0 .2, self.1 .2, self.2 .2),
+        )
+    }
+
+    /// Return an inverse matrix
+    #[inline]
+    pub fn inverse(self) -> Self {
+        let d = self.det();
+        Self(
+            self.1.cross(self.2),
+            self.2.cross(self.0),
+            self.0.cross(self.1),
+        )
+        .transpose()
+            / d
+    }
+}
+
+impl Mat4 {
+    /// Return a matrix to transform normal vectors with, which is the
+    /// inverse transposed upper-left 3x3 part of the matrix. Unlike the
+    /// matrix itself it keeps normals perpendicular to the surface under
+    /// non-uniform scaling.
+    #[inline]
+    pub fn normal_matrix(self) -> Mat3 {
+        Mat3::from(self).inverse().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Mat3, Mat4, Vec3, Vec4};
+    use std::f32::consts::FRAC_PI_3;
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Mat3 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Mat3({:?}, {:?}, {:?})", self.0, self.1, self.2)
+        }
+    }
+
+    #[test]
+    fn equal() {
+        let a = Mat3(
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        let b = Mat3(
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn copy() {
+        let a = Mat3(
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        let b = a;
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn dot_product() {
+        let a = Mat3(
+            Vec3(2.0, 4.0, 8.0),
+            Vec3(8.0, 2.0, 4.0),
+            Vec3(4.0, 8.0, 2.0),
+        );
+        let b = Mat3(
+            Vec3(1.0, 2.0, 3.0),
+            Vec3(0.0, 1.0, 2.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        let c = Mat3(
+            Vec3(2.0, 8.0, 22.0),
+            Vec3(8.0, 18.0, 32.0),
+            Vec3(4.0, 16.0, 30.0),
+        );
+        assert_eq!(c, a * b);
+    }
+
+    #[test]
+    fn transform() {
+        let a = Mat3(
+            Vec3(2.0, 4.0, 8.0),
+            Vec3(8.0, 2.0, 4.0),
+            Vec3(4.0, 8.0, 2.0),
+        );
+        let v = Vec3(1.0, 0.5, 0.25);
+        assert_eq!(Vec3(6.0, 10.0, 8.5), a * v);
+    }
+
+    #[test]
+    fn det() {
+        let a = Mat3(
+            Vec3(0.1, -2.3, 1.1),
+            Vec3(-0.3, -1.2, 1.2),
+            Vec3(2.3, 1.1, -2.0),
+        );
+        assert_eq!(a.det(), -2.1869996);
+    }
+
+    #[test]
+    fn identity() {
+        let a = Mat3(
+            Vec3(1.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        assert_eq!(Mat3::identity(), a);
+    }
+
+    #[test]
+    fn from_mat4() {
+        let a = Mat4(
+            Vec4(2.0, 4.0, 8.0, 16.0),
+            Vec4(32.0, 64.0, 128.0, 256.0),
+            Vec4(512.0, 1024.0, 2048.0, 4096.0),
+            Vec4(8192.0, 16384.0, 32768.0, 65536.0),
+        );
+        let b = Mat3(
+            Vec3(2.0, 4.0, 8.0),
+            Vec3(32.0, 64.0, 128.0),
+            Vec3(512.0, 1024.0, 2048.0),
+        );
+        assert_eq!(b, a.into());
+    }
+
+    #[test]
+    fn transpose() {
+        let a = Mat3(
+            Vec3(2.0, 4.0, 8.0),
+            Vec3(16.0, 32.0, 64.0),
+            Vec3(128.0, 256.0, 512.0),
+        );
+        let b = Mat3(
+            Vec3(2.0, 16.0, 128.0),
+            Vec3(4.0, 32.0, 256.0),
+            Vec3(8.0, 64.0, 512.0),
+        );
+        assert_eq!(b, a.transpose());
+    }
+
+    #[test]
+    fn inverse() {
+        let a = Mat3(
+            Vec3(-2.3, 4.5, -0.7),
+            Vec3(-1.3, -2.1, 4.8),
+            Vec3(5.4, -0.2, -1.8),
+        );
+        let b = Mat3(
+            Vec3(0.0544, 0.0946, 0.2311),
+            Vec3(0.2708, 0.0909, 0.1372),
+            Vec3(0.1332, 0.2737, 0.1226),
+        );
+        assert_eq!(a.inverse(), b);
+        assert_eq!(a * a.inverse(), Mat3::identity());
+    }
+
+    #[test]
+    fn normal_matrix() {
+        let a = Mat4::scaling(Vec3(2.0, 4.0, 1.25)).translate(Vec3(1.0, 2.0, 3.0));
+        let b = Mat3(
+            Vec3(0.5, 0.0, 0.0),
+            Vec3(0.0, 0.25, 0.0),
+            Vec3(0.0, 0.0, 0.8),
+        );
+        assert_eq!(a.normal_matrix(), b);
+
+        // Pure rotation matrix is a normal matrix of its own
+        let a = Mat4::axis_rotation(Vec3(0.4570, 0.8437, 0.2812), FRAC_PI_3);
+        assert_eq!(a.normal_matrix(), Mat3::from(a));
+    }
+}
@@ -1,7 +1,16 @@
-use crate::core::{Vec3, Vec4};
+#[cfg(feature = "simd")]
+use crate::core::simd;
+use crate::core::{Approx, Mat3, Quat, Vec3, Vec4};
 use std::f32::consts::FRAC_PI_2;
 use std::ops::{Div, Mul};
 
+/// Default threshold of the determinant absolute value relative to the
+/// product of the lengths of the upper-left 3x3 columns, below which a
+/// matrix is considered to be singular by `Mat4::try_inverse`. The ratio
+/// doesn't depend on a scale of the axes, nor on the translation of an
+/// affine matrix, it is 1 for orthogonal axes and 0 for dependent ones.
+pub const SINGULAR_DET: f32 = 1e-6;
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct Mat4(pub Vec4, pub Vec4, pub Vec4, pub Vec4);
@@ -13,12 +22,127 @@ impl PartialEq for Mat4 {
     }
 }
 
+impl Approx for Mat4 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+            && self.3.approx_eq_eps(rhs.3, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+            && self.3.approx_eq_rel(rhs.3, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+            && self.3.approx_eq_ulps(rhs.3, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0)
+            && self.1.bitwise_eq(rhs.1)
+            && self.2.bitwise_eq(rhs.2)
+            && self.3.bitwise_eq(rhs.3)
+    }
+}
+
+impl Default for Mat4 {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 impl Mul<Mat4> for Mat4 {
     type Output = Self;
 
     /// Return a dot product of two 4 dimensional matrices
     #[inline]
     fn mul(self, rhs: Self) -> Self {
+        #[cfg(feature = "simd")]
+        {
+            simd::mat4_mul(self, rhs)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.scalar_mul(rhs)
+        }
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    /// Return a vector multiplied by the matrix with its fields taken as
+    /// rows, which is the transposed transformation of `transform_point`
+    /// and of the shaders, as the fields are columns in the GPU memory.
+    #[inline]
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        #[cfg(feature = "simd")]
+        {
+            simd::mat4_transform(self, rhs)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.scalar_transform(rhs)
+        }
+    }
+}
+
+impl Mul<Vec3> for Mat4 {
+    type Output = Vec3;
+
+    /// Return a vector transformed by the matrix, a short cut of
+    /// Vec4 -> Vec3 and backwards conversion. Like `Mul<Vec4>` it takes the
+    /// fields as rows, so it doesn't translate a matrix made by `compose`,
+    /// use `transform_point` to transform positions the way shaders do.
+    #[inline]
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        let w = rhs.0 * self.3 .0 + rhs.1 * self.3 .1 + rhs.2 * self.3 .2 + self.3 .3;
+        Vec3(
+            rhs.0 * self.0 .0 + rhs.1 * self.0 .1 + rhs.2 * self.0 .2 + self.0 .3,
+            rhs.0 * self.1 .0 + rhs.1 * self.1 .1 + rhs.2 * self.1 .2 + self.1 .3,
+            rhs.0 * self.2 .0 + rhs.1 * self.2 .1 + rhs.2 * self.2 .2 + self.2 .3,
+        ) / w
+    }
+}
+
+impl Mul<f32> for Mat4 {
+    type Output = Self;
+
+    /// Return a result of multiplication of the matrix by a scalar.
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self(self.0 * rhs, self.1 * rhs, self.2 * rhs, self.3 * rhs)
+    }
+}
+
+impl Div<f32> for Mat4 {
+    type Output = Self;
+
+    /// Return a result of division of the matrix by a scalar.
+    #[inline]
+    fn div(self, rhs: f32) -> Self {
+        Self(self.0 / rhs, self.1 / rhs, self.2 / rhs, self.3 / rhs)
+    }
+}
+
+/// Scalar implementation of the heavy arithmetic, used unless the `simd`
+/// feature is enabled, in which case it is kept as a reference for tests.
+#[cfg_attr(all(feature = "simd", not(test)), allow(dead_code))]
+impl Mat4 {
+    #[inline]
+    pub(crate) fn scalar_mul(self, rhs: Self) -> Self {
         Self(
             Vec4(
                 self.0 .0 * rhs.0 .0
@@ -94,14 +218,9 @@ impl Mul<Mat4> for Mat4 {
             ),
         )
     }
-}
-
-impl Mul<Vec4> for Mat4 {
-    type Output = Vec4;
 
-    /// Return a vector transformed by the matrix.
     #[inline]
-    fn mul(self, rhs: Vec4) -> Vec4 {
+    pub(crate) fn scalar_transform(self, rhs: Vec4) -> Vec4 {
         Vec4(
             rhs.0 * self.0 .0 + rhs.1 * self.0 .1 + rhs.2 * self.0 .2 + rhs.3 * self.0 .3,
             rhs.0 * self.1 .0 + rhs.1 * self.1 .1 + rhs.2 * self.1 .2 + rhs.3 * self.1 .3,
@@ -109,52 +228,9 @@ impl Mul<Vec4> for Mat4 {
             rhs.0 * self.3 .0 + rhs.1 * self.3 .1 + rhs.2 * self.3 .2 + rhs.3 * self.3 .3,
         )
     }
-}
-
-impl Mul<Vec3> for Mat4 {
-    type Output = Vec3;
 
-    /// Return a vector transformed by the matrix, a short cut of  
-    /// Vec4 -> Vec3 and backwards conversion.
     #[inline]
-    fn mul(self, rhs: Vec3) -> Vec3 {
-        let w = rhs.0 * self.3 .0 + rhs.1 * self.3 .1 + rhs.2 * self.3 .2 + self.3 .3;
-        Vec3(
-            rhs.0 * self.0 .0 + rhs.1 * self.0 .1 + rhs.2 * self.0 .2 + self.0 .3,
-            rhs.0 * self.1 .0 + rhs.1 * self.1 .1 + rhs.2 * self.1 .2 + self.1 .3,
-            rhs.0 * self.2 .0 + rhs.1 * self.2 .1 + rhs.2 * self.2 .2 + self.2 .3,
-        ) / w
-    }
-}
-
-impl Mul<f32> for Mat4 {
-    type Output = Self;
-
-    /// Return a result of multiplication of the matrix by a scalar.
-    #[inline]
-    fn mul(self, rhs: f32) -> Self {
-        Self(self.0 * rhs, self.1 * rhs, self.2 * rhs, self.3 * rhs)
-    }
-}
-
-impl Div<f32> for Mat4 {
-    type Output = Self;
-
-    /// Return a result of division of the matrix by a scalar.
-    #[inline]
-    fn div(self, rhs: f32) -> Self {
-        Self(self.0 / rhs, self.1 / rhs, self.2 / rhs, self.3 / rhs)
-    }
-}
-
-impl Mat4 {
-    #[inline]
-    pub fn new() -> Self {
-        Self::identity()
-    }
-
-    #[inline]
-    pub fn det(self) -> f32 {
+    pub(crate) fn scalar_det(self) -> f32 {
         self.0 .0 * self.1 .1 * self.2 .2 * self.3 .3
             + self.0 .0 * self.1 .2 * self.2 .3 * self.3 .1
             + self.0 .0 * self.1 .3 * self.2 .1 * self.3 .2
@@ -181,43 +257,172 @@ impl Mat4 {
             + self.0 .1 * self.1 .3 * self.2 .2 * self.3 .0
     }
 
+    #[inline]
+    pub(crate) fn scalar_inverse(self) -> Self {
+        let d = self.scalar_det();
+        Self(
+            Vec4(
+                self.1 .1 * self.2 .2 * self.3 .3
+                    + self.1 .2 * self.2 .3 * self.3 .1
+                    + self.1 .3 * self.2 .1 * self.3 .2
+                    - self.1 .3 * self.2 .2 * self.3 .1
+                    - self.1 .2 * self.2 .1 * self.3 .3
+                    - self.1 .1 * self.2 .3 * self.3 .2,
+                -self.0 .1 * self.2 .2 * self.3 .3
+                    - self.0 .2 * self.2 .3 * self.3 .1
+                    - self.0 .3 * self.2 .1 * self.3 .2
+                    + self.0 .3 * self.2 .2 * self.3 .1
+                    + self.0 .2 * self.2 .1 * self.3 .3
+                    + self.0 .1 * self.2 .3 * self.3 .2,
+                self.0 .1 * self.1 .2 * self.3 .3
+                    + self.0 .2 * self.1 .3 * self.3 .1
+                    + self.0 .3 * self.1 .1 * self.3 .2
+                    - self.0 .3 * self.1 .2 * self.3 .1
+                    - self.0 .2 * self.1 .1 * self.3 .3
+                    - self.0 .1 * self.1 .3 * self.3 .2,
+                -self.0 .1 * self.1 .2 * self.2 .3
+                    - self.0 .2 * self.1 .3 * self.2 .1
+                    - self.0 .3 * self.1 .1 * self.2 .2
+                    + self.0 .3 * self.1 .2 * self.2 .1
+                    + self.0 .2 * self.1 .1 * self.2 .3
+                    + self.0 .1 * self.1 .3 * self.2 .2,
+            ),
+            Vec4(
+                -self.1 .0 * self.2 .2 * self.3 .3
+                    - self.1 .2 * self.2 .3 * self.3 .0
+                    - self.1 .3 * self.2 .0 * self.3 .2
+                    + self.1 .3 * self.2 .2 * self.3 .0
+                    + self.1 .2 * self.2 .0 * self.3 .3
+                    + self.1 .0 * self.2 .3 * self.3 .2,
+                self.0 .0 * self.2 .2 * self.3 .3
+                    + self.0 .2 * self.2 .3 * self.3 .0
+                    + self.0 .3 * self.2 .0 * self.3 .2
+                    - self.0 .3 * self.2 .2 * self.3 .0
+                    - self.0 .2 * self.2 .0 * self.3 .3
+                    - self.0 .0 * self.2 .3 * self.3 .2,
+                -self.0 .0 * self.1 .2 * self.3 .3
+                    - self.0 .2 * self.1 .3 * self.3 .0
+                    - self.0 .3 * self.1 .0 * self.3 .2
+                    + self.0 .3 * self.1 .2 * self.3 .0
+                    + self.0 .2 * self.1 .0 * self.3 .3
+                    + self.0 .0 * self.1 .3 * self.3 .2,
+                self.0 .0 * self.1 .2 * self.2 .3
+                    + self.0 .2 * self.1 .3 * self.2 .0
+                    + self.0 .3 * self.1 .0 * self.2 .2
+                    - self.0 .3 * self.1 .2 * self.2 .0
+                    - self.0 .2 * self.1 .0 * self.2 .3
+                    - self.0 .0 * self.1 .3 * self.2 .2,
+            ),
+            Vec4(
+                self.1 .0 * self.2 .1 * self.3 .3
+                    + self.1 .1 * self.2 .3 * self.3 .0
+                    + self.1 .3 * self.2 .0 * self.3 .1
+                    - self.1 .3 * self.2 .1 * self.3 .0
+                    - self.1 .1 * self.2 .0 * self.3 .3
+                    - self.1 .0 * self.2 .3 * self.3 .1,
+                -self.0 .0 * self.2 .1 * self.3 .3
+                    - self.0 .1 * self.2 .3 * self.3 .0
+                    - self.0 .3 * self.2 .0 * self.3 .1
+                    + self.0 .3 * self.2 .1 * self.3 .0
+                    + self.0 .1 * self.2 .0 * self.3 .3
+                    + self.0 .0 * self.2 .3 * self.3 .1,
+                self.0 .0 * self.1 .1 * self.3 .3
+                    + self.0 .1 * self.1 .3 * self.3 .0
+                    + self.0 .3 * self.1 .0 * self.3 .1
+                    - self.0 .3 * self.1 .1 * self.3 .0
+                    - self.0 .1 * self.1 .0 * self.3 .3
+                    - self.0 .0 * self.1 .3 * self.3 .1,
+                -self.0 .0 * self.1 .1 * self.2 .3
+                    - self.0 .1 * self.1 .3 * self.2 .0
+                    - self.0 .3 * self.1 .0 * self.2 .1
+                    + self.0 .3 * self.1 .1 * self.2 .0
+                    + self.0 .1 * self.1 .0 * self.2 .3
+                    + self.0 .0 * self.1 .3 * self.2 .1,
+            ),
+            Vec4(
+                -self.1 .0 * self.2 .1 * self.3 .2
+                    - self.1 .1 * self.2 .2 * self.3 .0
+                    - self.1 .2 * self.2 .0 * self.3 .1
+                    + self.1 .2 * self.2 .1 * self.3 .0
+                    + self.1 .1 * self.2 .0 * self.3 .2
+                    + self.1 .0 * self.2 .2 * self.3 .1,
+                self.0 .0 * self.2 .1 * self.3 .2
+                    + self.0 .1 * self.2 .2 * self.3 .0
+                    + self.0 .2 * self.2 .0 * self.3 .1
+                    - self.0 .2 * self.2 .1 * self.3 .0
+                    - self.0 .1 * self.2 .0 * self.3 .2
+                    - self.0 .0 * self.2 .2 * self.3 .1,
+                -self.0 .0 * self.1 .1 * self.3 .2
+                    - self.0 .1 * self.1 .2 * self.3 .0
+                    - self.0 .2 * self.1 .0 * self.3 .1
+                    + self.0 .2 * self.1 .1 * self.3 .0
+                    + self.0 .1 * self.1 .0 * self.3 .2
+                    + self.0 .0 * self.1 .2 * self.3 .1,
+                self.0 .0 * self.1 .1 * self.2 .2
+                    + self.0 .1 * self.1 .2 * self.2 .0
+                    + self.0 .2 * self.1 .0 * self.2 .1
+                    - self.0 .2 * self.1 .1 * self.2 .0
+                    - self.0 .1 * self.1 .0 * self.2 .2
+                    - self.0 .0 * self.1 .2 * self.2 .1,
+            ),
+        ) / d
+    }
+}
+
+impl Mat4 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::identity()
+    }
+
+    #[inline]
+    pub fn det(self) -> f32 {
+        #[cfg(feature = "simd")]
+        {
+            simd::mat4_det(self)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.scalar_det()
+        }
+    }
+
     /// Coverts a rotation matrix to a quaternion, assuming that the matrix  
     /// is a pure rotational matrix (unscaled).
     #[inline]
-    pub fn to_quat(self) -> Vec4 {
+    pub fn to_quat(self) -> Quat {
         let trace = self.0 .0 + self.1 .1 + self.2 .2;
         if trace > 0.0 {
             let s = 0.5 / (trace + 1.0).sqrt();
-            Vec4(
-                s * (self.2 .1 - self.1 .2),
-                s * (self.0 .2 - self.2 .0),
-                s * (self.1 .0 - self.0 .1),
+            Quat(
+                s * (self.1 .2 - self.2 .1),
+                s * (self.2 .0 - self.0 .2),
+                s * (self.0 .1 - self.1 .0),
                 0.25 / s,
             )
         } else if self.0 .0 > self.1 .1 && self.0 .0 > self.2 .2 {
             let s = 2.0 * (1.0 + self.0 .0 - self.1 .1 - self.2 .2).sqrt();
-            println!("0.0 is the most valuable, s is {}", s);
-            Vec4(
+            Quat(
                 0.25 * s,
                 (self.0 .1 + self.1 .0) / s,
                 (self.0 .2 + self.2 .0) / s,
-                (self.2 .1 - self.1 .2) / s,
+                (self.1 .2 - self.2 .1) / s,
             )
         } else if self.1 .1 > self.2 .2 {
             let s = 2.0 * (1.0 - self.0 .0 + self.1 .1 - self.2 .2).sqrt();
-            Vec4(
+            Quat(
                 (self.0 .1 + self.1 .0) / s,
                 0.25 * s,
                 (self.1 .2 + self.2 .1) / s,
-                (self.0 .2 - self.2 .0) / s,
+                (self.2 .0 - self.0 .2) / s,
             )
         } else {
             let s = 2.0 * (1.0 - self.0 .0 - self.1 .1 + self.2 .2).sqrt();
-            Vec4(
+            Quat(
                 (self.0 .2 + self.2 .0) / s,
                 (self.1 .2 + self.2 .1) / s,
                 0.25 * s,
-                (self.1 .0 - self.0 .1) / s,
+                (self.0 .1 - self.1 .0) / s,
             )
         }
     }
@@ -478,8 +683,8 @@ impl Mat4 {
 
     /// Return a resulting matrix of translation, scaling and rotation.
     #[inline]
-    pub fn compose(t: Vec3, s: Vec3, r: Vec4) -> Self {
-        let Vec4(x, y, z, w) = r;
+    pub fn compose(t: Vec3, s: Vec3, r: Quat) -> Self {
+        let Quat(x, y, z, w) = r;
         Self(
             Vec4(
                 s.0 * (1.0 - 2.0 * y * y - 2.0 * z * z),
@@ -505,16 +710,12 @@ impl Mat4 {
 
     /// Return a translate, scale and rotation from the matrix.
     #[inline]
-    pub fn decompose(self) -> (Vec3, Vec3, Vec4) {
+    pub fn decompose(self) -> (Vec3, Vec3, Quat) {
         let d = self.det();
 
         let t = Vec3(self.3 .0, self.3 .1, self.3 .2);
         let s = Vec3(
-            if d > 0.0 {
-                self.0.len()
-            } else {
-                -1.0 * self.0.len()
-            },
+            if d > 0.0 { self.0.len() } else { -self.0.len() },
             self.1.len(),
             self.2.len(),
         );
@@ -524,6 +725,28 @@ impl Mat4 {
         (t, s, r)
     }
 
+    /// Return a position vector transformed the same way a shader does with
+    /// the matrix, that is with the translation stored in the last field and
+    /// followed by the perspective division. This is the convention of
+    /// `compose`, `perspective` and the multiplication of matrices, unlike
+    /// the transposed one of `Mul<Vec3>`.
+    #[inline]
+    pub fn transform_point(self, p: Vec3) -> Vec3 {
+        let w = p.0 * self.0 .3 + p.1 * self.1 .3 + p.2 * self.2 .3 + self.3 .3;
+        (self.transform_vector(p) + Vec3(self.3 .0, self.3 .1, self.3 .2)) / w
+    }
+
+    /// Return a direction vector transformed by the matrix, which unlike a
+    /// position is not affected by the translation.
+    #[inline]
+    pub fn transform_vector(self, v: Vec3) -> Vec3 {
+        Vec3(
+            v.0 * self.0 .0 + v.1 * self.1 .0 + v.2 * self.2 .0,
+            v.0 * self.0 .1 + v.1 * self.1 .1 + v.2 * self.2 .1,
+            v.0 * self.0 .2 + v.1 * self.1 .2 + v.2 * self.2 .2,
+        )
+    }
+
     /// Return a transposed matrix
     #[inline]
     pub fn transpose(self) -> Self {
@@ -535,125 +758,89 @@ impl Mat4 {
         )
     }
 
-    /// Return an inverse matrix
+    /// Return an inverse matrix. The matrix is assumed to be invertible,
+    /// otherwise the result is filled with infinities and NaNs, use
+    /// `try_inverse` if that is not guaranteed.
     #[inline]
     pub fn inverse(self) -> Self {
-        let d = self.det();
+        #[cfg(feature = "simd")]
+        {
+            simd::mat4_inverse(self)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.scalar_inverse()
+        }
+    }
+
+    /// Return an inverse matrix, or None if the matrix is singular, with
+    /// the default `SINGULAR_DET` threshold.
+    #[inline]
+    pub fn try_inverse(self) -> Option<Self> {
+        self.try_inverse_with(SINGULAR_DET)
+    }
+
+    /// Return an inverse matrix, or None if the absolute value of the matrix
+    /// determinant doesn't exceed the threshold times the product of the
+    /// lengths of the upper-left 3x3 columns (or is not a number). These are
+    /// the axes, so the translation doesn't make the matrix look singular.
+    #[inline]
+    pub fn try_inverse_with(self, threshold: f32) -> Option<Self> {
+        let Mat3(x, y, z) = Mat3::from(self);
+        let scale = x.len() * y.len() * z.len();
+        if self.det().abs() > threshold * scale {
+            Some(self.inverse())
+        } else {
+            None
+        }
+    }
+
+    /// Return an inverse of an affine transformation matrix, such as the
+    /// one made by `compose`, which has no projection part. It only inverts
+    /// the upper-left 3x3 part instead of doing a full 4x4 expansion.
+    #[inline]
+    pub fn inverse_affine(self) -> Self {
+        let r = Mat3::from(self).inverse();
+        let t = Vec3(self.3 .0, self.3 .1, self.3 .2);
         Self(
+            Vec4(r.0 .0, r.0 .1, r.0 .2, 0.0),
+            Vec4(r.1 .0, r.1 .1, r.1 .2, 0.0),
+            Vec4(r.2 .0, r.2 .1, r.2 .2, 0.0),
             Vec4(
-                self.1 .1 * self.2 .2 * self.3 .3
-                    + self.1 .2 * self.2 .3 * self.3 .1
-                    + self.1 .3 * self.2 .1 * self.3 .2
-                    - self.1 .3 * self.2 .2 * self.3 .1
-                    - self.1 .2 * self.2 .1 * self.3 .3
-                    - self.1 .1 * self.2 .3 * self.3 .2,
-                -self.0 .1 * self.2 .2 * self.3 .3
-                    - self.0 .2 * self.2 .3 * self.3 .1
-                    - self.0 .3 * self.2 .1 * self.3 .2
-                    + self.0 .3 * self.2 .2 * self.3 .1
-                    + self.0 .2 * self.2 .1 * self.3 .3
-                    + self.0 .1 * self.2 .3 * self.3 .2,
-                self.0 .1 * self.1 .2 * self.3 .3
-                    + self.0 .2 * self.1 .3 * self.3 .1
-                    + self.0 .3 * self.1 .1 * self.3 .2
-                    - self.0 .3 * self.1 .2 * self.3 .1
-                    - self.0 .2 * self.1 .1 * self.3 .3
-                    - self.0 .1 * self.1 .3 * self.3 .2,
-                -self.0 .1 * self.1 .2 * self.2 .3
-                    - self.0 .2 * self.1 .3 * self.2 .1
-                    - self.0 .3 * self.1 .1 * self.2 .2
-                    + self.0 .3 * self.1 .2 * self.2 .1
-                    + self.0 .2 * self.1 .1 * self.2 .3
-                    + self.0 .1 * self.1 .3 * self.2 .2,
-            ),
-            Vec4(
-                -self.1 .0 * self.2 .2 * self.3 .3
-                    - self.1 .2 * self.2 .3 * self.3 .0
-                    - self.1 .3 * self.2 .0 * self.3 .2
-                    + self.1 .3 * self.2 .2 * self.3 .0
-                    + self.1 .2 * self.2 .0 * self.3 .3
-                    + self.1 .0 * self.2 .3 * self.3 .2,
-                self.0 .0 * self.2 .2 * self.3 .3
-                    + self.0 .2 * self.2 .3 * self.3 .0
-                    + self.0 .3 * self.2 .0 * self.3 .2
-                    - self.0 .3 * self.2 .2 * self.3 .0
-                    - self.0 .2 * self.2 .0 * self.3 .3
-                    - self.0 .0 * self.2 .3 * self.3 .2,
-                -self.0 .0 * self.1 .2 * self.3 .3
-                    - self.0 .2 * self.1 .3 * self.3 .0
-                    - self.0 .3 * self.1 .0 * self.3 .2
-                    + self.0 .3 * self.1 .2 * self.3 .0
-                    + self.0 .2 * self.1 .0 * self.3 .3
-                    + self.0 .0 * self.1 .3 * self.3 .2,
-                self.0 .0 * self.1 .2 * self.2 .3
-                    + self.0 .2 * self.1 .3 * self.2 .0
-                    + self.0 .3 * self.1 .0 * self.2 .2
-                    - self.0 .3 * self.1 .2 * self.2 .0
-                    - self.0 .2 * self.1 .0 * self.2 .3
-                    - self.0 .0 * self.1 .3 * self.2 .2,
-            ),
-            Vec4(
-                self.1 .0 * self.2 .1 * self.3 .3
-                    + self.1 .1 * self.2 .3 * self.3 .0
-                    + self.1 .3 * self.2 .0 * self.3 .1
-                    - self.1 .3 * self.2 .1 * self.3 .0
-                    - self.1 .1 * self.2 .0 * self.3 .3
-                    - self.1 .0 * self.2 .3 * self.3 .1,
-                -self.0 .0 * self.2 .1 * self.3 .3
-                    - self.0 .1 * self.2 .3 * self.3 .0
-                    - self.0 .3 * self.2 .0 * self.3 .1
-                    + self.0 .3 * self.2 .1 * self.3 .0
-                    + self.0 .1 * self.2 .0 * self.3 .3
-                    + self.0 .0 * self.2 .3 * self.3 .1,
-                self.0 .0 * self.1 .1 * self.3 .3
-                    + self.0 .1 * self.1 .3 * self.3 .0
-                    + self.0 .3 * self.1 .0 * self.3 .1
-                    - self.0 .3 * self.1 .1 * self.3 .0
-                    - self.0 .1 * self.1 .0 * self.3 .3
-                    - self.0 .0 * self.1 .3 * self.3 .1,
-                -self.0 .0 * self.1 .1 * self.2 .3
-                    - self.0 .1 * self.1 .3 * self.2 .0
-                    - self.0 .3 * self.1 .0 * self.2 .1
-                    + self.0 .3 * self.1 .1 * self.2 .0
-                    + self.0 .1 * self.1 .0 * self.2 .3
-                    + self.0 .0 * self.1 .3 * self.2 .1,
+                -(t.0 * r.0 .0 + t.1 * r.1 .0 + t.2 * r.2 .0),
+                -(t.0 * r.0 .1 + t.1 * r.1 .1 + t.2 * r.2 .1),
+                -(t.0 * r.0 .2 + t.1 * r.1 .2 + t.2 * r.2 .2),
+                1.0,
             ),
+        )
+    }
+
+    /// Return an inverse of a rigid transformation matrix, consisting of a
+    /// rotation and a translation only, such as camera made by `look_at`.
+    /// The rotation part is inverted by a transposition.
+    #[inline]
+    pub fn inverse_rigid(self) -> Self {
+        let t = Vec3(self.3 .0, self.3 .1, self.3 .2);
+        Self(
+            Vec4(self.0 .0, self.1 .0, self.2 .0, 0.0),
+            Vec4(self.0 .1, self.1 .1, self.2 .1, 0.0),
+            Vec4(self.0 .2, self.1 .2, self.2 .2, 0.0),
             Vec4(
-                -self.1 .0 * self.2 .1 * self.3 .2
-                    - self.1 .1 * self.2 .2 * self.3 .0
-                    - self.1 .2 * self.2 .0 * self.3 .1
-                    + self.1 .2 * self.2 .1 * self.3 .0
-                    + self.1 .1 * self.2 .0 * self.3 .2
-                    + self.1 .0 * self.2 .2 * self.3 .1,
-                self.0 .0 * self.2 .1 * self.3 .2
-                    + self.0 .1 * self.2 .2 * self.3 .0
-                    + self.0 .2 * self.2 .0 * self.3 .1
-                    - self.0 .2 * self.2 .1 * self.3 .0
-                    - self.0 .1 * self.2 .0 * self.3 .2
-                    - self.0 .0 * self.2 .2 * self.3 .1,
-                -self.0 .0 * self.1 .1 * self.3 .2
-                    - self.0 .1 * self.1 .2 * self.3 .0
-                    - self.0 .2 * self.1 .0 * self.3 .1
-                    + self.0 .2 * self.1 .1 * self.3 .0
-                    + self.0 .1 * self.1 .0 * self.3 .2
-                    + self.0 .0 * self.1 .2 * self.3 .1,
-                self.0 .0 * self.1 .1 * self.2 .2
-                    + self.0 .1 * self.1 .2 * self.2 .0
-                    + self.0 .2 * self.1 .0 * self.2 .1
-                    - self.0 .2 * self.1 .1 * self.2 .0
-                    - self.0 .1 * self.1 .0 * self.2 .2
-                    - self.0 .0 * self.1 .2 * self.2 .1,
+                -(t.0 * self.0 .0 + t.1 * self.0 .1 + t.2 * self.0 .2),
+                -(t.0 * self.1 .0 + t.1 * self.1 .1 + t.2 * self.1 .2),
+                -(t.0 * self.2 .0 + t.1 * self.2 .1 + t.2 * self.2 .2),
+                1.0,
             ),
-        ) / d
+        )
     }
 
     /// Return a matrix that represent transformation from the origin to a  
     /// place and orientation in the World.
     #[inline]
     pub fn look_at(origin: Vec3, target: Vec3, up: Vec3) -> Self {
-        let z = (origin - target).normalize();
-        let x = up.cross(z).normalize();
-        let y = z.cross(x).normalize();
+        let z = (origin - target).normalize_precise();
+        let x = up.cross(z).normalize_precise();
+        let y = z.cross(x);
         Self(
             Vec4(x.0, x.1, x.2, 0.0),
             Vec4(y.0, y.1, y.2, 0.0),
@@ -739,7 +926,7 @@ impl Mat4 {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Mat4, Vec3, Vec4};
+    use crate::core::{Approx, Mat4, Quat, Vec3, Vec4};
     use std::f32::consts::{FRAC_PI_2, FRAC_PI_3};
     use std::fmt::{Debug, Formatter, Result};
 
@@ -782,6 +969,19 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn approx() {
+        let a = Mat4::translation(Vec3(1000.0, 2000.0, 3000.0));
+        let b = Mat4::translation(Vec3(1000.0, 2000.0, 3000.1));
+        assert!(a != b);
+        assert!(a.approx_eq_rel(b, 0.0001));
+        assert!(a.approx_eq_eps(b, 0.2));
+        assert!(!a.approx_eq_ulps(b, 4));
+        assert!(a.approx_eq_ulps(a, 0));
+        assert!(a.bitwise_eq(Mat4::translation(Vec3(1000.0, 2000.0, 3000.0))));
+        assert!(!a.bitwise_eq(b));
+    }
+
     #[test]
     fn dot_product() {
         let a = Mat4(
@@ -813,7 +1013,10 @@ mod tests {
             Vec4(2.3, 1.1, -2.0, -4.0),
             Vec4(1.2, 1.1, 1.4, 3.1),
         );
-        assert_eq!(a.det(), -12.650698);
+        assert_eq!(a.scalar_det(), -12.650698);
+
+        // SIMD implementation groups the operations differently
+        assert!(a.det().approx_eq(-12.650698));
     }
 
     #[test]
@@ -824,7 +1027,7 @@ mod tests {
             Vec4(0.0, 0.0, 1.0, 0.0),
             Vec4(0.0, 0.0, 0.0, 1.0),
         );
-        let q = Vec4(0.0, 0.0, 0.0, 1.0);
+        let q = Quat(0.0, 0.0, 0.0, 1.0);
         assert_eq!(a.to_quat(), q);
 
         let a = Mat4(
@@ -833,7 +1036,7 @@ mod tests {
             Vec4(0.0, 1.0, 0.0, 0.0),
             Vec4(0.0, 0.0, 0.0, 1.0),
         );
-        let q = Vec4(0.7071, 0.0, 0.0, 0.7071);
+        let q = Quat(-0.7071, 0.0, 0.0, 0.7071);
         assert_eq!(a.to_quat(), q);
 
         let a = Mat4(
@@ -842,7 +1045,7 @@ mod tests {
             Vec4(-1.2, 2.3, -1.2, 0.0),
             Vec4(0.0, 0.0, 0.0, 1.0),
         );
-        let q = Vec4(-0.1795, 0.9746, 0.2821, 0.3590);
+        let q = Quat(-0.1795, 0.9746, 0.2821, -0.3590);
         assert_eq!(a.to_quat(), q);
 
         let a = Mat4(
@@ -851,7 +1054,7 @@ mod tests {
             Vec4(0.9430549, -0.04897247, -0.32761973, 0.0),
             Vec4(1.2, 1.4, 1.6, 1.0),
         );
-        let q = Vec4(0.3229, 0.3371, 0.6181, -0.4537);
+        let q = Quat(0.3229, 0.3371, 0.6181, 0.4537);
         assert_eq!(a.to_quat(), q);
     }
 
@@ -1075,7 +1278,7 @@ mod tests {
     fn compose() {
         let t = Vec3(1.2, 1.4, 1.6);
         let s = Vec3(1.8, 2.0, 2.2);
-        let r = Vec4(0.64, 0.72, 0.8, 1.0);
+        let r = Quat(0.64, 0.72, 0.8, 1.0);
         let a = Mat4::compose(t, s, r);
         let b = Mat4(
             Vec4(-2.3702, 4.5388, -0.7487, 0.0),
@@ -1097,7 +1300,38 @@ mod tests {
         let (t, s, r) = a.decompose();
         assert_eq!(t, Vec3(1.2, 1.4, 1.6));
         assert_eq!(s, Vec3(5.1748, 5.5073, 5.7454));
-        assert_eq!(r, Vec4(0.3229, 0.3371, 0.6181, -0.4537));
+        assert_eq!(r, Quat(0.3229, 0.3371, 0.6181, 0.4537));
+
+        // Decomposition reverts the composition of a unit quaternion
+        let q = Quat::from_axis_angle(Vec3(0.4570, 0.8437, 0.2812), FRAC_PI_3);
+        let (t, s, r) = Mat4::compose(Vec3(1.2, 1.4, 1.6), Vec3(1.8, 2.0, 2.2), q).decompose();
+        assert_eq!(t, Vec3(1.2, 1.4, 1.6));
+        assert_eq!(s, Vec3(1.8, 2.0, 2.2));
+        assert_eq!(r, q);
+    }
+
+    #[test]
+    fn transform_point() {
+        let a = Mat4::compose(Vec3(1.0, 2.0, 3.0), Vec3(2.0, 2.0, 2.0), Quat::identity());
+        assert_eq!(Vec3(3.0, 4.0, 5.0), a.transform_point(Vec3(1.0, 1.0, 1.0)));
+        assert_eq!(Vec3(2.0, 2.0, 2.0), a.transform_vector(Vec3(1.0, 1.0, 1.0)));
+
+        // Transformations are applied in the order of multiplication
+        let b = Mat4::z_rotation(FRAC_PI_2);
+        let p = Vec3(1.0, 0.0, 0.0);
+        assert_eq!(Vec3(-2.0, 3.0, 3.0), (a * b).transform_point(p));
+        assert_eq!(
+            b.transform_point(a.transform_point(p)),
+            (a * b).transform_point(p)
+        );
+
+        // Perspective division brings a view space point to the unit cube
+        let c = Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 3.0);
+        assert_eq!(
+            Vec3(0.0, 0.0, -1.0),
+            c.transform_point(Vec3(0.0, 0.0, -1.0))
+        );
+        assert_eq!(Vec3(1.0, 0.0, 1.0), c.transform_point(Vec3(3.0, 0.0, -3.0)));
     }
 
     #[test]
@@ -1134,6 +1368,61 @@ mod tests {
         assert_eq!(a.inverse(), b);
     }
 
+    #[test]
+    fn try_inverse() {
+        let a = Mat4(
+            Vec4(-2.3, 4.5, -0.7, 2.3),
+            Vec4(-1.3, -2.1, 4.8, 0.1),
+            Vec4(5.4, -0.2, -1.8, -1.2),
+            Vec4(1.2, 1.4, 1.6, 1.0),
+        );
+        assert_eq!(a.try_inverse(), Some(a.inverse()));
+
+        // Matrix with a zero scale on an axis can't be inverted
+        let a = Mat4::scaling(Vec3(1.0, 0.0, 1.0)).translate(Vec3(1.0, 2.0, 3.0));
+        assert_eq!(a.try_inverse(), None);
+
+        // Small scales are invertible, as the threshold is relative
+        let a = Mat4::scaling(Vec3(1e-4, 1e-4, 1e-4));
+        assert_eq!(a.try_inverse(), Some(Mat4::scaling(Vec3(1e4, 1e4, 1e4))));
+
+        // So are translations far from the origin
+        let a = Mat4::translation(Vec3(1e6, 0.0, 0.0));
+        assert_eq!(
+            a.try_inverse(),
+            Some(Mat4::translation(Vec3(-1e6, 0.0, 0.0)))
+        );
+
+        // Nearly parallel columns make the determinant below the threshold
+        let a = Mat4(
+            Vec4(1.0, 0.0, 0.0, 0.0),
+            Vec4(1.0, 0.001, 0.0, 0.0),
+            Vec4(0.0, 0.0, 1.0, 0.0),
+            Vec4(0.0, 0.0, 0.0, 1.0),
+        );
+        assert!(a.try_inverse().is_some());
+        assert_eq!(a.try_inverse_with(0.01), None);
+    }
+
+    #[test]
+    fn inverse_affine() {
+        let q = Quat::from_axis_angle(Vec3(0.4570, 0.8437, 0.2812), FRAC_PI_3);
+        let a = Mat4::compose(Vec3(1.2, 1.4, 1.6), Vec3(1.8, 2.0, 2.2), q);
+        assert_eq!(a.inverse_affine(), a.inverse());
+        assert_eq!(a * a.inverse_affine(), Mat4::identity());
+    }
+
+    #[test]
+    fn inverse_rigid() {
+        let a = Mat4::look_at(
+            Vec3(2.3, 2.3, 6.5),
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 0.0, 1.0),
+        );
+        assert_eq!(a.inverse_rigid(), a.inverse());
+        assert_eq!(a * a.inverse_rigid(), Mat4::identity());
+    }
+
     #[test]
     fn look_at() {
         let a = Mat4::look_at(
@@ -1148,6 +1437,17 @@ mod tests {
             Vec4(2.3, 2.3, 6.5, 1.0),
         );
         assert_eq!(a, b);
+
+        // Camera basis is orthonormal to the float precision
+        let x = Vec3(a.0 .0, a.0 .1, a.0 .2);
+        let y = Vec3(a.1 .0, a.1 .1, a.1 .2);
+        let z = Vec3(a.2 .0, a.2 .1, a.2 .2);
+        assert!(x.len().approx_eq_eps(1.0, 1e-6));
+        assert!(y.len().approx_eq_eps(1.0, 1e-6));
+        assert!(z.len().approx_eq_eps(1.0, 1e-6));
+        assert!(x.dot(y).approx_eq_eps(0.0, 1e-6));
+        assert!(y.dot(z).approx_eq_eps(0.0, 1e-6));
+        assert!(z.dot(x).approx_eq_eps(0.0, 1e-6));
     }
 
     #[test]
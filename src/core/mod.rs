@@ -1,11 +1,31 @@
 pub mod approx;
+pub mod bounds;
 pub mod color;
+pub mod frustum;
+pub mod mat2x3;
+pub mod mat3;
 pub mod mat4;
+pub mod plane;
+pub mod quat;
+pub mod ray;
+#[cfg(feature = "simd")]
+mod simd;
+pub mod transform;
+pub mod vec2;
 pub mod vec3;
 pub mod vec4;
 
-pub use approx::{Approx, EPS};
+pub use approx::{Approx, FastSqrt, EPS};
+pub use bounds::{Aabb, Sphere};
 pub use color::Color;
-pub use mat4::Mat4;
+pub use frustum::{Frustum, Intersection};
+pub use mat2x3::Mat2x3;
+pub use mat3::Mat3;
+pub use mat4::{Mat4, SINGULAR_DET};
+pub use plane::Plane;
+pub use quat::Quat;
+pub use ray::{Hit, Ray};
+pub use transform::Transform;
+pub use vec2::Vec2;
 pub use vec3::Vec3;
 pub use vec4::Vec4;
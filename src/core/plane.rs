@@ -0,0 +1,115 @@
+use crate::core::{Approx, Vec3, Vec4};
+
+/// Plane is stored as its normal and a signed distance from the origin,
+/// so that the points `p` of the plane satisfy `n.dot(p) + d == 0`. The
+/// side the normal points to is considered to be in front of the plane.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Plane(pub Vec3, pub f32);
+
+impl PartialEq for Plane {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1.approx_eq(rhs.1)
+    }
+}
+
+impl From<Vec4> for Plane {
+    /// Return a plane from its `ax + by + cz + d = 0` equation coefficients.
+    #[inline]
+    fn from(v: Vec4) -> Self {
+        Self(Vec3(v.0, v.1, v.2), v.3)
+    }
+}
+
+impl From<Plane> for Vec4 {
+    #[inline]
+    fn from(p: Plane) -> Self {
+        Vec4(p.0 .0, p.0 .1, p.0 .2, p.1)
+    }
+}
+
+impl Plane {
+    #[inline]
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        Self(normal, distance)
+    }
+
+    /// Return a plane passing through the point with the given normal.
+    #[inline]
+    pub fn from_point_normal(p: Vec3, normal: Vec3) -> Self {
+        Self(normal, -normal.dot(p))
+    }
+
+    /// Return a plane passing through the three points, facing the side
+    /// they are seen counter-clockwise from.
+    #[inline]
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self::from_point_normal(a, (b - a).cross(c - a).normalize_precise())
+    }
+
+    /// Return the same plane with the normal of unit length, so that
+    /// `distance` gives the actual distance to a point.
+    #[inline]
+    pub fn normalize(self) -> Self {
+        let len = self.0.len();
+        Self(self.0 / len, self.1 / len)
+    }
+
+    /// Signed distance from the plane to the point, which is positive in
+    /// front of the plane. It is scaled by the normal length, unless the
+    /// plane is normalized.
+    #[inline]
+    pub fn distance(self, p: Vec3) -> f32 {
+        self.0.dot(p) + self.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Approx, Plane, Vec3, Vec4};
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Plane {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Plane({:?}, {:.4})", self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn from_point_normal() {
+        let a = Plane::from_point_normal(Vec3(1.0, 2.0, 3.0), Vec3(0.0, 0.0, 1.0));
+        assert_eq!(Plane(Vec3(0.0, 0.0, 1.0), -3.0), a);
+    }
+
+    #[test]
+    fn from_points() {
+        let a = Plane::from_points(
+            Vec3(1.0, 0.0, 2.0),
+            Vec3(0.0, 1.0, 2.0),
+            Vec3(-1.0, 0.0, 2.0),
+        );
+        assert_eq!(Plane(Vec3(0.0, 0.0, 1.0), -2.0), a);
+    }
+
+    #[test]
+    fn from_vec4() {
+        let a = Vec4(0.0, 3.0, 4.0, 10.0);
+        assert_eq!(Plane(Vec3(0.0, 3.0, 4.0), 10.0), a.into());
+        assert_eq!(a, Plane(Vec3(0.0, 3.0, 4.0), 10.0).into());
+    }
+
+    #[test]
+    fn normalize() {
+        let a = Plane(Vec3(0.0, 3.0, 4.0), 10.0);
+        assert_eq!(Plane(Vec3(0.0, 0.6, 0.8), 2.0), a.normalize());
+    }
+
+    #[test]
+    fn distance() {
+        let a = Plane(Vec3(0.0, 0.6, 0.8), 2.0);
+        assert!(a.distance(Vec3(5.0, 0.0, -2.5)).approx_eq(0.0));
+        assert!(a.distance(Vec3(0.0, 0.6, 0.8)).approx_eq(3.0));
+        assert!(a.distance(Vec3(0.0, -2.4, -3.2)).approx_eq(-2.0));
+    }
+}
@@ -0,0 +1,391 @@
+use crate::core::{Approx, Mat4, Vec3, Vec4};
+use std::ops::{Mul, MulAssign, Neg};
+
+/// Quat is a data structure that represent a rotation quaternion, stored as
+/// an imaginary part followed by a real one (x, y, z, w). The layout is the
+/// same as Vec4 one, so a quaternion can be passed over FFI as is. Most of
+/// the methods assume that the quaternion is of a unit length.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Quat(pub f32, pub f32, pub f32, pub f32);
+
+impl PartialEq for Quat {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.approx_eq(*rhs)
+    }
+}
+
+impl Approx for Quat {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+            && self.3.approx_eq_eps(rhs.3, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+            && self.3.approx_eq_rel(rhs.3, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+            && self.3.approx_eq_ulps(rhs.3, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0)
+            && self.1.bitwise_eq(rhs.1)
+            && self.2.bitwise_eq(rhs.2)
+            && self.3.bitwise_eq(rhs.3)
+    }
+}
+
+impl Default for Quat {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Vec4> for Quat {
+    #[inline]
+    fn from(v: Vec4) -> Self {
+        Self(v.0, v.1, v.2, v.3)
+    }
+}
+
+impl From<Quat> for Vec4 {
+    #[inline]
+    fn from(q: Quat) -> Self {
+        Vec4(q.0, q.1, q.2, q.3)
+    }
+}
+
+impl From<Mat4> for Quat {
+    #[inline]
+    fn from(m: Mat4) -> Self {
+        m.to_quat()
+    }
+}
+
+impl From<Quat> for Mat4 {
+    /// Return a rotation matrix, equal to `Mat4::compose` with no translation
+    /// and a unit scale.
+    #[inline]
+    fn from(q: Quat) -> Self {
+        Mat4::compose(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0), q)
+    }
+}
+
+impl Neg for Quat {
+    type Output = Self;
+
+    /// Return a quaternion with all the components negated, which represent
+    /// the same rotation.
+    #[inline]
+    fn neg(self) -> Self {
+        Self(-self.0, -self.1, -self.2, -self.3)
+    }
+}
+
+impl Mul<Quat> for Quat {
+    type Output = Self;
+
+    /// Return a Hamilton product of two quaternions, which is a rotation by
+    /// `rhs` followed by the rotation by `self`.
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(
+            self.3 * rhs.0 + self.0 * rhs.3 + self.1 * rhs.2 - self.2 * rhs.1,
+            self.3 * rhs.1 - self.0 * rhs.2 + self.1 * rhs.3 + self.2 * rhs.0,
+            self.3 * rhs.2 + self.0 * rhs.1 - self.1 * rhs.0 + self.2 * rhs.3,
+            self.3 * rhs.3 - self.0 * rhs.0 - self.1 * rhs.1 - self.2 * rhs.2,
+        )
+    }
+}
+
+impl MulAssign<Quat> for Quat {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    /// Return a vector rotated by the quaternion.
+    #[inline]
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        let u = Vec3(self.0, self.1, self.2);
+        let t = u.cross(rhs) * 2.0_f32;
+        rhs + t * self.3 + u.cross(t)
+    }
+}
+
+/// Scalar multiplication
+impl Mul<f32> for Quat {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self(self.0 * rhs, self.1 * rhs, self.2 * rhs, self.3 * rhs)
+    }
+}
+
+impl Quat {
+    /// Return a quaternion that represent no rotation.
+    #[inline]
+    pub fn identity() -> Self {
+        Self(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Return a rotation around an arbitrary axis.
+    /// Assume that provided axis is already normalized.
+    #[inline]
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (0.5 * angle).sin_cos();
+        Self(axis.0 * sin, axis.1 * sin, axis.2 * sin, cos)
+    }
+
+    /// Return a rotation from Euler angles, applying the rotation around x
+    /// axis first, then around y axis and around z axis the last.
+    #[inline]
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+        let (sx, cx) = (0.5 * x).sin_cos();
+        let (sy, cy) = (0.5 * y).sin_cos();
+        let (sz, cz) = (0.5 * z).sin_cos();
+        Self(
+            sx * cy * cz - cx * sy * sz,
+            cx * sy * cz + sx * cy * sz,
+            cx * cy * sz - sx * sy * cz,
+            cx * cy * cz + sx * sy * sz,
+        )
+    }
+
+    /// Squared length of the quaternion
+    #[inline]
+    pub fn len2(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Length of the quaternion
+    #[inline]
+    pub fn len(self) -> f32 {
+        self.len2().sqrt()
+    }
+
+    /// Normalize quaternion turning it's length to 1
+    #[inline]
+    pub fn normalize(self) -> Self {
+        self * (1.0 / self.len())
+    }
+
+    /// Dot product of two quaternions
+    #[inline]
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.0 * rhs.0 + self.1 * rhs.1 + self.2 * rhs.2 + self.3 * rhs.3
+    }
+
+    /// Return a conjugated quaternion, which for a unit quaternion represent
+    /// the opposite rotation.
+    #[inline]
+    pub fn conjugate(self) -> Self {
+        Self(-self.0, -self.1, -self.2, self.3)
+    }
+
+    /// Return an inverse quaternion, valid for quaternions of any length.
+    #[inline]
+    pub fn inverse(self) -> Self {
+        self.conjugate() * (1.0 / self.len2())
+    }
+
+    /// Normalized linear interpolation between two rotations. It is cheaper
+    /// than `slerp`, but doesn't keep the angular velocity constant.
+    #[inline]
+    pub fn nlerp(self, rhs: Self, t: f32) -> Self {
+        let rhs = if self.dot(rhs) < 0.0 { -rhs } else { rhs };
+        Self(
+            self.0 + (rhs.0 - self.0) * t,
+            self.1 + (rhs.1 - self.1) * t,
+            self.2 + (rhs.2 - self.2) * t,
+            self.3 + (rhs.3 - self.3) * t,
+        )
+        .normalize()
+    }
+
+    /// Spherical linear interpolation between two rotations along the
+    /// shortest arc. Falls back to `nlerp` for nearly equal rotations.
+    #[inline]
+    pub fn slerp(self, rhs: Self, t: f32) -> Self {
+        let mut cos = self.dot(rhs);
+        let rhs = if cos < 0.0 {
+            cos = -cos;
+            -rhs
+        } else {
+            rhs
+        };
+
+        if cos > 0.9995 {
+            return self.nlerp(rhs, t);
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        Self(
+            self.0 * a + rhs.0 * b,
+            self.1 * a + rhs.1 * b,
+            self.2 * a + rhs.2 * b,
+            self.3 * a + rhs.3 * b,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Mat4, Quat, Vec3, Vec4};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_3, FRAC_PI_4};
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Quat {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(
+                f,
+                "Quat({:.4}, {:.4}, {:.4}, {:.4})",
+                self.0, self.1, self.2, self.3
+            )
+        }
+    }
+
+    #[test]
+    fn equal() {
+        let a = Quat(0.5, 0.5, 0.5, 0.5);
+        let b = Quat(0.5, 0.5, 0.5, 0.5);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn from_vec4() {
+        let a = Vec4(0.1, 0.2, 0.3, 0.4);
+        let b = Quat(0.1, 0.2, 0.3, 0.4);
+        assert_eq!(b, a.into());
+        assert_eq!(a, b.into());
+    }
+
+    #[test]
+    fn from_axis_angle() {
+        let a = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2);
+        let b = Quat(0.0, 0.0, 0.7071, 0.7071);
+        assert_eq!(a, b);
+
+        // Produce the same rotation as an axis rotation matrix
+        let axis = Vec3(0.4570, 0.8437, 0.2812);
+        let a: Mat4 = Quat::from_axis_angle(axis, FRAC_PI_3).into();
+        assert_eq!(a, Mat4::axis_rotation(axis, FRAC_PI_3));
+    }
+
+    #[test]
+    fn from_euler() {
+        let a: Mat4 = Quat::from_euler(FRAC_PI_3, FRAC_PI_4, -FRAC_PI_2).into();
+        let b = Mat4::x_rotation(FRAC_PI_3)
+            * Mat4::y_rotation(FRAC_PI_4)
+            * Mat4::z_rotation(-FRAC_PI_2);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn from_mat4() {
+        let q = Quat::from_euler(0.3, -1.2, 2.5);
+        assert_eq!(q, Quat::from(Mat4::from(q)));
+
+        let q = Quat::from_axis_angle(Vec3(1.0, 0.0, 0.0), 3.0);
+        assert_eq!(q, Quat::from(Mat4::from(q)));
+    }
+
+    #[test]
+    fn mul() {
+        let a = Quat(1.0, 2.0, 3.0, 4.0);
+        let b = Quat(5.0, 6.0, 7.0, 8.0);
+        let c = Quat(24.0, 48.0, 48.0, -6.0);
+        assert_eq!(c, a * b);
+
+        // Combine two rotations around the same axis
+        let z = Vec3(0.0, 0.0, 1.0);
+        let a = Quat::from_axis_angle(z, FRAC_PI_4) * Quat::from_axis_angle(z, FRAC_PI_4);
+        assert_eq!(a, Quat::from_axis_angle(z, FRAC_PI_2));
+    }
+
+    #[test]
+    fn rotate() {
+        let q = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2);
+        assert_eq!(Vec3(0.0, 1.0, 0.0), q * Vec3(1.0, 0.0, 0.0));
+
+        let q = Quat::from_axis_angle(Vec3(1.0, 0.0, 0.0), FRAC_PI_2);
+        assert_eq!(Vec3(1.2, 0.0, 0.2), q * Vec3(1.2, 0.2, 0.0));
+    }
+
+    #[test]
+    fn conjugate() {
+        let a = Quat(0.1, 0.2, 0.3, 0.4);
+        let b = Quat(-0.1, -0.2, -0.3, 0.4);
+        assert_eq!(b, a.conjugate());
+    }
+
+    #[test]
+    fn inverse() {
+        let a = Quat(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(Quat::identity(), a * a.inverse());
+        assert_eq!(Quat::identity(), a.inverse() * a);
+    }
+
+    #[test]
+    fn normalize() {
+        let a = Quat(0.0, 3.0, 0.0, 4.0);
+        let b = Quat(0.0, 0.6, 0.0, 0.8);
+        assert_eq!(b, a.normalize());
+    }
+
+    #[test]
+    fn nlerp() {
+        let a = Quat::identity();
+        let b = Quat::from_axis_angle(Vec3(0.0, 1.0, 0.0), FRAC_PI_2);
+        assert_eq!(a, a.nlerp(b, 0.0));
+        assert_eq!(b, a.nlerp(b, 1.0));
+        assert_eq!(
+            Quat::from_axis_angle(Vec3(0.0, 1.0, 0.0), FRAC_PI_4),
+            a.nlerp(b, 0.5)
+        );
+
+        // Take the shortest path for the opposite sign quaternions
+        assert_eq!(b, a.nlerp(-b, 1.0));
+    }
+
+    #[test]
+    fn slerp() {
+        let y = Vec3(0.0, 1.0, 0.0);
+        let a = Quat::identity();
+        let b = Quat::from_axis_angle(y, FRAC_PI_2);
+        assert_eq!(a, a.slerp(b, 0.0));
+        assert_eq!(b, a.slerp(b, 1.0));
+        assert_eq!(
+            Quat::from_axis_angle(y, FRAC_PI_2 / 3.0),
+            a.slerp(b, 1.0 / 3.0)
+        );
+        assert_eq!(
+            Quat::from_axis_angle(y, FRAC_PI_2 / 3.0),
+            a.slerp(-b, 1.0 / 3.0)
+        );
+    }
+}
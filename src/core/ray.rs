@@ -0,0 +1,312 @@
+use crate::core::{Aabb, Approx, Mat4, Plane, Sphere, Vec2, Vec3};
+
+/// Threshold of the determinant absolute value in `Ray::intersect_triangle`,
+/// below which the ray is considered to be parallel to the triangle.
+const PARALLEL_DET: f32 = 1e-12;
+
+/// Ray is a half-line, stored as its origin and direction. The direction
+/// is expected to be normalized for the hit distances to be measured in
+/// the World units, otherwise they are scaled by its length.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Ray(pub Vec3, pub Vec3);
+
+/// Hit of a ray with a triangle: a distance along the ray and barycentric
+/// coordinates of the point, which are weights of the second and third
+/// vertices, while the first one has the weight of `1 - u - v`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Hit {
+    pub distance: f32,
+    pub u: f32,
+    pub v: f32,
+}
+
+impl PartialEq for Ray {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1 == rhs.1
+    }
+}
+
+impl PartialEq for Hit {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.distance.approx_eq(rhs.distance) && self.u.approx_eq(rhs.u) && self.v.approx_eq(rhs.v)
+    }
+}
+
+impl Mat4 {
+    /// Return a World position of a point on the screen, which reverts the
+    /// transformation by the view projection matrix. The screen coordinates
+    /// are in pixels from the top left corner, as in the canvas events, and
+    /// the depth is from 0 at the near plane to 1 at the far one. The size
+    /// is the width and height of the screen in pixels. Return None if the
+    /// matrix is not invertible.
+    #[inline]
+    pub fn unproject(self, screen_xy: Vec2, depth: f32, size: Vec2) -> Option<Vec3> {
+        let ndc = Vec3(
+            2.0 * screen_xy.0 / size.0 - 1.0,
+            1.0 - 2.0 * screen_xy.1 / size.1,
+            2.0 * depth - 1.0,
+        );
+        self.try_inverse().map(|m| m.transform_point(ndc))
+    }
+}
+
+impl Ray {
+    /// Return a ray, normalizing its direction.
+    #[inline]
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self(origin, direction.normalize_precise())
+    }
+
+    /// Return a ray from the near plane through a point on the screen, which
+    /// picks whatever is drawn in that point with the view projection matrix.
+    #[inline]
+    pub fn from_screen(m: Mat4, screen_xy: Vec2, size: Vec2) -> Option<Self> {
+        let near = m.unproject(screen_xy, 0.0, size)?;
+        let far = m.unproject(screen_xy, 1.0, size)?;
+        Some(Self::new(near, far - near))
+    }
+
+    /// Return a point at the distance along the ray.
+    #[inline]
+    pub fn at(self, distance: f32) -> Vec3 {
+        self.0 + self.1 * distance
+    }
+
+    /// Return a distance to the plane, or None if the ray is parallel to the
+    /// plane or points away from it.
+    #[inline]
+    pub fn intersect_plane(self, p: Plane) -> Option<f32> {
+        let d = p.0.dot(self.1);
+        if d == 0.0 {
+            return None;
+        }
+        let t = -p.distance(self.0) / d;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// Return a distance to the sphere surface, which is 0 if the ray starts
+    /// inside the sphere, or None if the ray misses it.
+    #[inline]
+    pub fn intersect_sphere(self, s: Sphere) -> Option<f32> {
+        if s.is_empty() {
+            return None;
+        }
+        let oc = self.0 - s.0;
+        let a = self.1.len2();
+        let b = oc.dot(self.1);
+        let c = oc.len2() - s.1 * s.1;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let discriminant = b * b - a * c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        Some((-b - discriminant.sqrt()) / a)
+    }
+
+    /// Return a distance to the box surface, which is 0 if the ray starts
+    /// inside the box, or None if the ray misses it. The box is tested slab
+    /// by slab, pairs of planes perpendicular to each of the axes.
+    #[inline]
+    pub fn intersect_aabb(self, b: Aabb) -> Option<f32> {
+        let mut near = 0.0_f32;
+        let mut far = f32::INFINITY;
+        for (o, d, min, max) in [
+            (self.0 .0, self.1 .0, b.0 .0, b.1 .0),
+            (self.0 .1, self.1 .1, b.0 .1, b.1 .1),
+            (self.0 .2, self.1 .2, b.0 .2, b.1 .2),
+        ] {
+            if d == 0.0 {
+                if o < min || o > max {
+                    return None;
+                }
+                continue;
+            }
+            let (t0, t1) = ((min - o) / d, (max - o) / d);
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+
+    /// Return a hit of the triangle, or None if the ray misses it, found
+    /// with the Möller–Trumbore algorithm. Triangles are hit from both
+    /// sides.
+    #[inline]
+    pub fn intersect_triangle(self, a: Vec3, b: Vec3, c: Vec3) -> Option<Hit> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = self.1.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() < PARALLEL_DET {
+            return None;
+        }
+        let ao = self.0 - a;
+        let u = ao.dot(p) / det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = ao.cross(ab);
+        let v = self.1.dot(q) / det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = ac.dot(q) / det;
+        (distance >= 0.0).then_some(Hit { distance, u, v })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Approx, Hit, Mat4, Plane, Ray, Sphere, Vec2, Vec3};
+    use std::f32::consts::FRAC_PI_2;
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Ray {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Ray({:?}, {:?})", self.0, self.1)
+        }
+    }
+
+    impl Debug for Hit {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(
+                f,
+                "Hit {{ distance: {:.4}, u: {:.4}, v: {:.4} }}",
+                self.distance, self.u, self.v
+            )
+        }
+    }
+
+    #[test]
+    fn unproject() {
+        let m = Mat4::perspective(FRAC_PI_2, 2.0, 1.0, 10.0);
+        let s = Vec2(800.0, 400.0);
+        let a = m.unproject(Vec2(400.0, 200.0), 0.0, s);
+        assert_eq!(Some(Vec3(0.0, 0.0, -1.0)), a);
+        let a = m.unproject(Vec2(800.0, 0.0), 1.0, s);
+        assert_eq!(Some(Vec3(20.0, 10.0, -10.0)), a);
+
+        // Unprojection reverts the projection
+        let p = Vec3(1.2, -0.7, -3.4);
+        let ndc = m.transform_point(p);
+        let xy = Vec2((ndc.0 + 1.0) * 400.0, (1.0 - ndc.1) * 200.0);
+        assert_eq!(Some(p), m.unproject(xy, (ndc.2 + 1.0) * 0.5, s));
+
+        let m = Mat4::scaling(Vec3(1.0, 0.0, 1.0));
+        assert_eq!(None, m.unproject(Vec2(400.0, 200.0), 0.0, s));
+    }
+
+    #[test]
+    fn from_screen() {
+        let view = Mat4::look_at(
+            Vec3(0.0, 0.0, 5.0),
+            Vec3(0.0, 0.0, 0.0),
+            Vec3(0.0, 1.0, 0.0),
+        )
+        .inverse_rigid();
+        let m = view * Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0);
+        let s = Vec2(400.0, 400.0);
+
+        // Center of the screen looks at the origin
+        let a = Ray::from_screen(m, Vec2(200.0, 200.0), s);
+        assert_eq!(Some(Ray(Vec3(0.0, 0.0, 4.0), Vec3(0.0, 0.0, -1.0))), a);
+
+        // Top right corner is 45 degrees off both horizontally and vertically
+        let a = Ray::from_screen(m, Vec2(400.0, 0.0), s).unwrap();
+        assert_eq!(Vec3(1.0, 1.0, 4.0), a.0);
+        assert_eq!(Vec3(0.5774, 0.5774, -0.5774), a.1);
+    }
+
+    #[test]
+    fn at() {
+        let a = Ray::new(Vec3(1.0, 2.0, 3.0), Vec3(0.0, 3.0, 4.0));
+        assert_eq!(Vec3(1.0, 5.0, 7.0), a.at(5.0));
+    }
+
+    #[test]
+    fn intersect_plane() {
+        let p = Plane(Vec3(0.0, 0.0, 1.0), 0.0);
+        let a = Ray::new(Vec3(1.0, 1.0, 2.0), Vec3(0.0, 0.6, -0.8));
+        assert!(a.intersect_plane(p).unwrap().approx_eq(2.5));
+        let a = Ray::new(Vec3(1.0, 1.0, 2.0), Vec3(0.0, 0.6, 0.8));
+        assert_eq!(None, a.intersect_plane(p));
+        let a = Ray::new(Vec3(1.0, 1.0, 2.0), Vec3(0.0, 1.0, 0.0));
+        assert_eq!(None, a.intersect_plane(p));
+    }
+
+    #[test]
+    fn intersect_sphere() {
+        let s = Sphere(Vec3(0.0, 0.0, -5.0), 1.0);
+        let a = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0));
+        assert!(a.intersect_sphere(s).unwrap().approx_eq(4.0));
+        let a = Ray::new(Vec3(0.0, 0.5, -5.0), Vec3(0.0, 0.0, -1.0));
+        assert_eq!(Some(0.0), a.intersect_sphere(s));
+        let a = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 1.0));
+        assert_eq!(None, a.intersect_sphere(s));
+        let a = Ray::new(Vec3(0.0, 1.1, 0.0), Vec3(0.0, 0.0, -1.0));
+        assert_eq!(None, a.intersect_sphere(s));
+    }
+
+    #[test]
+    fn intersect_aabb() {
+        let b = Aabb(Vec3(-1.0, -1.0, -6.0), Vec3(1.0, 1.0, -4.0));
+        let a = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0));
+        assert!(a.intersect_aabb(b).unwrap().approx_eq(4.0));
+        let a = Ray::new(Vec3(3.0, 0.0, -2.0), Vec3(-1.0, 0.0, -1.0));
+        assert!(a.intersect_aabb(b).unwrap().approx_eq(2.8284));
+        let a = Ray::new(Vec3(0.0, 0.0, -5.0), Vec3(1.0, 0.0, 0.0));
+        assert_eq!(Some(0.0), a.intersect_aabb(b));
+        let a = Ray::new(Vec3(0.0, 2.0, 0.0), Vec3(0.0, 0.0, -1.0));
+        assert_eq!(None, a.intersect_aabb(b));
+        let a = Ray::new(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 1.0));
+        assert_eq!(None, a.intersect_aabb(b));
+    }
+
+    #[test]
+    fn intersect_triangle() {
+        let (p0, p1, p2) = (
+            Vec3(0.0, 0.0, -2.0),
+            Vec3(2.0, 0.0, -2.0),
+            Vec3(0.0, 2.0, -2.0),
+        );
+        let a = Ray::new(Vec3(0.5, 1.0, 0.0), Vec3(0.0, 0.0, -1.0));
+        let hit = Hit {
+            distance: 2.0,
+            u: 0.25,
+            v: 0.5,
+        };
+        assert_eq!(Some(hit), a.intersect_triangle(p0, p1, p2));
+
+        // Barycentric coordinates interpolate the vertices to the hit point
+        let hit = a.intersect_triangle(p0, p1, p2).unwrap();
+        let p = p0 * (1.0 - hit.u - hit.v) + p1 * hit.u + p2 * hit.v;
+        assert_eq!(a.at(hit.distance), p);
+
+        // Both sides are hit, but not the triangle behind the ray
+        assert_eq!(
+            Some(hit),
+            a.intersect_triangle(p0, p2, p1).map(|h| Hit {
+                distance: h.distance,
+                u: h.v,
+                v: h.u,
+            })
+        );
+        let a = Ray::new(Vec3(0.5, 1.0, 0.0), Vec3(0.0, 0.0, 1.0));
+        assert_eq!(None, a.intersect_triangle(p0, p1, p2));
+
+        // Outside of the triangle and parallel to it
+        let a = Ray::new(Vec3(1.5, 1.5, 0.0), Vec3(0.0, 0.0, -1.0));
+        assert_eq!(None, a.intersect_triangle(p0, p1, p2));
+        let a = Ray::new(Vec3(0.5, 1.0, -2.0), Vec3(1.0, 0.0, 0.0));
+        assert_eq!(None, a.intersect_triangle(p0, p1, p2));
+    }
+}
@@ -0,0 +1,350 @@
+//! SIMD implementation of the Mat4 arithmetic, enabled by the `simd` feature.
+//!
+//! Every algorithm is written once against a tiny 4-lane API (`load`, `store`,
+//! `splat`, `add`, `sub`, `mul` and the `shuffle!` macro), which is backed by
+//! `simd128` on wasm32, by SSE on x86_64 (to be able to compare the paths in
+//! benches) and by plain arrays on any other target. Note that a wasm module
+//! has to be built with `-C target-feature=+simd128` to use the v128 backend.
+//!
+//! Matrix multiplication and vector transformation perform the very same
+//! operations in the same order as the scalar path, so the results are equal
+//! bit-for-bit. Determinant and inverse use a different grouping of the
+//! cofactor expansion and therefore agree with the scalar path within EPS.
+
+use crate::core::{Mat4, Vec4};
+use arch::*;
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    //! SSE is a part of the x86_64 baseline, so its intrinsics are always
+    //! available and safe to call.
+    use crate::core::Vec4;
+    pub use std::arch::x86_64::__m128 as F4;
+    use std::arch::x86_64::*;
+
+    /// Return a vector of lanes `[a[x], a[y], b[z], b[w]]`.
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $x:literal, $y:literal, $z:literal, $w:literal) => {
+            unsafe {
+                std::arch::x86_64::_mm_shuffle_ps::<{ ($w << 6) | ($z << 4) | ($y << 2) | $x }>(
+                    $a, $b,
+                )
+            }
+        };
+    }
+    pub(super) use shuffle;
+
+    #[inline]
+    pub fn load(v: Vec4) -> F4 {
+        unsafe { _mm_set_ps(v.3, v.2, v.1, v.0) }
+    }
+
+    #[inline]
+    pub fn store(a: F4) -> Vec4 {
+        let mut v = Vec4(0.0, 0.0, 0.0, 0.0);
+        unsafe { _mm_storeu_ps(&mut v as *mut Vec4 as *mut f32, a) };
+        v
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F4 {
+        unsafe { _mm_set1_ps(x) }
+    }
+
+    #[inline]
+    pub fn add(a: F4, b: F4) -> F4 {
+        unsafe { _mm_add_ps(a, b) }
+    }
+
+    #[inline]
+    pub fn sub(a: F4, b: F4) -> F4 {
+        unsafe { _mm_sub_ps(a, b) }
+    }
+
+    #[inline]
+    pub fn mul(a: F4, b: F4) -> F4 {
+        unsafe { _mm_mul_ps(a, b) }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod arch {
+    use crate::core::Vec4;
+    pub use std::arch::wasm32::v128 as F4;
+    use std::arch::wasm32::*;
+
+    /// Return a vector of lanes `[a[x], a[y], b[z], b[w]]`.
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $x:literal, $y:literal, $z:literal, $w:literal) => {
+            std::arch::wasm32::i32x4_shuffle::<$x, $y, { $z + 4 }, { $w + 4 }>($a, $b)
+        };
+    }
+    pub(super) use shuffle;
+
+    #[inline]
+    pub fn load(v: Vec4) -> F4 {
+        f32x4(v.0, v.1, v.2, v.3)
+    }
+
+    #[inline]
+    pub fn store(a: F4) -> Vec4 {
+        Vec4(
+            f32x4_extract_lane::<0>(a),
+            f32x4_extract_lane::<1>(a),
+            f32x4_extract_lane::<2>(a),
+            f32x4_extract_lane::<3>(a),
+        )
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F4 {
+        f32x4_splat(x)
+    }
+
+    #[inline]
+    pub fn add(a: F4, b: F4) -> F4 {
+        f32x4_add(a, b)
+    }
+
+    #[inline]
+    pub fn sub(a: F4, b: F4) -> F4 {
+        f32x4_sub(a, b)
+    }
+
+    #[inline]
+    pub fn mul(a: F4, b: F4) -> F4 {
+        f32x4_mul(a, b)
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+mod arch {
+    use crate::core::Vec4;
+    pub type F4 = [f32; 4];
+
+    /// Return a vector of lanes `[a[x], a[y], b[z], b[w]]`.
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $x:literal, $y:literal, $z:literal, $w:literal) => {{
+            let (a, b): (F4, F4) = ($a, $b);
+            [a[$x], a[$y], b[$z], b[$w]]
+        }};
+    }
+    pub(super) use shuffle;
+
+    #[inline]
+    pub fn load(v: Vec4) -> F4 {
+        [v.0, v.1, v.2, v.3]
+    }
+
+    #[inline]
+    pub fn store(a: F4) -> Vec4 {
+        Vec4(a[0], a[1], a[2], a[3])
+    }
+
+    #[inline]
+    pub fn splat(x: f32) -> F4 {
+        [x; 4]
+    }
+
+    #[inline]
+    pub fn add(a: F4, b: F4) -> F4 {
+        [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+    }
+
+    #[inline]
+    pub fn sub(a: F4, b: F4) -> F4 {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+    }
+
+    #[inline]
+    pub fn mul(a: F4, b: F4) -> F4 {
+        [a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]]
+    }
+}
+
+/// Return the columns of the matrix, stored in rows of the transposed one.
+#[inline]
+fn transpose(r0: F4, r1: F4, r2: F4, r3: F4) -> [F4; 4] {
+    let t0 = shuffle!(r0, r1, 0, 1, 0, 1);
+    let t1 = shuffle!(r0, r1, 2, 3, 2, 3);
+    let t2 = shuffle!(r2, r3, 0, 1, 0, 1);
+    let t3 = shuffle!(r2, r3, 2, 3, 2, 3);
+    [
+        shuffle!(t0, t2, 0, 2, 0, 2),
+        shuffle!(t0, t2, 1, 3, 1, 3),
+        shuffle!(t1, t3, 0, 2, 0, 2),
+        shuffle!(t1, t3, 1, 3, 1, 3),
+    ]
+}
+
+/// Same as `Mat4 * Mat4`.
+#[inline]
+pub fn mat4_mul(a: Mat4, b: Mat4) -> Mat4 {
+    let (b0, b1, b2, b3) = (load(b.0), load(b.1), load(b.2), load(b.3));
+    let row = |r: Vec4| {
+        store(add(
+            add(
+                add(mul(splat(r.0), b0), mul(splat(r.1), b1)),
+                mul(splat(r.2), b2),
+            ),
+            mul(splat(r.3), b3),
+        ))
+    };
+    Mat4(row(a.0), row(a.1), row(a.2), row(a.3))
+}
+
+/// Same as `Mat4 * Vec4`.
+#[inline]
+pub fn mat4_transform(m: Mat4, v: Vec4) -> Vec4 {
+    let [c0, c1, c2, c3] = transpose(load(m.0), load(m.1), load(m.2), load(m.3));
+    store(add(
+        add(
+            add(mul(splat(v.0), c0), mul(splat(v.1), c1)),
+            mul(splat(v.2), c2),
+        ),
+        mul(splat(v.3), c3),
+    ))
+}
+
+/// Determinants of 2x2 minors of the matrix, built from the pairs of columns
+/// (0, 1), (0, 2), (0, 3), (1, 2), (1, 3) and (2, 3) of the first two rows
+/// (`s`) and of the last two rows (`c`). The last vector of each pair holds
+/// two remaining minors twice.
+#[inline]
+fn minors(m: Mat4) -> (F4, F4, F4, F4) {
+    let (r0, r1, r2, r3) = (load(m.0), load(m.1), load(m.2), load(m.3));
+    let minor = |a: F4, b: F4| {
+        (
+            sub(
+                mul(shuffle!(a, a, 0, 0, 0, 1), shuffle!(b, b, 1, 2, 3, 2)),
+                mul(shuffle!(b, b, 0, 0, 0, 1), shuffle!(a, a, 1, 2, 3, 2)),
+            ),
+            sub(
+                mul(shuffle!(a, a, 1, 2, 1, 2), shuffle!(b, b, 3, 3, 3, 3)),
+                mul(shuffle!(b, b, 1, 2, 1, 2), shuffle!(a, a, 3, 3, 3, 3)),
+            ),
+        )
+    };
+    let (s0123, s45) = minor(r0, r1);
+    let (c0123, c45) = minor(r2, r3);
+    (s0123, s45, c0123, c45)
+}
+
+#[inline]
+fn det_of_minors(s0123: F4, s45: F4, c0123: F4, c45: F4) -> f32 {
+    let a = store(mul(s0123, shuffle!(c45, c0123, 1, 0, 3, 2)));
+    let b = store(mul(s45, shuffle!(c0123, c0123, 1, 0, 1, 0)));
+    a.0 - a.1 + a.2 + a.3 - b.0 + b.1
+}
+
+/// Same as `Mat4::det`.
+#[inline]
+pub fn mat4_det(m: Mat4) -> f32 {
+    let (s0123, s45, c0123, c45) = minors(m);
+    det_of_minors(s0123, s45, c0123, c45)
+}
+
+/// Same as `Mat4::inverse`.
+#[inline]
+pub fn mat4_inverse(m: Mat4) -> Mat4 {
+    let (s0123, s45, c0123, c45) = minors(m);
+    let d = det_of_minors(s0123, s45, c0123, c45);
+
+    // Pairs of minors [c, c, s, s] to multiply the columns by.
+    let cs0 = shuffle!(c0123, s0123, 0, 0, 0, 0);
+    let cs1 = shuffle!(c0123, s0123, 1, 1, 1, 1);
+    let cs2 = shuffle!(c0123, s0123, 2, 2, 2, 2);
+    let cs3 = shuffle!(c0123, s0123, 3, 3, 3, 3);
+    let cs4 = shuffle!(c45, s45, 0, 0, 0, 0);
+    let cs5 = shuffle!(c45, s45, 1, 1, 1, 1);
+
+    // Columns of the matrix with swapped lanes and alternating signs:
+    // [a1j, -a0j, a3j, -a2j].
+    let [c0, c1, c2, c3] = transpose(load(m.0), load(m.1), load(m.2), load(m.3));
+    let sign = load(Vec4(1.0, -1.0, 1.0, -1.0));
+    let p0 = mul(shuffle!(c0, c0, 1, 0, 3, 2), sign);
+    let p1 = mul(shuffle!(c1, c1, 1, 0, 3, 2), sign);
+    let p2 = mul(shuffle!(c2, c2, 1, 0, 3, 2), sign);
+    let p3 = mul(shuffle!(c3, c3, 1, 0, 3, 2), sign);
+
+    let r = splat(1.0 / d);
+    let row = |x: F4| store(mul(x, r));
+    Mat4(
+        row(add(sub(mul(p1, cs5), mul(p2, cs4)), mul(p3, cs3))),
+        row(sub(sub(mul(p2, cs2), mul(p0, cs5)), mul(p3, cs1))),
+        row(add(sub(mul(p0, cs4), mul(p1, cs2)), mul(p3, cs0))),
+        row(sub(sub(mul(p1, cs1), mul(p0, cs3)), mul(p2, cs0))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{simd, Approx, Mat4, Vec4};
+
+    fn matrices() -> [Mat4; 4] {
+        [
+            Mat4::identity(),
+            Mat4(
+                Vec4(-2.3, 4.5, -0.7, 2.3),
+                Vec4(-1.3, -2.1, 4.8, 0.1),
+                Vec4(5.4, -0.2, -1.8, -1.2),
+                Vec4(1.2, 1.4, 1.6, 1.0),
+            ),
+            Mat4(
+                Vec4(0.1, -2.3, 1.1, 1.0),
+                Vec4(-0.3, -1.2, 1.2, 1.0),
+                Vec4(2.3, 1.1, -2.0, -4.0),
+                Vec4(1.2, 1.1, 1.4, 3.1),
+            ),
+            Mat4::perspective(1.2, 0.5625, 0.1, 100.0).translate(Vec4(1.0, 2.0, 3.0, 1.0).into()),
+        ]
+    }
+
+    fn bits(m: Mat4) -> [u32; 16] {
+        [
+            m.0 .0, m.0 .1, m.0 .2, m.0 .3, m.1 .0, m.1 .1, m.1 .2, m.1 .3, m.2 .0, m.2 .1, m.2 .2,
+            m.2 .3, m.3 .0, m.3 .1, m.3 .2, m.3 .3,
+        ]
+        .map(f32::to_bits)
+    }
+
+    #[test]
+    fn mul() {
+        for a in matrices() {
+            for b in matrices() {
+                assert_eq!(bits(a.scalar_mul(b)), bits(simd::mat4_mul(a, b)));
+            }
+        }
+    }
+
+    #[test]
+    fn transform() {
+        let v = Vec4(1.2, -3.4, 5.6, 1.0);
+        for a in matrices() {
+            let x = a.scalar_transform(v);
+            let y = simd::mat4_transform(a, v);
+            assert_eq!(
+                [x.0, x.1, x.2, x.3].map(f32::to_bits),
+                [y.0, y.1, y.2, y.3].map(f32::to_bits)
+            );
+        }
+    }
+
+    #[test]
+    fn det() {
+        for a in matrices() {
+            assert!(a.scalar_det().approx_eq(simd::mat4_det(a)));
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for a in matrices() {
+            assert_eq!(a.scalar_inverse(), simd::mat4_inverse(a));
+            assert_eq!(a * simd::mat4_inverse(a), Mat4::identity());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::core::{Mat4, Quat, Vec3};
+
+/// Transform is a decomposed affine transformation: a scaling, followed by
+/// a rotation and a translation. Unlike a matrix it can be interpolated
+/// component-wise, which is how animations and scene nodes store it.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl PartialEq for Transform {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.translation == rhs.translation
+            && self.rotation == rhs.rotation
+            && self.scale == rhs.scale
+    }
+}
+
+impl Default for Transform {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Transform> for Mat4 {
+    #[inline]
+    fn from(t: Transform) -> Self {
+        Mat4::compose(t.translation, t.scale, t.rotation)
+    }
+}
+
+impl From<Mat4> for Transform {
+    /// Return a decomposition of an affine matrix without shear.
+    #[inline]
+    fn from(m: Mat4) -> Self {
+        let (translation, scale, rotation) = m.decompose();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+}
+
+impl Transform {
+    #[inline]
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Return a transformation that keeps everything in place.
+    #[inline]
+    pub fn identity() -> Self {
+        Self {
+            translation: Vec3(0.0, 0.0, 0.0),
+            rotation: Quat::identity(),
+            scale: Vec3(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// Return a matrix of the transformation.
+    #[inline]
+    pub fn to_mat4(self) -> Mat4 {
+        Mat4::from(self)
+    }
+
+    /// Linear interpolation between two transformations, normalized linear
+    /// for the rotations.
+    #[inline]
+    pub fn lerp(self, rhs: Self, t: f32) -> Self {
+        Self {
+            translation: self.translation + (rhs.translation - self.translation) * t,
+            rotation: self.rotation.nlerp(rhs.rotation, t),
+            scale: self.scale + (rhs.scale - self.scale) * t,
+        }
+    }
+
+    /// Return a difference between the transformation and a reference one,
+    /// such that adding it to the reference gives the transformation back.
+    /// A zero scale of the reference, such as of a joint hidden by scaling,
+    /// can't be undone, so its delta is 1, which keeps the scale it is
+    /// added to.
+    #[inline]
+    pub fn delta(self, reference: Self) -> Self {
+        let ratio = |s: f32, r: f32| if r == 0.0 { 1.0 } else { s / r };
+        Self {
+            translation: self.translation - reference.translation,
+            rotation: reference.rotation.conjugate() * self.rotation,
+            scale: Vec3(
+                ratio(self.scale.0, reference.scale.0),
+                ratio(self.scale.1, reference.scale.1),
+                ratio(self.scale.2, reference.scale.2),
+            ),
+        }
+    }
+
+    /// Return the transformation with a weighted difference added on top,
+    /// the way additive animations are applied.
+    #[inline]
+    pub fn add_weighted(self, delta: Self, weight: f32) -> Self {
+        let delta = Self::identity().lerp(delta, weight);
+        Self {
+            translation: self.translation + delta.translation,
+            rotation: self.rotation * delta.rotation,
+            scale: Vec3(
+                self.scale.0 * delta.scale.0,
+                self.scale.1 * delta.scale.1,
+                self.scale.2 * delta.scale.2,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Mat4, Quat, Transform, Vec3};
+    use std::f32::consts::FRAC_PI_3;
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Transform {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(
+                f,
+                "Transform({:?}, {:?}, {:?})",
+                self.translation, self.rotation, self.scale
+            )
+        }
+    }
+
+    #[test]
+    fn identity() {
+        assert_eq!(Mat4::identity(), Transform::identity().to_mat4());
+        assert_eq!(Transform::identity(), Mat4::identity().into());
+    }
+
+    #[test]
+    fn to_mat4() {
+        let q = Quat::from_axis_angle(Vec3(0.4570, 0.8437, 0.2812), FRAC_PI_3);
+        let a = Transform::new(Vec3(1.2, 1.4, 1.6), q, Vec3(1.8, 2.0, 2.2));
+        let m = Mat4::compose(Vec3(1.2, 1.4, 1.6), Vec3(1.8, 2.0, 2.2), q);
+        assert_eq!(m, a.to_mat4());
+        assert_eq!(a, m.into());
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Transform::identity();
+        let b = Transform::new(
+            Vec3(2.0, 4.0, 6.0),
+            Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_3),
+            Vec3(3.0, 3.0, 3.0),
+        );
+        let c = a.lerp(b, 0.5);
+        assert_eq!(Vec3(1.0, 2.0, 3.0), c.translation);
+        assert_eq!(Vec3(2.0, 2.0, 2.0), c.scale);
+        assert_eq!(
+            Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_3 / 2.0),
+            c.rotation
+        );
+        assert_eq!(a, a.lerp(b, 0.0));
+        assert_eq!(b, a.lerp(b, 1.0));
+    }
+
+    #[test]
+    fn delta() {
+        let q = Quat::from_axis_angle(Vec3(0.4570, 0.8437, 0.2812), FRAC_PI_3);
+        let a = Transform::new(Vec3(1.2, 1.4, 1.6), q, Vec3(1.8, 2.0, 2.2));
+        let b = Transform::new(
+            Vec3(0.2, -0.4, 0.6),
+            Quat::from_axis_angle(Vec3(1.0, 0.0, 0.0), FRAC_PI_3),
+            Vec3(2.0, 1.0, 0.5),
+        );
+        let d = a.delta(b);
+        assert_eq!(a, b.add_weighted(d, 1.0));
+        assert_eq!(b, b.add_weighted(d, 0.0));
+        assert_eq!(Transform::identity(), a.delta(a));
+
+        // A zero scale of the reference keeps the scale
+        let hidden = Transform::new(Vec3(0.0, 0.0, 0.0), q, Vec3(0.0, 2.0, 0.0));
+        let d = a.delta(hidden);
+        assert_eq!(Vec3(1.0, 1.0, 1.0), d.scale);
+        assert_eq!(b.scale, b.add_weighted(d, 0.5).scale);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::core::{Approx, FastSqrt};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+/// Vec2 is a data structure that represent 2-component vector. It can be used
+/// both for storing state data in memory as well as to perform calculations.
+/// For performance sake the overflow checks are omitted for math operations as
+/// it is user code responsibility to pick a suitable data type for components
+/// for a specific purpose.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Vec2(pub f32, pub f32);
+
+impl PartialEq for Vec2 {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.approx_eq(*rhs)
+    }
+}
+
+impl Approx for Vec2 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps) && self.1.approx_eq_eps(rhs.1, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel) && self.1.approx_eq_rel(rhs.1, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps) && self.1.approx_eq_ulps(rhs.1, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0) && self.1.bitwise_eq(rhs.1)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl AddAssign for Vec2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl SubAssign for Vec2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Scalar multiplication
+impl<T: Into<f32> + Copy> Mul<T> for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: T) -> Self {
+        Self(self.0 * rhs.into(), self.1 * rhs.into())
+    }
+}
+
+/// Scalar multiplication
+impl<T: Into<f32> + Copy> MulAssign<T> for Vec2 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: T) {
+        *self = *self * rhs;
+    }
+}
+
+/// Scalar division
+impl<T: Into<f32> + Copy> Div<T> for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: T) -> Self {
+        Self(self.0 / rhs.into(), self.1 / rhs.into())
+    }
+}
+
+/// Scalar division
+impl<T: Into<f32> + Copy> DivAssign<T> for Vec2 {
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        *self = *self / rhs;
+    }
+}
+
+impl Vec2 {
+    /// Squared length of the vector
+    #[inline]
+    pub fn len2(self) -> f32 {
+        self.0 * self.0 + self.1 * self.1
+    }
+
+    /// Length of the vector
+    #[inline]
+    pub fn len(self) -> f32 {
+        self.len2().sqrt()
+    }
+
+    /// Normalize vector turing it's length to 1 using a fast inverse square
+    /// root approximation, which is off by up to 0.2%. Use `normalize_precise`
+    /// or `try_normalize` where the error accumulates or the length may be 0.
+    #[inline]
+    pub fn normalize(self) -> Self {
+        let isqrt = self.len2().isqrt();
+        Self(self.0 * isqrt, self.1 * isqrt)
+    }
+
+    /// Normalize vector turing it's length to 1 with an exact square root,
+    /// which is slower than `normalize` but keeps the length within a few
+    /// ULPs of 1. A zero vector has no direction and is returned as is.
+    #[inline]
+    pub fn normalize_precise(self) -> Self {
+        self.try_normalize().unwrap_or(self)
+    }
+
+    /// Return a vector normalized with an exact square root, or None if the
+    /// vector has zero or non-finite length and thereby no direction. The
+    /// vector is scaled by its largest component first, so that the squared
+    /// length of large or tiny vectors doesn't overflow or underflow.
+    #[inline]
+    pub fn try_normalize(self) -> Option<Self> {
+        let max = self.0.abs().max(self.1.abs());
+        let v = self / max;
+        let len = v.len();
+        if max > 0.0 && max.is_finite() && len.is_finite() {
+            Some(v / len)
+        } else {
+            None
+        }
+    }
+
+    /// Return a vector rotated by 90 degrees counter-clockwise
+    #[inline]
+    pub fn perp(self) -> Self {
+        Self(-self.1, self.0)
+    }
+
+    /// Cross product of two 2D vectors, which is a z component of the cross
+    /// product of the same vectors in 3D. Its sign tells on which side of
+    /// the vector the other one is.
+    #[inline]
+    pub fn cross(self, rhs: Self) -> f32 {
+        self.0 * rhs.1 - self.1 * rhs.0
+    }
+
+    /// Dot product of two 2D vectors
+    #[inline]
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.0 * rhs.0 + self.1 * rhs.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Approx, Vec2};
+    use std::fmt::{Debug, Formatter, Result};
+
+    impl Debug for Vec2 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "Vec2({:.4}, {:.4})", self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn equal() {
+        let a = Vec2(0.32, 0.64);
+        let b = Vec2(0.32, 0.64);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn copy() {
+        let a = Vec2(0.32, 0.64);
+        let b = a;
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn add() {
+        let a = Vec2(0.1, 255.0);
+        let b = Vec2(255.0, 0.1);
+        let c = Vec2(255.1, 255.1);
+        assert_eq!(a + b, c);
+    }
+
+    #[test]
+    fn sub() {
+        let a = Vec2(8.0, 16.0);
+        let b = Vec2(8.0, 8.0);
+        let c = Vec2(0.0, 8.0);
+        assert_eq!(a - b, c);
+    }
+
+    #[test]
+    fn mul() {
+        let a = Vec2(8.0, 16.0);
+        let b: f32 = 2.0;
+        let c = Vec2(16.0, 32.0);
+        assert_eq!(a * b, c);
+    }
+
+    #[test]
+    fn div() {
+        let a = Vec2(8.0, 16.0);
+        let b: f32 = 2.0;
+        let c = Vec2(4.0, 8.0);
+        assert_eq!(a / b, c);
+    }
+
+    #[test]
+    fn len2() {
+        let a = Vec2(3.0, 4.0);
+        assert_eq!(25.0, a.len2());
+    }
+
+    #[test]
+    fn len() {
+        let a = Vec2(3.0, 4.0);
+        assert_eq!(5.0, a.len());
+    }
+
+    #[test]
+    fn normalize() {
+        let a = Vec2(3.0, 4.0);
+        let i = Vec2(0.6, 0.8);
+        assert_eq!(i, a.normalize());
+    }
+
+    #[test]
+    fn try_normalize() {
+        let a = Vec2(3.0, 4.0);
+        assert_eq!(Some(Vec2(0.6, 0.8)), a.try_normalize());
+        assert_eq!(Vec2(0.0, 0.0), Vec2(0.0, 0.0).normalize_precise());
+        assert_eq!(None, Vec2(0.0, 0.0).try_normalize());
+        assert_eq!(Some(Vec2(0.6, 0.8)), Vec2(3e30, 4e30).try_normalize());
+        assert!(Vec2(3e-30, 4e-30).try_normalize().is_some());
+    }
+
+    #[test]
+    fn perp() {
+        let a = Vec2(3.0, 4.0);
+        assert_eq!(Vec2(-4.0, 3.0), a.perp());
+        assert_eq!(0.0, a.dot(a.perp()));
+    }
+
+    #[test]
+    fn cross() {
+        let a = Vec2(1.3, 2.1);
+        let b = Vec2(2.2, 1.8);
+        assert!(a.cross(b).approx_eq(-2.28));
+        assert!(b.cross(a).approx_eq(2.28));
+    }
+
+    #[test]
+    fn dot() {
+        let a = Vec2(1.0, 3.0);
+        let b = Vec2(4.0, -2.0);
+        assert_eq!(-2.0, a.dot(b));
+    }
+}
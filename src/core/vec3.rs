@@ -1,4 +1,4 @@
-use crate::core::{Approx, Vec4};
+use crate::core::{Approx, FastSqrt, Vec4};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 /// Vec3 is a data structure that represent 3-component vector. It can be used
@@ -13,7 +13,35 @@ pub struct Vec3(pub f32, pub f32, pub f32);
 impl PartialEq for Vec3 {
     #[inline]
     fn eq(&self, rhs: &Self) -> bool {
-        self.0.approx_eq(rhs.0) && self.1.approx_eq(rhs.1) && self.2.approx_eq(rhs.2)
+        self.approx_eq(*rhs)
+    }
+}
+
+impl Approx for Vec3 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0) && self.1.bitwise_eq(rhs.1) && self.2.bitwise_eq(rhs.2)
     }
 }
 
@@ -24,10 +52,10 @@ impl From<Vec4> for Vec3 {
     }
 }
 
-impl Into<Vec4> for Vec3 {
+impl From<Vec3> for Vec4 {
     #[inline]
-    fn into(self) -> Vec4 {
-        Vec4(self.0, self.1, self.2, 1.0)
+    fn from(v: Vec3) -> Self {
+        Vec4(v.0, v.1, v.2, 1.0)
     }
 }
 
@@ -120,13 +148,51 @@ impl Vec3 {
         self.len2().sqrt()
     }
 
-    /// Normalize vector turing it's length to 1
+    /// Normalize vector turing it's length to 1 using a fast inverse square
+    /// root approximation, which is off by up to 0.2%. Use `normalize_precise`
+    /// or `try_normalize` where the error accumulates or the length may be 0.
     #[inline]
     pub fn normalize(self) -> Self {
         let isqrt = self.len2().isqrt();
         Self(self.0 * isqrt, self.1 * isqrt, self.2 * isqrt)
     }
 
+    /// Normalize vector turing it's length to 1 with an exact square root,
+    /// which is slower than `normalize` but keeps the length within a few
+    /// ULPs of 1. A zero vector has no direction and is returned as is.
+    #[inline]
+    pub fn normalize_precise(self) -> Self {
+        self.try_normalize().unwrap_or(self)
+    }
+
+    /// Return a vector normalized with an exact square root, or None if the
+    /// vector has zero or non-finite length and thereby no direction. The
+    /// vector is scaled by its largest component first, so that the squared
+    /// length of large or tiny vectors doesn't overflow or underflow.
+    #[inline]
+    pub fn try_normalize(self) -> Option<Self> {
+        let max = self.0.abs().max(self.1.abs()).max(self.2.abs());
+        let v = self / max;
+        let len = v.len();
+        if max > 0.0 && max.is_finite() && len.is_finite() {
+            Some(v / len)
+        } else {
+            None
+        }
+    }
+
+    /// Return a vector of the smallest components of the two vectors
+    #[inline]
+    pub fn min(self, rhs: Self) -> Self {
+        Self(self.0.min(rhs.0), self.1.min(rhs.1), self.2.min(rhs.2))
+    }
+
+    /// Return a vector of the largest components of the two vectors
+    #[inline]
+    pub fn max(self, rhs: Self) -> Self {
+        Self(self.0.max(rhs.0), self.1.max(rhs.1), self.2.max(rhs.2))
+    }
+
     /// Cross product of two 3D vectors
     #[inline]
     pub fn cross(self, rhs: Self) -> Self {
@@ -146,7 +212,7 @@ impl Vec3 {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Vec3, Vec4};
+    use crate::core::{Approx, Vec3, Vec4};
     use std::fmt::{Debug, Formatter, Result};
 
     impl Debug for Vec3 {
@@ -202,7 +268,7 @@ mod tests {
     #[test]
     fn mul() {
         let a = Vec3(8.0, 16.0, 32.0);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Vec3(16.0, 32.0, 64.0);
         assert_eq!(a * b, c);
     }
@@ -210,7 +276,7 @@ mod tests {
     #[test]
     fn div() {
         let a = Vec3(8.0, 16.0, 32.0);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Vec3(4.0, 8.0, 16.0);
         assert_eq!(a / b, c);
     }
@@ -234,6 +300,33 @@ mod tests {
         assert_eq!(i, a.normalize());
     }
 
+    #[test]
+    fn normalize_precise() {
+        let a = Vec3(2.0, 3.0, 6.0);
+        let i = Vec3(2.0 / 7.0, 3.0 / 7.0, 6.0 / 7.0);
+        assert!(i.approx_eq_ulps(a.normalize_precise(), 1));
+        assert!(a.normalize_precise().len().approx_eq_ulps(1.0, 1));
+        assert!(Vec3(0.0, 0.0, 0.0).bitwise_eq(Vec3(0.0, 0.0, 0.0).normalize_precise()));
+    }
+
+    #[test]
+    fn try_normalize() {
+        let a = Vec3(3.0, 4.0, 0.0);
+        assert_eq!(Some(Vec3(0.6, 0.8, 0.0)), a.try_normalize());
+        assert_eq!(None, Vec3(0.0, 0.0, 0.0).try_normalize());
+        assert_eq!(None, Vec3(f32::NAN, 1.0, 0.0).try_normalize());
+        assert_eq!(Some(Vec3(0.6, 0.8, 0.0)), Vec3(3e30, 4e30, 0.0).try_normalize());
+        assert!(Vec3(0.0, 3e-30, 4e-30).try_normalize().is_some());
+    }
+
+    #[test]
+    fn min_max() {
+        let a = Vec3(1.0, -2.0, 3.0);
+        let b = Vec3(-1.0, 2.0, 3.5);
+        assert_eq!(Vec3(-1.0, -2.0, 3.0), a.min(b));
+        assert_eq!(Vec3(1.0, 2.0, 3.5), a.max(b));
+    }
+
     #[test]
     fn cross() {
         let a = Vec3(1.3, 2.1, 3.4);
@@ -242,6 +335,20 @@ mod tests {
         assert_eq!(c, a.cross(b));
     }
 
+    #[test]
+    fn approx() {
+        let a = Vec3(10000.0, 20000.0, 0.5);
+        let b = Vec3(10000.1, 20000.0, 0.5);
+        assert!(a != b);
+        assert!(a.approx_eq_rel(b, 0.0001));
+        assert!(!a.approx_eq_eps(b, 0.001));
+        assert!(a.approx_eq_eps(b, 0.1));
+        assert!(a.approx_eq_ulps(a, 0));
+        assert!(!a.approx_eq_ulps(b, 4));
+        assert!(a.bitwise_eq(a));
+        assert!(!a.bitwise_eq(b));
+    }
+
     #[test]
     fn dot() {
         let a = Vec3(1.0, 3.0, -5.0);
@@ -1,4 +1,4 @@
-use crate::core::Approx;
+use crate::core::{Approx, FastSqrt};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 /// Vec4 is a data structure that represent 4-component vector. It can be used
@@ -13,10 +13,41 @@ pub struct Vec4(pub f32, pub f32, pub f32, pub f32);
 impl PartialEq for Vec4 {
     #[inline]
     fn eq(&self, rhs: &Self) -> bool {
-        self.0.approx_eq(rhs.0)
-            && self.1.approx_eq(rhs.1)
-            && self.2.approx_eq(rhs.2)
-            && self.3.approx_eq(rhs.3)
+        self.approx_eq(*rhs)
+    }
+}
+
+impl Approx for Vec4 {
+    #[inline]
+    fn approx_eq_eps(self, rhs: Self, eps: f32) -> bool {
+        self.0.approx_eq_eps(rhs.0, eps)
+            && self.1.approx_eq_eps(rhs.1, eps)
+            && self.2.approx_eq_eps(rhs.2, eps)
+            && self.3.approx_eq_eps(rhs.3, eps)
+    }
+
+    #[inline]
+    fn approx_eq_rel(self, rhs: Self, rel: f32) -> bool {
+        self.0.approx_eq_rel(rhs.0, rel)
+            && self.1.approx_eq_rel(rhs.1, rel)
+            && self.2.approx_eq_rel(rhs.2, rel)
+            && self.3.approx_eq_rel(rhs.3, rel)
+    }
+
+    #[inline]
+    fn approx_eq_ulps(self, rhs: Self, ulps: u32) -> bool {
+        self.0.approx_eq_ulps(rhs.0, ulps)
+            && self.1.approx_eq_ulps(rhs.1, ulps)
+            && self.2.approx_eq_ulps(rhs.2, ulps)
+            && self.3.approx_eq_ulps(rhs.3, ulps)
+    }
+
+    #[inline]
+    fn bitwise_eq(self, rhs: Self) -> bool {
+        self.0.bitwise_eq(rhs.0)
+            && self.1.bitwise_eq(rhs.1)
+            && self.2.bitwise_eq(rhs.2)
+            && self.3.bitwise_eq(rhs.3)
     }
 }
 
@@ -118,10 +149,12 @@ impl Vec4 {
     /// Length of the vector
     #[inline]
     pub fn len(self) -> f32 {
-        1.0 / self.len2().isqrt()
+        self.len2().sqrt()
     }
 
-    /// Normalize vector turing it's length to 1
+    /// Normalize vector turing it's length to 1 using a fast inverse square
+    /// root approximation, which is off by up to 0.2%. Use `normalize_precise`
+    /// or `try_normalize` where the error accumulates or the length may be 0.
     #[inline]
     pub fn normalize(self) -> Self {
         let isqrt = self.len2().isqrt();
@@ -132,6 +165,30 @@ impl Vec4 {
             self.3 * isqrt,
         )
     }
+
+    /// Normalize vector turing it's length to 1 with an exact square root,
+    /// which is slower than `normalize` but keeps the length within a few
+    /// ULPs of 1. A zero vector has no direction and is returned as is.
+    #[inline]
+    pub fn normalize_precise(self) -> Self {
+        self.try_normalize().unwrap_or(self)
+    }
+
+    /// Return a vector normalized with an exact square root, or None if the
+    /// vector has zero or non-finite length and thereby no direction. The
+    /// vector is scaled by its largest component first, so that the squared
+    /// length of large or tiny vectors doesn't overflow or underflow.
+    #[inline]
+    pub fn try_normalize(self) -> Option<Self> {
+        let max = self.0.abs().max(self.1.abs()).max(self.2.abs()).max(self.3.abs());
+        let v = self / max;
+        let len = v.len();
+        if max > 0.0 && max.is_finite() && len.is_finite() {
+            Some(v / len)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -182,7 +239,7 @@ mod tests {
     #[test]
     fn mul() {
         let a = Vec4(8.0, 16.0, 32.0, 0.0);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Vec4(16.0, 32.0, 64.0, 0.0);
         assert_eq!(a * b, c);
     }
@@ -190,7 +247,7 @@ mod tests {
     #[test]
     fn div() {
         let a = Vec4(8.0, 16.0, 32.0, 0.0);
-        let b = 2.0;
+        let b: f32 = 2.0;
         let c = Vec4(4.0, 8.0, 16.0, 0.0);
         assert_eq!(a / b, c);
     }
@@ -204,7 +261,7 @@ mod tests {
     #[test]
     fn len() {
         let a = Vec4(3.0, 4.0, 0.0, 0.0);
-        assert_eq!(true, a.len().approx_eq(5.0));
+        assert!(a.len().approx_eq(5.0));
     }
 
     #[test]
@@ -213,4 +270,35 @@ mod tests {
         let i = Vec4(0.6, 0.8, 0.0, 0.0);
         assert_eq!(i, a.normalize());
     }
+
+    #[test]
+    fn normalize_precise() {
+        let a = Vec4(1.0, 2.0, 2.0, 4.0);
+        let i = Vec4(0.2, 0.4, 0.4, 0.8);
+        assert!(i.approx_eq_ulps(a.normalize_precise(), 1));
+        assert!(a.normalize_precise().len().approx_eq_ulps(1.0, 1));
+        assert!(Vec4(0.0, 0.0, 0.0, 0.0).bitwise_eq(Vec4(0.0, 0.0, 0.0, 0.0).normalize_precise()));
+    }
+
+    #[test]
+    fn try_normalize() {
+        let a = Vec4(3.0, 4.0, 0.0, 0.0);
+        assert_eq!(Some(Vec4(0.6, 0.8, 0.0, 0.0)), a.try_normalize());
+        assert_eq!(None, Vec4(0.0, 0.0, 0.0, 0.0).try_normalize());
+        assert_eq!(None, Vec4(f32::INFINITY, 0.0, 0.0, 1.0).try_normalize());
+        assert_eq!(Some(Vec4(0.6, 0.8, 0.0, 0.0)), Vec4(3e30, 4e30, 0.0, 0.0).try_normalize());
+    }
+
+    #[test]
+    fn approx() {
+        let a = Vec4(0.5, 0.5, 0.5, 0.5);
+        let b = Vec4(0.5, 0.5, 0.505, 0.5);
+        assert_eq!(a, b);
+        assert!(!a.approx_eq_eps(b, 0.001));
+        assert!(!a.approx_eq_rel(b, 0.001));
+        assert!(a.approx_eq_rel(b, 0.01));
+        assert!(!a.approx_eq_ulps(b, 1));
+        assert!(!a.bitwise_eq(b));
+        assert!(a.bitwise_eq(Vec4(0.5, 0.5, 0.5, 0.5)));
+    }
 }
@@ -0,0 +1,368 @@
+pub mod query;
+pub mod schedule;
+pub mod storage;
+
+pub use query::Query;
+pub use schedule::{Schedule, System};
+pub use storage::SparseSet;
+
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+
+/// Entity is a handle to a set of components. The index of a despawned
+/// entity is reused by the next spawned one, with another generation, so
+/// that stale handles don't refer to it.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    #[inline]
+    pub const fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    #[inline]
+    pub fn index(self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+
+    /// Return the entity as a single number for the host, with the
+    /// generation in the high bits.
+    #[inline]
+    pub fn to_bits(self) -> u64 {
+        (self.generation as u64) << 32 | self.index as u64
+    }
+
+    #[inline]
+    pub fn from_bits(bits: u64) -> Self {
+        Self::new(bits as u32, (bits >> 32) as u32)
+    }
+}
+
+/// Storage is a sparse set of components of any type, which despawning an
+/// entity removes its component from without knowing the type. Components
+/// are `Send`, so that an engine can be kept in a static.
+trait Storage: Send {
+    fn remove(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Send + 'static> Storage for RefCell<SparseSet<T>> {
+    #[inline]
+    fn remove(&mut self, entity: Entity) {
+        self.get_mut().remove(entity);
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// World is the entities with a storage of components per type, and the
+/// resources, which are single values of a type shared by the systems.
+///
+/// Memory of the entities and components is reused, so that spawning and
+/// despawning as many of them as before, and running queries, doesn't
+/// allocate. Storages are found by a linear search, as there are only ever
+/// a handful of component types.
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    len: usize,
+    storages: Vec<(TypeId, Box<dyn Storage>)>,
+    resources: Vec<(TypeId, Box<dyn Any + Send>)>,
+}
+
+impl Default for World {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            storages: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Return the number of entities alive.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return a new entity without any components.
+    pub fn spawn(&mut self) -> Entity {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            self.alive[index as usize] = true;
+            return Entity::new(index, self.generations[index as usize]);
+        }
+        let index = self.generations.len() as u32;
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity::new(index, 0)
+    }
+
+    #[inline]
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let i = entity.index as usize;
+        self.alive.get(i) == Some(&true) && self.generations[i] == entity.generation
+    }
+
+    /// Remove an entity with all its components. Return false if it isn't
+    /// alive.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        for (_, storage) in &mut self.storages {
+            storage.remove(entity);
+        }
+        let i = entity.index as usize;
+        self.alive[i] = false;
+        self.generations[i] = self.generations[i].wrapping_add(1);
+        self.free.push(entity.index);
+        self.len -= 1;
+        true
+    }
+
+    /// Return the storage of a component type, if any has been inserted.
+    #[inline]
+    pub fn storage<T: 'static>(&self) -> Option<&RefCell<SparseSet<T>>> {
+        let id = TypeId::of::<T>();
+        let (_, storage) = self.storages.iter().find(|(t, _)| *t == id)?;
+        storage.as_any().downcast_ref()
+    }
+
+    /// Return the storage of a component type, adding it if needed.
+    fn storage_mut<T: Send + 'static>(&mut self) -> &mut SparseSet<T> {
+        let id = TypeId::of::<T>();
+        let i = match self.storages.iter().position(|(t, _)| *t == id) {
+            Some(i) => i,
+            None => {
+                let storage = RefCell::new(SparseSet::<T>::new());
+                self.storages.push((id, Box::new(storage)));
+                self.storages.len() - 1
+            }
+        };
+        let storage = self.storages[i].1.as_any_mut();
+        storage
+            .downcast_mut::<RefCell<SparseSet<T>>>()
+            .unwrap()
+            .get_mut()
+    }
+
+    /// Set a component of an entity, and return the previous one. The
+    /// component is given back if the entity isn't alive.
+    pub fn insert<T: Send + 'static>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<Option<T>, T> {
+        if !self.is_alive(entity) {
+            return Err(component);
+        }
+        Ok(self.storage_mut().insert(entity, component))
+    }
+
+    /// Remove a component of an entity and return it.
+    #[inline]
+    pub fn remove<T: Send + 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storage::<T>()?;
+        self.storage_mut().remove(entity)
+    }
+
+    /// Return a component of an entity. Panics if its storage is borrowed
+    /// mutably, such as by a running query.
+    #[inline]
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.storage::<T>()?.borrow(), |s| s.get(entity)).ok()
+    }
+
+    /// Return a component of an entity to be changed. Panics if its storage
+    /// is borrowed, such as by a running query.
+    #[inline]
+    pub fn get_mut<T: 'static>(&self, entity: Entity) -> Option<RefMut<'_, T>> {
+        RefMut::filter_map(self.storage::<T>()?.borrow_mut(), |s| s.get_mut(entity)).ok()
+    }
+
+    /// Run a closure over every entity with all the components of a query,
+    /// in the order of the smallest storage. Components can't be inserted
+    /// or removed meanwhile, which is left to the caller to do after.
+    pub fn query<Q: Query>(&self, mut f: impl FnMut(Entity, Q::Item<'_>)) {
+        let Some(mut storage) = Q::borrow(self) else {
+            return;
+        };
+        for i in 0..Q::len(&storage) {
+            let entity = Q::entity(&storage, i);
+            if let Some(item) = Q::fetch(&mut storage, entity) {
+                f(entity, item);
+            }
+        }
+    }
+
+    /// Set the resource of a type, and return the previous one.
+    pub fn insert_resource<T: Send + 'static>(&mut self, resource: T) -> Option<T> {
+        let id = TypeId::of::<T>();
+        match self.resources.iter_mut().find(|(t, _)| *t == id) {
+            Some((_, r)) => Some(std::mem::replace(r.downcast_mut().unwrap(), resource)),
+            None => {
+                self.resources.push((id, Box::new(resource)));
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn resource<T: 'static>(&self) -> Option<&T> {
+        let id = TypeId::of::<T>();
+        let (_, r) = self.resources.iter().find(|(t, _)| *t == id)?;
+        r.downcast_ref()
+    }
+
+    #[inline]
+    pub fn resource_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        let id = TypeId::of::<T>();
+        let (_, r) = self.resources.iter_mut().find(|(t, _)| *t == id)?;
+        r.downcast_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{Entity, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug, PartialEq)]
+    struct Velocity(f32);
+    #[derive(Debug, PartialEq)]
+    struct Frozen;
+
+    #[test]
+    fn entities() {
+        let mut world = World::new();
+        let a = world.spawn();
+        let b = world.spawn();
+        assert_eq!(2, world.len());
+        assert!(world.despawn(a));
+        assert!(!world.despawn(a));
+        assert!(!world.is_alive(a));
+
+        // The index is reused with another generation
+        let c = world.spawn();
+        assert_eq!((a.index(), 1), (c.index(), c.generation()));
+        assert!(world.is_alive(c) && world.is_alive(b));
+        assert_eq!(c, Entity::from_bits(c.to_bits()));
+        assert!(!world.is_alive(Entity::new(1, 1)));
+        assert!(!world.is_alive(Entity::new(9, 0)));
+    }
+
+    #[test]
+    fn components() {
+        let mut world = World::new();
+        let a = world.spawn();
+        assert_eq!(Ok(None), world.insert(a, Position(1.0)));
+        assert_eq!(Ok(Some(Position(1.0))), world.insert(a, Position(2.0)));
+        assert!(world.get::<Velocity>(a).is_none());
+        *world.get_mut::<Position>(a).unwrap() = Position(3.0);
+        assert_eq!(Position(3.0), *world.get::<Position>(a).unwrap());
+
+        // Despawning removes the components, and dead entities get none
+        world.despawn(a);
+        assert!(world.storage::<Position>().unwrap().borrow().is_empty());
+        assert_eq!(Err(Position(4.0)), world.insert(a, Position(4.0)));
+        let b = world.spawn();
+        assert!(world.get::<Position>(b).is_none());
+        world.insert(b, Velocity(1.0)).unwrap();
+        assert_eq!(Some(Velocity(1.0)), world.remove(b));
+        assert_eq!(None, world.remove::<Velocity>(b));
+        assert_eq!(None, world.remove::<Frozen>(b));
+    }
+
+    #[test]
+    fn query() {
+        let mut world = World::new();
+        let mut moving = Vec::new();
+        for i in 0..6 {
+            let e = world.spawn();
+            world.insert(e, Position(i as f32)).unwrap();
+            if i % 2 == 0 {
+                world.insert(e, Velocity(10.0)).unwrap();
+                moving.push(e);
+            }
+            if i == 4 {
+                world.insert(e, Frozen).unwrap();
+            }
+        }
+
+        let mut seen = Vec::new();
+        world.query::<(&mut Position, &Velocity)>(|e, (p, v)| {
+            p.0 += v.0;
+            seen.push(e);
+        });
+        assert_eq!(moving, seen);
+        let mut sum = 0.0;
+        world.query::<&Position>(|_, p| sum += p.0);
+        assert_eq!(15.0 + 30.0, sum);
+
+        let mut frozen = 0;
+        world.query::<(&Position, &Velocity, &Frozen)>(|_, (p, _, _)| {
+            assert_eq!(Position(14.0), *p);
+            frozen += 1;
+        });
+        assert_eq!(1, frozen);
+
+        // A component never inserted matches nothing
+        world.query::<(&Position, &String)>(|_, _| panic!());
+    }
+
+    #[test]
+    #[should_panic]
+    fn query_aliasing() {
+        let mut world = World::new();
+        let e = world.spawn();
+        world.insert(e, Position(0.0)).unwrap();
+        world.query::<(&mut Position, &Position)>(|_, _| {});
+    }
+
+    #[test]
+    fn resources() {
+        let mut world = World::new();
+        assert_eq!(None, world.resource::<u32>());
+        assert_eq!(None, world.insert_resource(1_u32));
+        assert_eq!(Some(1), world.insert_resource(2_u32));
+        *world.resource_mut::<u32>().unwrap() += 1;
+        assert_eq!(Some(&3), world.resource::<u32>());
+    }
+}
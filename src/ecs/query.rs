@@ -0,0 +1,119 @@
+use crate::ecs::{Entity, SparseSet, World};
+use std::cell::{Ref, RefMut};
+
+/// Query is a component reference, or a tuple of them, to run a closure
+/// over for every entity that has all of them. Shared references borrow
+/// their storage for reading and mutable ones for writing, so a query of
+/// the same component type twice, with one of them mutable, panics.
+pub trait Query {
+    /// Borrowed storages of the components.
+    type Storage<'w>;
+    /// Components of a single entity.
+    type Item<'a>;
+
+    /// Borrow the storages, or return `None` if any of the components has
+    /// never been added to the world.
+    fn borrow(world: &World) -> Option<Self::Storage<'_>>;
+
+    /// Return the number of entities to go through, which is the number of
+    /// components in the smallest storage.
+    fn len(storage: &Self::Storage<'_>) -> usize;
+
+    /// Return an entity of the smallest storage.
+    fn entity(storage: &Self::Storage<'_>, i: usize) -> Entity;
+
+    /// Return the components of an entity if it has all of them.
+    fn fetch<'a>(storage: &'a mut Self::Storage<'_>, entity: Entity) -> Option<Self::Item<'a>>;
+}
+
+impl<T: 'static> Query for &T {
+    type Storage<'w> = Ref<'w, SparseSet<T>>;
+    type Item<'a> = &'a T;
+
+    #[inline]
+    fn borrow(world: &World) -> Option<Self::Storage<'_>> {
+        Some(world.storage::<T>()?.borrow())
+    }
+
+    #[inline]
+    fn len(storage: &Self::Storage<'_>) -> usize {
+        storage.len()
+    }
+
+    #[inline]
+    fn entity(storage: &Self::Storage<'_>, i: usize) -> Entity {
+        storage.entities()[i]
+    }
+
+    #[inline]
+    fn fetch<'a>(storage: &'a mut Self::Storage<'_>, entity: Entity) -> Option<&'a T> {
+        storage.get(entity)
+    }
+}
+
+impl<T: 'static> Query for &mut T {
+    type Storage<'w> = RefMut<'w, SparseSet<T>>;
+    type Item<'a> = &'a mut T;
+
+    #[inline]
+    fn borrow(world: &World) -> Option<Self::Storage<'_>> {
+        Some(world.storage::<T>()?.borrow_mut())
+    }
+
+    #[inline]
+    fn len(storage: &Self::Storage<'_>) -> usize {
+        storage.len()
+    }
+
+    #[inline]
+    fn entity(storage: &Self::Storage<'_>, i: usize) -> Entity {
+        storage.entities()[i]
+    }
+
+    #[inline]
+    fn fetch<'a>(storage: &'a mut Self::Storage<'_>, entity: Entity) -> Option<&'a mut T> {
+        storage.get_mut(entity)
+    }
+}
+
+macro_rules! tuple_query {
+    ($($q:ident $i:tt),+) => {
+        impl<$($q: Query),+> Query for ($($q,)+) {
+            type Storage<'w> = ($($q::Storage<'w>,)+);
+            type Item<'a> = ($($q::Item<'a>,)+);
+
+            #[inline]
+            fn borrow(world: &World) -> Option<Self::Storage<'_>> {
+                Some(($($q::borrow(world)?,)+))
+            }
+
+            #[inline]
+            fn len(storage: &Self::Storage<'_>) -> usize {
+                [$($q::len(&storage.$i)),+].into_iter().min().unwrap_or(0)
+            }
+
+            #[inline]
+            fn entity(storage: &Self::Storage<'_>, i: usize) -> Entity {
+                let len = Self::len(storage);
+                $(
+                    if $q::len(&storage.$i) == len {
+                        return $q::entity(&storage.$i, i);
+                    }
+                )+
+                unreachable!()
+            }
+
+            #[inline]
+            fn fetch<'a>(storage: &'a mut Self::Storage<'_>, entity: Entity) -> Option<Self::Item<'a>> {
+                #[allow(non_snake_case)]
+                let ($($q,)+) = storage;
+                Some(($($q::fetch($q, entity)?,)+))
+            }
+        }
+    };
+}
+
+tuple_query!(A 0);
+tuple_query!(A 0, B 1);
+tuple_query!(A 0, B 1, C 2);
+tuple_query!(A 0, B 1, C 2, D 3);
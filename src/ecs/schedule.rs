@@ -0,0 +1,92 @@
+use crate::ecs::World;
+
+/// System is a function run over the world every simulation step, with the
+/// duration of the step in seconds.
+pub type System = fn(&mut World, f32);
+
+/// Schedule is the systems to run every step, in the order they were added.
+/// Systems are plain functions, so that running them doesn't allocate.
+pub struct Schedule {
+    systems: Vec<(System, bool)>,
+}
+
+impl Default for Schedule {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Schedule {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            systems: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Add a system after the others and return its index.
+    #[inline]
+    pub fn add(&mut self, system: System) -> usize {
+        self.systems.push((system, true));
+        self.systems.len() - 1
+    }
+
+    /// Pause or resume a system. Return false if it is out of bounds.
+    #[inline]
+    pub fn set_enabled(&mut self, system: usize, enabled: bool) -> bool {
+        let Some(s) = self.systems.get_mut(system) else {
+            return false;
+        };
+        s.1 = enabled;
+        true
+    }
+
+    /// Run the enabled systems over the world.
+    #[inline]
+    pub fn run(&self, world: &mut World, dt: f32) {
+        for &(system, enabled) in &self.systems {
+            if enabled {
+                system(world, dt);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{Schedule, World};
+
+    #[test]
+    fn order() {
+        fn double(world: &mut World, _: f32) {
+            *world.resource_mut::<f32>().unwrap() *= 2.0;
+        }
+        fn add(world: &mut World, dt: f32) {
+            *world.resource_mut::<f32>().unwrap() += dt;
+        }
+
+        let mut world = World::new();
+        world.insert_resource(1.0_f32);
+        let mut schedule = Schedule::new();
+        schedule.add(double);
+        let a = schedule.add(add);
+        schedule.run(&mut world, 0.5);
+        assert_eq!(Some(&2.5), world.resource::<f32>());
+
+        assert!(schedule.set_enabled(a, false));
+        assert!(!schedule.set_enabled(2, false));
+        schedule.run(&mut world, 0.5);
+        assert_eq!(Some(&5.0), world.resource::<f32>());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::ecs::Entity;
+
+/// Index of the sparse array that has no component.
+const EMPTY: u32 = u32::MAX;
+
+/// SparseSet stores components of a type densely, so that iterating over
+/// them is linear, with a sparse array from the entity indices into them,
+/// so that looking them up is constant. Removing a component moves the last
+/// one into its place.
+pub struct SparseSet<T> {
+    sparse: Vec<u32>,
+    entities: Vec<Entity>,
+    values: Vec<T>,
+}
+
+impl<T> Default for SparseSet<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseSet<T> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return the entities with a component, in the order of the components.
+    #[inline]
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Return the position of the component of an entity in the dense
+    /// arrays.
+    #[inline]
+    fn dense(&self, entity: Entity) -> Option<usize> {
+        let dense = *self.sparse.get(entity.index() as usize)? as usize;
+        (self.entities.get(dense) == Some(&entity)).then_some(dense)
+    }
+
+    #[inline]
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.dense(entity).is_some()
+    }
+
+    #[inline]
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.dense(entity).map(|i| &self.values[i])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.dense(entity).map(|i| &mut self.values[i])
+    }
+
+    /// Set the component of an entity, and return the previous one.
+    pub fn insert(&mut self, entity: Entity, value: T) -> Option<T> {
+        if let Some(i) = self.dense(entity) {
+            return Some(std::mem::replace(&mut self.values[i], value));
+        }
+        let index = entity.index() as usize;
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, EMPTY);
+        }
+        self.sparse[index] = self.values.len() as u32;
+        self.entities.push(entity);
+        self.values.push(value);
+        None
+    }
+
+    /// Remove the component of an entity and return it.
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let i = self.dense(entity)?;
+        self.sparse[entity.index() as usize] = EMPTY;
+        self.entities.swap_remove(i);
+        if let Some(moved) = self.entities.get(i) {
+            self.sparse[moved.index() as usize] = i as u32;
+        }
+        Some(self.values.swap_remove(i))
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(&self.values)
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities.iter().copied().zip(&mut self.values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{Entity, SparseSet};
+
+    #[test]
+    fn sparse_set() {
+        let (a, b, c) = (Entity::new(0, 0), Entity::new(5, 0), Entity::new(2, 0));
+        let mut set = SparseSet::new();
+        assert_eq!(None, set.insert(a, 'a'));
+        assert_eq!(None, set.insert(b, 'b'));
+        assert_eq!(None, set.insert(c, 'c'));
+        assert_eq!(Some('b'), set.insert(b, 'B'));
+        assert_eq!(3, set.len());
+        assert_eq!(Some(&'B'), set.get(b));
+
+        // Another generation of the same index is another entity
+        assert!(!set.contains(Entity::new(5, 1)));
+        assert_eq!(None, set.remove(Entity::new(5, 1)));
+
+        // The last component moves into the place of the removed one
+        assert_eq!(Some('a'), set.remove(a));
+        assert_eq!([c, b], set.entities());
+        assert_eq!(Some(&'c'), set.get(c));
+        *set.get_mut(c).unwrap() = 'C';
+        assert_eq!(vec![(c, &'C'), (b, &'B')], set.iter().collect::<Vec<_>>());
+        assert_eq!(None, set.remove(a));
+        assert_eq!(Some('B'), set.remove(b));
+        assert_eq!(Some('C'), set.remove(c));
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,770 @@
+use crate::anim::{Animator, BlendMode, Layer, Mask};
+use crate::atlas::Atlas;
+use crate::camera::{Camera, Controller, Fly, Follow, Orbit};
+use crate::core::{Color, Frustum, Intersection, Mat4, Ray, Vec2, Vec3, Vec4};
+use crate::ecs::{Schedule, World};
+use crate::input::{key, Input};
+use crate::instance::{Batch, Instance};
+use crate::loader::gltf::{self, Gltf, Primitive};
+use crate::loader::{Error, Result};
+use crate::mesh::Mesh;
+use crate::region::{self, Layout, View};
+use crate::scene::Scene;
+use crate::sprite::Batcher;
+use crate::state::{Screen, State, Vertex};
+use crate::timestep::{Timestep, MAX_STEPS, TICK_RATE};
+use crate::vertex::{Packing, VertexBuffer};
+use std::f32::consts::FRAC_PI_4;
+use std::{mem, slice};
+
+/// Number of vertices the engine has memory for up front.
+pub const VERTEX_CAPACITY: usize = 65536;
+
+/// Number of indices the engine has memory for up front.
+pub const INDEX_CAPACITY: usize = 3 * VERTEX_CAPACITY;
+
+/// Number of instances a batch has memory for up front.
+pub const INSTANCE_CAPACITY: usize = 1024;
+
+/// Number of sprites the engine has memory for up front.
+pub const SPRITE_CAPACITY: usize = 4096;
+
+/// Engine is an instance of the world rendered to a single canvas. The host
+/// owns engines by handles, so several of them can run side by side, such
+/// as an editor preview next to the game, and be reset by recreating.
+#[repr(C)]
+pub struct Engine {
+    pub state: State,
+}
+
+impl Default for Engine {
+    /// Return an engine with the mesh and sprite memory allocated up front.
+    #[inline]
+    fn default() -> Self {
+        let mut engine = Self::new();
+        engine.state.mesh = Mesh::with_capacity(VERTEX_CAPACITY, INDEX_CAPACITY);
+        engine.state.sprites = Batcher::with_capacity(SPRITE_CAPACITY);
+        engine.place_camera();
+        engine
+    }
+}
+
+impl Engine {
+    /// Return an engine with an empty world, orbiting the origin. No memory is
+    /// allocated, so that it can be used in statics, and the camera is only
+    /// placed by the first simulation step.
+    pub const fn new() -> Self {
+        Self {
+            state: State {
+                screen: Screen(1980.0, 1024.0),
+                camera: Camera::new(),
+                input: Input::new(),
+                controller: Controller::Orbit(Orbit::new(
+                    Vec3(0.0, 0.0, 0.5),
+                    -FRAC_PI_4,
+                    FRAC_PI_4,
+                    12.0,
+                )),
+                view_matrix: Mat4(
+                    Vec4(1.0, 0.0, 0.0, 0.0),
+                    Vec4(0.0, 1.0, 0.0, 0.0),
+                    Vec4(0.0, 0.0, 1.0, 0.0),
+                    Vec4(0.0, 0.0, 0.0, 1.0),
+                ),
+                ray: Ray(Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, -1.0)),
+                scene: Scene::new(),
+                world: World::new(),
+                schedule: Schedule::new(),
+                mesh: Mesh::new(),
+                vertex_buffer: VertexBuffer::empty(),
+                batches: Vec::new(),
+                sprites: Batcher::new(),
+                atlases: Vec::new(),
+                packing: Packing::Interleaved,
+                uploads: Vec::new(),
+                animator: None,
+                layout: Layout::new(),
+                timestep: Timestep::new(TICK_RATE, MAX_STEPS),
+                previous_camera: Camera::new(),
+                count: 0,
+            },
+        }
+    }
+
+    /// Move the camera to where its controller puts it right away, without
+    /// waiting for the next simulation step or interpolating to it.
+    pub fn place_camera(&mut self) {
+        let state = &mut self.state;
+        if let Controller::Follow(follow) = &mut state.controller {
+            follow.snap(&mut state.camera);
+        } else {
+            state.controller.update(&mut state.camera, 0.0);
+        }
+        state.previous_camera = state.camera;
+    }
+
+    /// Switch to orbiting a target from where the camera is.
+    pub fn orbit_camera(&mut self, target: Vec3) {
+        self.state.controller = Controller::Orbit(Orbit::around(&self.state.camera, target));
+    }
+
+    /// Switch to flying the camera from where it is.
+    pub fn fly_camera(&mut self) {
+        self.state.controller = Controller::Fly(Fly::from_camera(&self.state.camera));
+    }
+
+    /// Switch to following a target, with the camera catching up with it
+    /// from where it is.
+    pub fn follow_camera(&mut self, target: Vec3, heading: f32) {
+        self.state.controller = Controller::Follow(Follow::new(target, heading));
+    }
+
+    /// Turn the camera by the pointer movement in pixels.
+    pub fn look_camera(&mut self, dx: f32, dy: f32) {
+        self.state.controller.look(dx, dy);
+    }
+
+    /// Zoom an orbiting camera by the wheel movement.
+    pub fn zoom_camera(&mut self, delta: f32) {
+        if let Controller::Orbit(orbit) = &mut self.state.controller {
+            orbit.zoom(delta);
+        }
+    }
+
+    /// Feed the input since the last step to the camera controller: dragging
+    /// with the primary button turns it, the wheel zooms it, and WASD with
+    /// space and shift fly it.
+    fn drive_camera(&mut self) {
+        let input = &self.state.input;
+        let delta = input.pointer.delta;
+        let wheel = input.pointer.wheel;
+        if input.pointer.buttons.held(0) {
+            self.look_camera(delta.0, delta.1);
+        }
+        if wheel != 0.0 {
+            self.zoom_camera(wheel);
+        }
+
+        let input = &self.state.input;
+        if let Controller::Fly(fly) = &mut self.state.controller {
+            fly.movement = Vec3(
+                input.axis(key::A, key::D),
+                input.axis(key::SHIFT, key::SPACE),
+                input.axis(key::S, key::W),
+            );
+        }
+    }
+
+    /// Move the target of a following or orbiting camera.
+    pub fn set_camera_target(&mut self, target: Vec3, heading: f32) {
+        match &mut self.state.controller {
+            Controller::Orbit(orbit) => orbit.target = target,
+            Controller::Follow(follow) => follow.set_target(target, heading),
+            Controller::Fly(_) => {}
+        }
+    }
+
+    /// Cast a ray from the camera through a point of the canvas, given in
+    /// pixels from its top left corner. The previous ray is kept if the view
+    /// can not be unprojected.
+    pub fn ray_from_screen(&mut self, x: f32, y: f32) -> &Ray {
+        let state = &mut self.state;
+        let size = Vec2(state.screen.0, state.screen.1);
+        if let Some(ray) = Ray::from_screen(state.view_matrix, Vec2(x, y), size) {
+            state.ray = ray;
+        }
+        &state.ray
+    }
+
+    pub fn gen(&mut self) {
+        let white = Color(255, 255, 255, 255);
+        let vertices = [
+            Vertex(Vec3(0.5, -0.5, 1.0), white),
+            Vertex(Vec3(0.5, -0.5, 0.0), white),
+            Vertex(Vec3(0.5, -0.5, 0.0), white),
+            Vertex(Vec3(0.5, -0.5, 0.0), white),
+            Vertex(Vec3(-0.5, 0.5, 1.0), white),
+            Vertex(Vec3(-0.5, 0.5, 1.0), white),
+            Vertex(Vec3(-0.5, 0.5, 1.0), white),
+            Vertex(Vec3(-0.5, 0.5, 0.0), white),
+        ];
+
+        let state = &mut self.state;
+        state.mesh.append(vertices, 0..vertices.len() as u32);
+        state.mesh.weld();
+        state.vertex_buffer = VertexBuffer::from_vertices(&state.mesh.vertices, state.packing);
+    }
+
+    /// Allocate a buffer of the given size for the next asset to be loaded,
+    /// so that the asset bytes can be copied straight into it. Return None
+    /// and release the buffers uploaded so far if there is no memory for it.
+    pub fn upload_buffer(&mut self, len: usize) -> Option<&mut [u8]> {
+        let uploads = &mut self.state.uploads;
+        let mut buffer = Vec::new();
+        if buffer.try_reserve_exact(len).is_err() {
+            uploads.clear();
+            return None;
+        }
+        buffer.resize(len, 0);
+        uploads.push(buffer);
+        uploads.last_mut().map(Vec::as_mut_slice)
+    }
+
+    /// Load a glTF asset from the uploaded buffers, the first of which is the
+    /// JSON and the rest are the binary buffers in order, and replace the
+    /// scene with its default one, and the mesh and the vertex buffer with
+    /// all of its meshes. The animator is replaced with the
+    /// first skin of the asset, if there is one. The uploaded buffers are
+    /// released either way and the state is kept intact if the asset is
+    /// invalid.
+    pub fn load_gltf(&mut self) -> Result<()> {
+        let state = &mut self.state;
+        let uploads = mem::take(&mut state.uploads);
+        let Some((json, buffers)) = uploads.split_first() else {
+            return Err(Error::Missing("uploaded JSON"));
+        };
+        let buffers: Vec<&[u8]> = buffers.iter().map(Vec::as_slice).collect();
+        let gltf = Gltf::parse(json, &buffers)?;
+        let meshes = gltf.meshes()?;
+        let scene = gltf.scene(None)?;
+        let animator = if gltf.json().get("skins").elements().is_empty() {
+            None
+        } else {
+            Some(Animator::new(gltf.skeleton(0)?, gltf.clips(0)?))
+        };
+
+        let primitives: Vec<&Primitive> = meshes.iter().flat_map(|m| &m.primitives).collect();
+        let primitive_meshes = primitives
+            .iter()
+            .map(|p| p.mesh())
+            .collect::<Result<Vec<_>>>()?;
+        let vertices: usize = primitives.iter().map(|p| p.positions.len()).sum();
+        if u32::try_from(vertices).is_err() {
+            return Err(Error::OutOfBounds("index"));
+        }
+
+        state.mesh.clear();
+        state.batches.clear();
+        let mut primitive_meshes = primitive_meshes.iter();
+        for mesh in &meshes {
+            let first = state.mesh.indices.len();
+            for m in primitive_meshes.by_ref().take(mesh.primitives.len()) {
+                // The indices fit as the vertices of all primitives do
+                let appended = state
+                    .mesh
+                    .append(m.vertices.iter().copied(), m.indices.iter());
+                debug_assert!(appended);
+            }
+            let count = state.mesh.indices.len() - first;
+            let bounds = state.mesh.bounds(first, count);
+            state
+                .batches
+                .push(Batch::new(first, count, bounds, INSTANCE_CAPACITY));
+        }
+        state.vertex_buffer = gltf::vertex_buffer(&primitives, state.packing);
+        state.scene = scene;
+        state.animator = animator;
+        Ok(())
+    }
+
+    /// Replace the images of an atlas with the uploaded sizes, which are
+    /// pairs of 32 bit widths and heights, packed anew.
+    pub fn pack_atlas(&mut self, atlas: usize) -> Result<()> {
+        let state = &mut self.state;
+        let uploads = mem::take(&mut state.uploads);
+        let Some(data) = uploads.first() else {
+            return Err(Error::Missing("uploaded sizes"));
+        };
+        let Some(options) = state.atlases.get(atlas).map(|a| *a.options()) else {
+            return Err(Error::OutOfBounds("atlas"));
+        };
+        if data.len() % 8 != 0 {
+            return Err(Error::Invalid("atlas sizes"));
+        }
+        let sizes: Vec<(u32, u32)> = data
+            .chunks_exact(8)
+            .map(|c| {
+                let w = u32::from_le_bytes(c[..4].try_into().unwrap());
+                let h = u32::from_le_bytes(c[4..].try_into().unwrap());
+                (w, h)
+            })
+            .collect();
+        let packed = Atlas::pack(&sizes, options).ok_or(Error::OutOfBounds("atlas sizes"))?;
+        state.atlases[atlas] = packed;
+        Ok(())
+    }
+
+    /// Show an image of an atlas on a sprite, drawing it with the texture
+    /// of the same number as the atlas. Return false if there is no such
+    /// sprite or image.
+    pub fn set_sprite_slot(&mut self, sprite: usize, atlas: usize, slot: usize) -> bool {
+        let state = &mut self.state;
+        let uv = state.atlases.get(atlas).and_then(|a| a.uv(slot));
+        match (state.sprites.sprites.get_mut(sprite), uv) {
+            (Some(s), Some(uv)) => {
+                s.texture = atlas as u32;
+                s.uv = uv;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Add an animation layer with a state per clip on top of the others,
+    /// limited to a joint and its descendants if there is one, and return
+    /// its index. Return `None` if no animated asset is loaded.
+    pub fn add_animation_layer(&mut self, mode: BlendMode, mask: Option<usize>) -> Option<usize> {
+        let animator = self.state.animator.as_mut()?;
+        let mut layer = Layer::from_clips(&animator.clips, mode);
+        layer.mask = mask.map(|joint| Mask::new(&animator.skeleton, joint));
+        animator.graph.layers.push(layer);
+        Some(animator.graph.layers.len() - 1)
+    }
+
+    /// Return the animation layer, if an animated asset is loaded.
+    pub fn animation_layer(&mut self, layer: usize) -> Option<&mut Layer> {
+        self.state.animator.as_mut()?.graph.layers.get_mut(layer)
+    }
+
+    /// Return the skinning matrices of the current animation pose.
+    pub fn palette(&self) -> &[Mat4] {
+        self.state.animator.as_ref().map_or(&[], |a| a.palette())
+    }
+
+    /// Add a batch of instances of an index range of the mesh, and return its
+    /// index. Return `None` if the range is past the indices.
+    pub fn add_batch(&mut self, first: usize, count: usize) -> Option<usize> {
+        let state = &mut self.state;
+        let len = state.mesh.indices.len();
+        if first.checked_add(count).is_none_or(|end| end > len) {
+            return None;
+        }
+        let bounds = state.mesh.bounds(first, count);
+        state
+            .batches
+            .push(Batch::new(first, count, bounds, INSTANCE_CAPACITY));
+        Some(state.batches.len() - 1)
+    }
+
+    /// Replace the instances of the batches with the nodes of the scene that
+    /// draw their meshes, at their world transformations as of the last
+    /// update and with their colors. Nodes whose bounds are out of the view
+    /// as of the last interpolation are culled, so the instances of a node
+    /// move between the slots, and are rewritten from the node every time.
+    pub fn instance_scene(&mut self) {
+        let state = &mut self.state;
+        let frustum = Frustum::from(state.view_matrix);
+        let mut lens = vec![0; state.batches.len()];
+        for node in state.scene.nodes() {
+            let Some(m) = node.mesh.filter(|&m| m < lens.len()) else {
+                continue;
+            };
+            let batch = &mut state.batches[m];
+            let bounds = batch.bounds.transform(*node.world());
+            if frustum.intersects_aabb(bounds) == Intersection::Outside {
+                continue;
+            }
+            match batch.instances_mut().get_mut(lens[m]) {
+                Some(instance) => *instance = Instance::new(*node.world(), node.color),
+                None => {
+                    batch.push(*node.world(), node.color);
+                }
+            }
+            lens[m] += 1;
+        }
+        for (batch, len) in state.batches.iter_mut().zip(lens) {
+            batch.truncate(len);
+        }
+    }
+
+    /// Return the layout of the memory shared with the host, updated to the
+    /// current state. The views are of the vertices, the joint palette, the
+    /// view matrix, the indices, the vertex buffer, the sprite vertices and
+    /// the sprite indices, in this order, followed by the instances of every
+    /// batch.
+    pub fn layout(&mut self) -> &Layout {
+        let state = &mut self.state;
+        let palette = state.animator.as_ref().map_or(&[][..], |a| a.palette());
+        let views = [
+            state.mesh.vertices.view(),
+            View::of(palette, palette.len()),
+            View::of(slice::from_ref(&state.view_matrix), 1),
+            state.mesh.indices.view(),
+            state.vertex_buffer.view(),
+            state.sprites.vertices_view(),
+            state.sprites.indices_view(),
+        ];
+        let batches = state.batches.iter().map(Batch::view);
+        state
+            .layout
+            .update(region::memory_size(), views.into_iter().chain(batches));
+        &state.layout
+    }
+
+    /// Advance the world by the time of a frame in milliseconds: simulate
+    /// as many fixed steps as fit into it, then interpolate between the last
+    /// two of them for rendering.
+    pub fn update(&mut self, dt: f64) {
+        for _ in 0..self.state.timestep.advance(dt) {
+            self.simulate();
+        }
+        self.interpolate(self.state.timestep.alpha());
+    }
+
+    /// Run a single simulation step of the fixed duration.
+    pub fn simulate(&mut self) {
+        let dt = (self.state.timestep.step() / 1000.0) as f32;
+        self.drive_camera();
+        let state = &mut self.state;
+        state.previous_camera = state.camera;
+        state.controller.update(&mut state.camera, dt);
+        if let Some(animator) = &mut state.animator {
+            animator.advance(dt);
+        }
+        state.schedule.run(&mut state.world, dt);
+
+        state.input.flush();
+        state.count += 1;
+    }
+
+    /// Compute the rendered state at a fraction of the way between the last
+    /// two simulation steps, and bring the scene and the sprites up to date.
+    pub fn interpolate(&mut self, alpha: f32) {
+        let state = &mut self.state;
+        state.scene.update();
+        state.sprites.build();
+        let camera = state.previous_camera.lerp(&state.camera, alpha);
+
+        // Keep the last view while the canvas is collapsed, as an infinite
+        // aspect ratio would fill the view with NaNs.
+        if state.screen.0 > 0.0 && state.screen.1 > 0.0 {
+            state.view_matrix = camera.view_projection(state.screen.0 / state.screen.1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anim::BlendMode;
+    use crate::atlas::{Atlas, Options};
+    use crate::core::{Color, Mat4, Transform, Vec2, Vec3};
+    use crate::ecs::World;
+    use crate::engine::{Engine, VERTEX_CAPACITY};
+    use crate::input::key;
+    use crate::instance::Instance;
+    use crate::loader::Error;
+    use crate::scene::Scene;
+    use crate::sprite::Sprite;
+    use crate::timestep::Timestep;
+    use crate::vertex::Semantic;
+
+    const CRATE: (&[u8], &[u8]) = (
+        include_bytes!("../public/assets/models/crate.gltf"),
+        include_bytes!("../public/assets/models/crate.bin"),
+    );
+    const WIZARD: (&[u8], &[u8]) = (
+        include_bytes!("../public/assets/models/wizard.gltf"),
+        include_bytes!("../public/assets/models/wizard.bin"),
+    );
+
+    fn upload(engine: &mut Engine, data: &[u8]) {
+        engine
+            .upload_buffer(data.len())
+            .unwrap()
+            .copy_from_slice(data);
+    }
+
+    #[test]
+    fn instances() {
+        let mut a = Engine::new();
+        let mut b = Engine::new();
+        a.gen();
+        a.look_camera(100.0, 0.0);
+        a.update(20.0);
+        assert_eq!(4, a.state.mesh.vertices.len());
+        assert_eq!(8, a.state.mesh.indices.len());
+        assert!(b.state.mesh.vertices.is_empty());
+        assert_eq!(1, a.state.count);
+        assert_eq!(0, b.state.count);
+
+        b.update(20.0);
+        assert!(a.state.view_matrix != b.state.view_matrix);
+    }
+
+    #[test]
+    fn update() {
+        let mut engine = Engine::new();
+        engine.state.timestep = Timestep::new(100.0, 4);
+        engine.update(25.0);
+        assert_eq!(2, engine.state.count);
+        assert_eq!(0.5, engine.state.timestep.alpha());
+
+        // The view is rendered halfway between the last two camera positions
+        engine.fly_camera();
+        engine.state.input.key_down(key::W);
+        engine.simulate();
+        let (previous, current) = (engine.state.previous_camera, engine.state.camera);
+        assert!(previous.position != current.position);
+        engine.interpolate(0.5);
+        let view_matrix = engine.state.view_matrix;
+        engine.state.camera = previous.lerp(&current, 0.5);
+        engine.interpolate(1.0);
+        assert_eq!(engine.state.view_matrix, view_matrix);
+    }
+
+    #[test]
+    fn systems() {
+        fn count(world: &mut World, _: f32) {
+            world.query::<&mut u32>(|_, n| *n += 1);
+        }
+
+        let mut engine = Engine::new();
+        engine.state.timestep = Timestep::new(100.0, 4);
+        let world = &mut engine.state.world;
+        let e = world.spawn();
+        world.insert(e, 0_u32).unwrap();
+        engine.state.schedule.add(count);
+        engine.update(25.0);
+        assert_eq!(Some(2), engine.state.world.get::<u32>(e).map(|n| *n));
+    }
+
+    #[test]
+    fn camera() {
+        let mut engine = Engine::default();
+        engine.update(0.0);
+        let centered = |e: &Engine| {
+            let p = e.state.view_matrix.transform_point(Vec3(0.0, 0.0, 0.5));
+            p == Vec3(0.0, 0.0, p.2)
+        };
+        assert!(centered(&engine));
+
+        // Zooming only applies to the orbit, and keeps the target centered
+        let distance = engine.state.camera.position.len();
+        engine.zoom_camera(500.0);
+        engine.update(20.0);
+        assert!(engine.state.camera.position.len() > distance);
+        assert!(centered(&engine));
+
+        // Switching the controller keeps the camera in place
+        let position = engine.state.camera.position;
+        engine.fly_camera();
+        engine.zoom_camera(500.0);
+        engine.update(20.0);
+        assert_eq!(position, engine.state.camera.position);
+        assert!(centered(&engine));
+
+        engine.follow_camera(Vec3(0.0, 0.0, 0.0), 0.0);
+        engine.set_camera_target(Vec3(1.0, 0.0, 0.0), 0.0);
+        engine.place_camera();
+        assert_eq!(Vec3(-3.0, 0.0, 2.0), engine.state.camera.position);
+    }
+
+    #[test]
+    fn sprites() {
+        let mut engine = Engine::default();
+        let generation = engine.layout().generation();
+        let ptr = engine.layout().views()[5].ptr;
+        let sprites = &mut engine.state.sprites;
+        sprites.push(Sprite::new(1, Vec2(0.0, 0.0), Vec2(1.0, 1.0)));
+        sprites.push(Sprite::new(0, Vec2(2.0, 0.0), Vec2(1.0, 1.0)));
+
+        // Sprites are written into the shared memory on rendering
+        engine.update(0.0);
+        assert_eq!(2, engine.state.sprites.batches().len());
+        let layout = engine.layout();
+        assert_eq!(generation + 1, layout.generation());
+        assert_eq!(
+            (ptr, 2 * 80),
+            (layout.views()[5].ptr, layout.views()[5].len)
+        );
+        assert_eq!(12 * 2, layout.views()[6].len);
+    }
+
+    #[test]
+    fn atlas() {
+        let mut engine = Engine::default();
+        engine.state.atlases.push(Atlas::new(Options::default()));
+        assert_eq!(Err(Error::Missing("uploaded sizes")), engine.pack_atlas(0));
+        upload(&mut engine, &[0; 12]);
+        assert_eq!(Err(Error::Invalid("atlas sizes")), engine.pack_atlas(0));
+
+        let sizes: Vec<u8> = [32_u32, 16, 8, 8]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        upload(&mut engine, &sizes);
+        assert_eq!(Err(Error::OutOfBounds("atlas")), engine.pack_atlas(1));
+        upload(&mut engine, &sizes);
+        assert_eq!(Ok(()), engine.pack_atlas(0));
+        let atlas = &engine.state.atlases[0];
+        assert_eq!(2, atlas.len());
+        let uv = atlas.uv(1).unwrap();
+
+        let sprite = engine
+            .state
+            .sprites
+            .push(Sprite::new(3, Vec2(0.0, 0.0), Vec2(8.0, 8.0)));
+        assert!(!engine.set_sprite_slot(sprite, 0, 2));
+        assert!(engine.set_sprite_slot(sprite, 0, 1));
+        let sprite = &engine.state.sprites.sprites[sprite];
+        assert_eq!(0, sprite.texture);
+        assert!(uv == sprite.uv);
+    }
+
+    #[test]
+    fn layout() {
+        let mut engine = Engine::default();
+        assert_eq!(VERTEX_CAPACITY, engine.state.mesh.vertices.capacity());
+        let generation = engine.layout().generation();
+        let views = engine.layout().views().to_vec();
+        assert_eq!(7, views.len());
+        assert_eq!(generation, engine.layout().generation());
+
+        // The mesh stays in place, but its length changes
+        engine.gen();
+        let layout = engine.layout();
+        assert_eq!(generation + 1, layout.generation());
+        assert_eq!(views[0].ptr, layout.views()[0].ptr);
+        assert_eq!(4 * 16, layout.views()[0].len);
+        assert_eq!(views[3].ptr, layout.views()[3].ptr);
+        assert_eq!(8 * 2, layout.views()[3].len);
+
+        // Loading an animated asset adds the palette
+        upload(&mut engine, WIZARD.0);
+        upload(&mut engine, WIZARD.1);
+        engine.load_gltf().unwrap();
+        let layout = engine.layout();
+        assert_eq!(generation + 2, layout.generation());
+        assert_eq!(views[0].ptr, layout.views()[0].ptr);
+        assert_eq!(30 * 64, layout.views()[1].len);
+        assert_eq!(views[2], layout.views()[2]);
+        assert_eq!(views[3].ptr, layout.views()[3].ptr);
+    }
+
+    #[test]
+    fn load_gltf() {
+        let mut engine = Engine::new();
+        assert_eq!(Err(Error::Missing("uploaded JSON")), engine.load_gltf());
+        assert!(engine
+            .add_animation_layer(BlendMode::Override, None)
+            .is_none());
+        assert!(engine.palette().is_empty());
+
+        upload(&mut engine, WIZARD.0);
+        upload(&mut engine, WIZARD.1);
+        assert_eq!(Ok(()), engine.load_gltf());
+        assert!(engine.state.uploads.is_empty());
+        assert!(!engine.state.mesh.vertices.is_empty());
+
+        // A buffer there is no memory for releases the ones before it
+        upload(&mut engine, WIZARD.0);
+        assert!(engine.upload_buffer(usize::MAX).is_none());
+        assert!(engine.state.uploads.is_empty());
+        let buffer = &engine.state.vertex_buffer;
+        assert_eq!(engine.state.mesh.vertices.len(), buffer.len());
+        assert!(buffer.format().attribute(Semantic::Weights).is_some());
+        assert_eq!(30, engine.palette().len());
+        assert_eq!(32, engine.state.scene.len());
+        assert_eq!(
+            Some(0),
+            engine.add_animation_layer(BlendMode::Override, Some(1))
+        );
+        assert!(engine.animation_layer(0).unwrap().crossfade(1, 0.5));
+        assert!(engine.animation_layer(1).is_none());
+
+        // An invalid asset keeps the loaded one
+        upload(&mut engine, b"{}");
+        assert!(engine.load_gltf().is_err());
+        assert_eq!(30, engine.palette().len());
+    }
+
+    #[test]
+    fn batches() {
+        let mut engine = Engine::default();
+        upload(&mut engine, CRATE.0);
+        upload(&mut engine, CRATE.1);
+        engine.load_gltf().unwrap();
+        let indices = engine.state.mesh.indices.len();
+        assert_eq!(1, engine.state.batches.len());
+        assert_eq!((0, indices), {
+            let b = &engine.state.batches[0];
+            (b.first, b.count)
+        });
+        assert_eq!(None, engine.add_batch(indices, 1));
+        assert_eq!(None, engine.add_batch(1, usize::MAX));
+        assert_eq!(Some(1), engine.add_batch(0, 6));
+
+        // Every node drawing the mesh becomes an instance of its batch
+        engine.update(0.0);
+        engine.instance_scene();
+        let batch = &mut engine.state.batches[0];
+        let node = engine
+            .state
+            .scene
+            .nodes()
+            .iter()
+            .find(|n| n.mesh == Some(0));
+        assert_eq!(1, batch.len());
+        assert_eq!(*node.unwrap().world(), batch.instances()[0].model);
+        assert!(engine.state.batches[1].is_empty());
+
+        // A forest of copies is a single upload per batch
+        let batch = &mut engine.state.batches[0];
+        for x in 0..100 {
+            batch.push(
+                Mat4::translation(Vec3(x as f32, 0.0, 0.0)),
+                Color(0, 255, 0, 255),
+            );
+        }
+        let layout = engine.layout();
+        assert_eq!(9, layout.views().len());
+        assert_eq!(101 * Instance::stride(), layout.views()[7].len);
+        engine.instance_scene();
+        assert_eq!(1, engine.state.batches[0].len());
+
+        // Nodes out of the view are culled
+        engine.state.view_matrix = Mat4::translation(Vec3(100.0, 0.0, 0.0));
+        engine.instance_scene();
+        assert!(engine.state.batches[0].is_empty());
+    }
+
+    #[test]
+    fn culling() {
+        let mut engine = Engine::default();
+        upload(&mut engine, CRATE.0);
+        upload(&mut engine, CRATE.1);
+        engine.load_gltf().unwrap();
+        let scene = &mut engine.state.scene;
+        *scene = Scene::new();
+        let nodes = [
+            (0.0, Color(255, 0, 0, 255)),
+            (500.0, Color(0, 255, 0, 255)),
+            (50.0, Color(0, 0, 255, 255)),
+        ];
+        for (x, color) in nodes {
+            let mut local = Transform::identity();
+            local.translation = Vec3(x, 0.0, 0.0);
+            let node = scene.add("crate", local, None).unwrap();
+            let node = scene.node_mut(node).unwrap();
+            node.mesh = Some(0);
+            node.color = color;
+        }
+        scene.update();
+
+        // Every instance has the color of its node, whichever is culled
+        let scale = Mat4::scaling(Vec3(0.01, 0.01, 0.01));
+        for (x, visible) in [(0.0, 2), (500.0, 1), (0.0, 2)] {
+            engine.state.view_matrix = Mat4::translation(Vec3(-x, 0.0, 0.0)) * scale;
+            engine.instance_scene();
+            let batch = &engine.state.batches[0];
+            assert_eq!(visible, batch.len());
+            for instance in batch.instances() {
+                let (_, color) = nodes
+                    .iter()
+                    .find(|(x, _)| instance.model.3 .0 == *x)
+                    .unwrap();
+                assert!(instance.color == *color);
+            }
+        }
+    }
+}
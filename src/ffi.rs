@@ -0,0 +1,1068 @@
+//! Exports of the module to the host. Every export takes a handle returned
+//! by `engine_create` as its first argument, which has to be alive, and is
+//! not used by several threads at once. Hosts written before the handles
+//! have to be updated, as their first argument would be taken as a handle.
+//! A null handle refers to a global engine if the `global` feature is
+//! enabled, and traps otherwise. Pointers returned into an engine are valid
+//! until it is changed next.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::anim::{BlendMode, Transition};
+use crate::atlas::{Atlas, Options, Slot};
+use crate::core::{Color, Mat4, Quat, Ray, Transform, Vec2, Vec3};
+use crate::engine::Engine;
+use crate::input::Binding;
+use crate::instance::Instance;
+use crate::region::View;
+use crate::sprite::{Rect, Sprite, SpriteBatch, SpriteVertex};
+use crate::state::Vertex;
+use crate::vertex::{Attribute, Packing};
+use std::mem;
+#[cfg(feature = "global")]
+use std::sync::Mutex;
+
+/// Global engine, created on the first use the same way `engine_create`
+/// does, so that both start in the same state.
+#[cfg(feature = "global")]
+static GLOBAL: Mutex<Option<Engine>> = Mutex::new(None);
+
+/// Run a closure with the engine behind the handle. Panics on a null handle
+/// without the `global` feature, which traps the module rather than reading
+/// through the null pointer.
+unsafe fn with<R>(engine: *mut Engine, f: impl FnOnce(&mut Engine) -> R) -> R {
+    if let Some(engine) = engine.as_mut() {
+        return f(engine);
+    }
+    #[cfg(feature = "global")]
+    {
+        f(GLOBAL.lock().unwrap().get_or_insert_with(Engine::default))
+    }
+    #[cfg(not(feature = "global"))]
+    {
+        panic!("null engine handle")
+    }
+}
+
+/// Create an engine and return a handle to it, which has to be released
+/// with `engine_destroy`.
+#[no_mangle]
+pub extern "C" fn engine_create() -> *mut Engine {
+    Box::into_raw(Box::default())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn engine_destroy(engine: *mut Engine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_screen_width(engine: *mut Engine, width: u32) {
+    with(engine, |e| e.state.screen.0 = width as f32)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_screen_height(engine: *mut Engine, height: u32) {
+    with(engine, |e| e.state.screen.1 = height as f32)
+}
+
+/// Switch the camera to orbiting a target from where it is.
+#[no_mangle]
+pub unsafe extern "C" fn orbit_camera(engine: *mut Engine, x: f32, y: f32, z: f32) {
+    with(engine, |e| e.orbit_camera(Vec3(x, y, z)))
+}
+
+/// Switch the camera to flying freely from where it is.
+#[no_mangle]
+pub unsafe extern "C" fn fly_camera(engine: *mut Engine) {
+    with(engine, Engine::fly_camera)
+}
+
+/// Switch the camera to following a target from behind, turned to the
+/// heading in radians around the world up.
+#[no_mangle]
+pub unsafe extern "C" fn follow_camera(engine: *mut Engine, x: f32, y: f32, z: f32, heading: f32) {
+    with(engine, |e| e.follow_camera(Vec3(x, y, z), heading))
+}
+
+/// Turn the camera by the pointer movement in pixels.
+#[no_mangle]
+pub unsafe extern "C" fn look_camera(engine: *mut Engine, dx: f32, dy: f32) {
+    with(engine, |e| e.look_camera(dx, dy))
+}
+
+/// Zoom an orbiting camera by the wheel movement.
+#[no_mangle]
+pub unsafe extern "C" fn zoom_camera(engine: *mut Engine, delta: f32) {
+    with(engine, |e| e.zoom_camera(delta))
+}
+
+/// Move the target of a following or orbiting camera.
+#[no_mangle]
+pub unsafe extern "C" fn set_camera_target(
+    engine: *mut Engine,
+    x: f32,
+    y: f32,
+    z: f32,
+    heading: f32,
+) {
+    with(engine, |e| e.set_camera_target(Vec3(x, y, z), heading))
+}
+
+/// Set the vertical field of view in radians and the clipping distances.
+#[no_mangle]
+pub unsafe extern "C" fn set_camera_projection(engine: *mut Engine, fov: f32, near: f32, far: f32) {
+    with(engine, |e| {
+        let camera = &mut e.state.camera;
+        camera.fov = fov;
+        camera.near = near;
+        camera.far = far;
+    })
+}
+
+/// Hold a key down, by its DOM key code. Key repeats are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn key_down(engine: *mut Engine, code: u32) {
+    with(engine, |e| e.state.input.key_down(code))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn key_up(engine: *mut Engine, code: u32) {
+    with(engine, |e| e.state.input.key_up(code))
+}
+
+/// Move the pointer to a position in pixels from the top left corner of the
+/// canvas, by the given movement, which keeps going while it is locked.
+#[no_mangle]
+pub unsafe extern "C" fn pointer_move(engine: *mut Engine, x: f32, y: f32, dx: f32, dy: f32) {
+    with(engine, |e| {
+        e.state.input.pointer_move(Vec2(x, y), Vec2(dx, dy))
+    })
+}
+
+/// Press or release a pointer button, by its DOM button number.
+#[no_mangle]
+pub unsafe extern "C" fn pointer_button(engine: *mut Engine, button: u32, down: bool) {
+    with(engine, |e| e.state.input.pointer_button(button, down))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pointer_wheel(engine: *mut Engine, delta: f32) {
+    with(engine, |e| e.state.input.pointer_wheel(delta))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn touch_start(engine: *mut Engine, id: u32, x: f32, y: f32) {
+    with(engine, |e| e.state.input.touch_start(id, Vec2(x, y)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn touch_move(engine: *mut Engine, id: u32, x: f32, y: f32) {
+    with(engine, |e| e.state.input.touch_move(id, Vec2(x, y)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn touch_end(engine: *mut Engine, id: u32) {
+    with(engine, |e| e.state.input.touch_end(id))
+}
+
+/// Release every key, button and touch, such as when the canvas loses the
+/// focus and the releases would be missed.
+#[no_mangle]
+pub unsafe extern "C" fn release_input(engine: *mut Engine) {
+    with(engine, |e| e.state.input.release_all())
+}
+
+/// Return the state flags of a key: 1 if it is held, 2 if it has been
+/// pressed and 4 if it has been released since the last simulation step.
+#[no_mangle]
+pub unsafe extern "C" fn key_state(engine: *mut Engine, code: u32) -> u32 {
+    with(engine, |e| e.state.input.keys.state(code))
+}
+
+/// Return the state flags of a pointer button, see `key_state`.
+#[no_mangle]
+pub unsafe extern "C" fn button_state(engine: *mut Engine, button: u32) -> u32 {
+    with(engine, |e| e.state.input.pointer.buttons.state(button))
+}
+
+/// Bind an action to a key if the device is 0, or to a pointer button if
+/// it is 1. Return false for any other device, or if the action index is
+/// not below `MAX_ACTIONS`.
+#[no_mangle]
+pub unsafe extern "C" fn bind_action(
+    engine: *mut Engine,
+    action: usize,
+    device: u32,
+    code: u32,
+) -> bool {
+    let binding = match device {
+        0 => Binding::Key(code),
+        1 => Binding::Button(code),
+        _ => return false,
+    };
+    with(engine, |e| e.state.input.actions.bind(action, binding))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unbind_action(engine: *mut Engine, action: usize) {
+    with(engine, |e| e.state.input.actions.unbind(action))
+}
+
+/// Return the state flags of an action, combined from all of its bindings,
+/// see `key_state`.
+#[no_mangle]
+pub unsafe extern "C" fn action_state(engine: *mut Engine, action: usize) -> u32 {
+    with(engine, |e| e.state.input.action_state(action))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn state_view_matrix(engine: *mut Engine) -> *const Mat4 {
+    with(engine, |e| &e.state.view_matrix as *const Mat4)
+}
+
+/// Cast a ray from the camera through a point of the canvas, given in pixels
+/// from its top left corner, and return a pointer to its origin and
+/// direction. The previous ray is kept if the view can not be unprojected.
+#[no_mangle]
+pub unsafe extern "C" fn ray_from_screen(engine: *mut Engine, x: f32, y: f32) -> *const Ray {
+    with(engine, |e| e.ray_from_screen(x, y) as *const Ray)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn state_data(engine: *mut Engine) -> *const Vertex {
+    with(engine, |e| e.state.mesh.vertices.as_ptr())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn state_len(engine: *mut Engine) -> usize {
+    with(engine, |e| {
+        e.state.mesh.vertices.len() * mem::size_of::<Vertex>()
+    })
+}
+
+/// Return a pointer to the indices of the mesh, which are drawn as
+/// triangles, or none if it is unindexed.
+#[no_mangle]
+pub unsafe extern "C" fn state_indices(engine: *mut Engine) -> *const u8 {
+    with(engine, |e| e.state.mesh.indices.view().ptr as *const u8)
+}
+
+/// Return the number of indices of the mesh.
+#[no_mangle]
+pub unsafe extern "C" fn state_indices_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.mesh.indices.len())
+}
+
+/// Return the WebGL type of the indices of the mesh, either
+/// `UNSIGNED_SHORT` or `UNSIGNED_INT`.
+#[no_mangle]
+pub unsafe extern "C" fn state_index_format(engine: *mut Engine) -> u32 {
+    with(engine, |e| e.state.mesh.indices.format() as u32)
+}
+
+/// Set whether the vertex buffer is laid out in a block per attribute
+/// rather than interleaved, from the next mesh loaded on.
+#[no_mangle]
+pub unsafe extern "C" fn set_vertex_packing(engine: *mut Engine, deinterleaved: bool) {
+    let packing = if deinterleaved {
+        Packing::Deinterleaved
+    } else {
+        Packing::Interleaved
+    };
+    with(engine, |e| e.state.packing = packing)
+}
+
+/// Return a pointer to the attributes of the vertex buffer, each of which is
+/// the semantic, the WebGL component type, the number of components, the
+/// offset and the stride in bytes as 32 bit words, followed by a byte of
+/// whether it is normalized, 24 bytes in total.
+#[no_mangle]
+pub unsafe extern "C" fn vertex_format(engine: *mut Engine) -> *const Attribute {
+    with(engine, |e| e.state.vertex_buffer.format().0.as_ptr())
+}
+
+/// Return the number of attributes of the vertex buffer.
+#[no_mangle]
+pub unsafe extern "C" fn vertex_format_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.vertex_buffer.format().0.len())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn vertex_buffer(engine: *mut Engine) -> *const u8 {
+    with(engine, |e| e.state.vertex_buffer.data().as_ptr())
+}
+
+/// Return the size of the vertex buffer in bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vertex_buffer_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.vertex_buffer.data().len())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gen(engine: *mut Engine) {
+    with(engine, Engine::gen)
+}
+
+/// Allocate a buffer of the given size for the next asset to be loaded and
+/// return a pointer to it, so that the asset bytes can be copied straight
+/// into the module memory. Return null and release the buffers uploaded so
+/// far if there is no memory for it.
+#[no_mangle]
+pub unsafe extern "C" fn upload_buffer(engine: *mut Engine, len: usize) -> *mut u8 {
+    with(engine, |e| {
+        e.upload_buffer(len)
+            .map_or(std::ptr::null_mut(), <[u8]>::as_mut_ptr)
+    })
+}
+
+/// Load a glTF asset from the uploaded buffers, the first of which is the
+/// JSON and the rest are the binary buffers in order. Return false if the
+/// asset is invalid, in which case the engine is kept intact.
+#[no_mangle]
+pub unsafe extern "C" fn load_gltf(engine: *mut Engine) -> bool {
+    with(engine, |e| e.load_gltf().is_ok())
+}
+
+/// Start playing an animation clip of the loaded asset from the beginning.
+/// Return false if there is no such clip.
+#[no_mangle]
+pub unsafe extern "C" fn play_animation(engine: *mut Engine, clip: usize) -> bool {
+    with(engine, |e| {
+        e.state.animator.as_mut().is_some_and(|a| a.play(clip))
+    })
+}
+
+/// Move the animation by the given number of milliseconds and recompute the
+/// joint palette. The simulation advances the animation every step, so this
+/// is for hosts moving it on their own, such as scrubbing a paused world.
+#[no_mangle]
+pub unsafe extern "C" fn advance_animation(engine: *mut Engine, dt: f32) {
+    with(engine, |e| {
+        if let Some(animator) = &mut e.state.animator {
+            animator.advance(dt / 1000.0);
+        }
+    })
+}
+
+/// Set a parameter of the animation graph, which drives the transitions
+/// and blends of its layers. Return false if no animated asset is loaded or
+/// the index is not below `MAX_PARAMETERS`.
+#[no_mangle]
+pub unsafe extern "C" fn set_animation_parameter(
+    engine: *mut Engine,
+    index: usize,
+    value: f32,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .animator
+            .as_mut()
+            .is_some_and(|a| a.graph.set_parameter(index, value))
+    })
+}
+
+/// Add an animation layer with a state per clip on top of the others, and
+/// return its index, or -1 if no animated asset is loaded. The layer is
+/// limited to a joint and its descendants unless the joint is negative.
+#[no_mangle]
+pub unsafe extern "C" fn add_animation_layer(
+    engine: *mut Engine,
+    additive: bool,
+    mask_joint: i32,
+) -> i32 {
+    let mode = if additive {
+        BlendMode::Additive
+    } else {
+        BlendMode::Override
+    };
+    let mask = usize::try_from(mask_joint).ok();
+    with(engine, |e| {
+        e.add_animation_layer(mode, mask).map_or(-1, |i| i as i32)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_animation_layer_weight(
+    engine: *mut Engine,
+    layer: usize,
+    weight: f32,
+) -> bool {
+    with(engine, |e| match e.animation_layer(layer) {
+        Some(layer) => {
+            layer.weight = weight;
+            true
+        }
+        None => false,
+    })
+}
+
+/// Crossfade a layer to the state of a clip over the given number of
+/// milliseconds. Return false if there is no such layer or state.
+#[no_mangle]
+pub unsafe extern "C" fn crossfade_animation(
+    engine: *mut Engine,
+    layer: usize,
+    clip: usize,
+    duration: f32,
+) -> bool {
+    with(engine, |e| {
+        e.animation_layer(layer)
+            .is_some_and(|l| l.crossfade(clip, duration / 1000.0))
+    })
+}
+
+/// Add a transition of a layer, taken once a parameter is in the
+/// `[min, max)` range, from a state or from any state if it is negative.
+/// Return false if there is no such layer or state.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn add_animation_transition(
+    engine: *mut Engine,
+    layer: usize,
+    from: i32,
+    to: usize,
+    parameter: usize,
+    min: f32,
+    max: f32,
+    duration: f32,
+) -> bool {
+    let from = usize::try_from(from).ok();
+    with(engine, |e| {
+        let Some(layer) = e.animation_layer(layer) else {
+            return false;
+        };
+        if to >= layer.states.len() || from.is_some_and(|f| f >= layer.states.len()) {
+            return false;
+        }
+        layer.transitions.push(Transition {
+            from,
+            to,
+            parameter,
+            min,
+            max,
+            duration: duration / 1000.0,
+        });
+        true
+    })
+}
+
+/// Return the number of nodes of the scene.
+#[no_mangle]
+pub unsafe extern "C" fn scene_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.scene.len())
+}
+
+/// Add a node to the scene under a parent, or as a root if it is negative,
+/// and return its index, or -1 if there is no such parent.
+#[no_mangle]
+pub unsafe extern "C" fn add_node(engine: *mut Engine, parent: i32) -> i32 {
+    let parent = usize::try_from(parent).ok();
+    with(engine, |e| {
+        e.state
+            .scene
+            .add("", Transform::identity(), parent)
+            .map_or(-1, |n| n as i32)
+    })
+}
+
+/// Return the parent of a node, or -1 if it is a root or there is no such
+/// node.
+#[no_mangle]
+pub unsafe extern "C" fn node_parent(engine: *mut Engine, node: usize) -> i32 {
+    with(engine, |e| {
+        e.state
+            .scene
+            .node(node)
+            .and_then(|n| n.parent())
+            .map_or(-1, |p| p as i32)
+    })
+}
+
+/// Move a node under a parent, or make it a root if it is negative. Return
+/// false if either doesn't exist, or the parent is below the node.
+#[no_mangle]
+pub unsafe extern "C" fn set_node_parent(engine: *mut Engine, node: usize, parent: i32) -> bool {
+    let parent = usize::try_from(parent).ok();
+    with(engine, |e| e.state.scene.set_parent(node, parent))
+}
+
+/// Return the mesh a node draws, or -1 if it draws none.
+#[no_mangle]
+pub unsafe extern "C" fn node_mesh(engine: *mut Engine, node: usize) -> i32 {
+    with(engine, |e| {
+        e.state
+            .scene
+            .node(node)
+            .and_then(|n| n.mesh)
+            .map_or(-1, |m| m as i32)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_node_translation(
+    engine: *mut Engine,
+    node: usize,
+    x: f32,
+    y: f32,
+    z: f32,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .scene
+            .local_mut(node)
+            .map(|t| t.translation = Vec3(x, y, z))
+            .is_some()
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_node_rotation(
+    engine: *mut Engine,
+    node: usize,
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .scene
+            .local_mut(node)
+            .map(|t| t.rotation = Quat(x, y, z, w))
+            .is_some()
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_node_scale(
+    engine: *mut Engine,
+    node: usize,
+    x: f32,
+    y: f32,
+    z: f32,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .scene
+            .local_mut(node)
+            .map(|t| t.scale = Vec3(x, y, z))
+            .is_some()
+    })
+}
+
+/// Set the color the instance of the node mesh is drawn with by
+/// `instance_scene`. Return false if there is no such node.
+#[no_mangle]
+pub unsafe extern "C" fn set_node_color(
+    engine: *mut Engine,
+    node: usize,
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .scene
+            .node_mut(node)
+            .map(|n| n.color = Color(r, g, b, a))
+            .is_some()
+    })
+}
+
+/// Return a pointer to the world matrix of a node as of the last update, or
+/// null if there is no such node.
+#[no_mangle]
+pub unsafe extern "C" fn node_world_matrix(engine: *mut Engine, node: usize) -> *const Mat4 {
+    with(engine, |e| {
+        e.state
+            .scene
+            .node(node)
+            .map_or(std::ptr::null(), |n| n.world() as *const Mat4)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn state_palette(engine: *mut Engine) -> *const Mat4 {
+    with(engine, |e| e.palette().as_ptr())
+}
+
+/// Add a batch of instances of an index range of the mesh, and return its
+/// index, or -1 if the range is past the indices.
+#[no_mangle]
+pub unsafe extern "C" fn add_batch(engine: *mut Engine, first: usize, count: usize) -> i32 {
+    with(engine, |e| {
+        e.add_batch(first, count).map_or(-1, |b| b as i32)
+    })
+}
+
+/// Return the number of batches, which is one per mesh of a loaded asset,
+/// followed by the added ones.
+#[no_mangle]
+pub unsafe extern "C" fn batches_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.batches.len())
+}
+
+/// Return the first index of the range a batch draws.
+#[no_mangle]
+pub unsafe extern "C" fn batch_first(engine: *mut Engine, batch: usize) -> usize {
+    with(engine, |e| {
+        e.state.batches.get(batch).map_or(0, |b| b.first)
+    })
+}
+
+/// Return the number of indices a batch draws.
+#[no_mangle]
+pub unsafe extern "C" fn batch_count(engine: *mut Engine, batch: usize) -> usize {
+    with(engine, |e| {
+        e.state.batches.get(batch).map_or(0, |b| b.count)
+    })
+}
+
+/// Return whether the host binds the colors of the instances of a batch.
+#[no_mangle]
+pub unsafe extern "C" fn batch_colored(engine: *mut Engine, batch: usize) -> bool {
+    with(engine, |e| {
+        e.state.batches.get(batch).is_some_and(|b| b.colored)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_batch_colored(engine: *mut Engine, batch: usize, colored: bool) {
+    with(engine, |e| {
+        if let Some(b) = e.state.batches.get_mut(batch) {
+            b.colored = colored;
+        }
+    })
+}
+
+/// Return the instances of a batch, or null if there is no such batch.
+#[no_mangle]
+pub unsafe extern "C" fn batch_instances(engine: *mut Engine, batch: usize) -> *const Instance {
+    with(engine, |e| {
+        e.state
+            .batches
+            .get(batch)
+            .map_or(std::ptr::null(), |b| b.instances().as_ptr())
+    })
+}
+
+/// Return the number of instances of a batch.
+#[no_mangle]
+pub unsafe extern "C" fn batch_instances_len(engine: *mut Engine, batch: usize) -> usize {
+    with(engine, |e| {
+        e.state.batches.get(batch).map_or(0, |b| b.len())
+    })
+}
+
+/// Return the size of an instance in bytes: the model matrix, in columns,
+/// followed by the color.
+#[no_mangle]
+pub extern "C" fn instance_stride() -> usize {
+    Instance::stride()
+}
+
+/// Add an instance to a batch, at the origin and white, and return its
+/// index, or -1 if there is no such batch.
+#[no_mangle]
+pub unsafe extern "C" fn add_instance(engine: *mut Engine, batch: usize) -> i32 {
+    with(engine, |e| {
+        e.state.batches.get_mut(batch).map_or(-1, |b| {
+            b.push(Mat4::identity(), Color(255, 255, 255, 255)) as i32
+        })
+    })
+}
+
+/// Remove an instance of a batch, moving the last one into its place.
+#[no_mangle]
+pub unsafe extern "C" fn remove_instance(
+    engine: *mut Engine,
+    batch: usize,
+    instance: usize,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .batches
+            .get_mut(batch)
+            .is_some_and(|b| b.remove(instance))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn clear_instances(engine: *mut Engine, batch: usize) {
+    with(engine, |e| {
+        if let Some(b) = e.state.batches.get_mut(batch) {
+            b.clear();
+        }
+    })
+}
+
+/// Set the model matrix of an instance from a translation, a rotation
+/// quaternion and a scale.
+#[no_mangle]
+pub unsafe extern "C" fn set_instance_transform(
+    engine: *mut Engine,
+    batch: usize,
+    instance: usize,
+    tx: f32,
+    ty: f32,
+    tz: f32,
+    rx: f32,
+    ry: f32,
+    rz: f32,
+    rw: f32,
+    s: f32,
+) -> bool {
+    let model = Mat4::compose(Vec3(tx, ty, tz), Vec3(s, s, s), Quat(rx, ry, rz, rw));
+    with(engine, |e| {
+        e.state
+            .batches
+            .get_mut(batch)
+            .and_then(|b| b.instances_mut().get_mut(instance))
+            .map(|i| i.model = model)
+            .is_some()
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_instance_color(
+    engine: *mut Engine,
+    batch: usize,
+    instance: usize,
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+) -> bool {
+    with(engine, |e| {
+        e.state
+            .batches
+            .get_mut(batch)
+            .and_then(|batch| batch.instances_mut().get_mut(instance))
+            .map(|i| i.color = Color(r, g, b, a))
+            .is_some()
+    })
+}
+
+/// Replace the instances of the batches with the scene nodes drawing their
+/// meshes, as of the last update and with their colors, culling the ones
+/// out of the view.
+#[no_mangle]
+pub unsafe extern "C" fn instance_scene(engine: *mut Engine) {
+    with(engine, Engine::instance_scene)
+}
+
+/// Add a sprite of the whole texture, untinted, on the layer 0, and return
+/// its index.
+#[no_mangle]
+pub unsafe extern "C" fn add_sprite(
+    engine: *mut Engine,
+    texture: u32,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> usize {
+    with(engine, |e| {
+        e.state
+            .sprites
+            .push(Sprite::new(texture, Vec2(x, y), Vec2(width, height)))
+    })
+}
+
+/// Remove a sprite, moving the last one into its place.
+#[no_mangle]
+pub unsafe extern "C" fn remove_sprite(engine: *mut Engine, sprite: usize) -> bool {
+    with(engine, |e| e.state.sprites.remove(sprite))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn clear_sprites(engine: *mut Engine) {
+    with(engine, |e| e.state.sprites.sprites.clear())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sprites_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.sprites.sprites.len())
+}
+
+/// Run a closure with a sprite, and return false if there is no such one.
+unsafe fn with_sprite(engine: *mut Engine, sprite: usize, f: impl FnOnce(&mut Sprite)) -> bool {
+    with(engine, |e| {
+        e.state.sprites.sprites.get_mut(sprite).map(f).is_some()
+    })
+}
+
+/// Place a sprite, centered at a position and turned by an angle in radians.
+#[no_mangle]
+pub unsafe extern "C" fn set_sprite_transform(
+    engine: *mut Engine,
+    sprite: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rotation: f32,
+) -> bool {
+    with_sprite(engine, sprite, |s| {
+        s.position = Vec2(x, y);
+        s.size = Vec2(width, height);
+        s.rotation = rotation;
+    })
+}
+
+/// Set the rectangle of the texture a sprite shows, in texture coordinates.
+#[no_mangle]
+pub unsafe extern "C" fn set_sprite_uv(
+    engine: *mut Engine,
+    sprite: usize,
+    u0: f32,
+    v0: f32,
+    u1: f32,
+    v1: f32,
+) -> bool {
+    with_sprite(engine, sprite, |s| {
+        s.uv = Rect::new(Vec2(u0, v0), Vec2(u1, v1))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_sprite_tint(
+    engine: *mut Engine,
+    sprite: usize,
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+) -> bool {
+    with_sprite(engine, sprite, |s| s.tint = Color(r, g, b, a))
+}
+
+/// Set the texture and the layer of a sprite, which are drawn from the
+/// lowest layer up.
+#[no_mangle]
+pub unsafe extern "C" fn set_sprite_texture(
+    engine: *mut Engine,
+    sprite: usize,
+    texture: u32,
+    layer: i32,
+) -> bool {
+    with_sprite(engine, sprite, |s| {
+        s.texture = texture;
+        s.layer = layer;
+    })
+}
+
+/// Return the sprite quad vertices as of the last update: a position, a
+/// texture coordinate and a tint each.
+#[no_mangle]
+pub unsafe extern "C" fn sprite_vertices(engine: *mut Engine) -> *const SpriteVertex {
+    with(engine, |e| e.state.sprites.vertices().as_ptr())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sprite_vertices_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.sprites.vertices().len())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sprite_indices(engine: *mut Engine) -> *const u8 {
+    with(engine, |e| {
+        e.state.sprites.indices().view().ptr as *const u8
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sprite_indices_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.sprites.indices().len())
+}
+
+/// Return the WebGL type of the sprite indices.
+#[no_mangle]
+pub unsafe extern "C" fn sprite_index_format(engine: *mut Engine) -> u32 {
+    with(engine, |e| e.state.sprites.indices().format() as u32)
+}
+
+/// Return the sprite batches as of the last update, in the order to draw
+/// them: the texture, the layer, the first index and the number of indices
+/// each.
+#[no_mangle]
+pub unsafe extern "C" fn sprite_batches(engine: *mut Engine) -> *const SpriteBatch {
+    with(engine, |e| e.state.sprites.batches().as_ptr())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sprite_batches_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.sprites.batches().len())
+}
+
+/// Add an empty atlas and return its index, which is also the number of the
+/// texture sprites of its images are drawn with.
+#[no_mangle]
+pub unsafe extern "C" fn add_atlas(
+    engine: *mut Engine,
+    max_size: u32,
+    padding: u32,
+    extrude: u32,
+    power_of_two: bool,
+) -> usize {
+    let options = Options {
+        max_size,
+        padding,
+        extrude,
+        power_of_two,
+    };
+    with(engine, |e| {
+        e.state.atlases.push(Atlas::new(options));
+        e.state.atlases.len() - 1
+    })
+}
+
+/// Add an image to an atlas, growing it if needed, and return the index of
+/// its slot, or -1 if it doesn't fit into the maximum size.
+#[no_mangle]
+pub unsafe extern "C" fn atlas_insert(
+    engine: *mut Engine,
+    atlas: usize,
+    width: u32,
+    height: u32,
+) -> i32 {
+    with(engine, |e| {
+        e.state
+            .atlases
+            .get_mut(atlas)
+            .and_then(|a| a.insert(width, height))
+            .map_or(-1, |s| s as i32)
+    })
+}
+
+/// Replace the images of an atlas with the uploaded pairs of 32 bit widths
+/// and heights. Return false if they don't fit, in which case the atlas is
+/// kept intact.
+#[no_mangle]
+pub unsafe extern "C" fn pack_atlas(engine: *mut Engine, atlas: usize) -> bool {
+    with(engine, |e| e.pack_atlas(atlas).is_ok())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn atlas_width(engine: *mut Engine, atlas: usize) -> u32 {
+    with(engine, |e| {
+        e.state.atlases.get(atlas).map_or(0, |a| a.width())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn atlas_height(engine: *mut Engine, atlas: usize) -> u32 {
+    with(engine, |e| {
+        e.state.atlases.get(atlas).map_or(0, |a| a.height())
+    })
+}
+
+/// Return the slots of the images of an atlas in pixels: the left, the
+/// bottom, the width and the height each.
+#[no_mangle]
+pub unsafe extern "C" fn atlas_slots(engine: *mut Engine, atlas: usize) -> *const Slot {
+    with(engine, |e| {
+        e.state
+            .atlases
+            .get(atlas)
+            .map_or(std::ptr::null(), |a| a.slots().as_ptr())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn atlas_slots_len(engine: *mut Engine, atlas: usize) -> usize {
+    with(engine, |e| {
+        e.state.atlases.get(atlas).map_or(0, |a| a.len())
+    })
+}
+
+/// Show an image of an atlas on a sprite. The texture coordinates are of
+/// the current atlas size, so they have to be set again after it grows.
+#[no_mangle]
+pub unsafe extern "C" fn set_sprite_slot(
+    engine: *mut Engine,
+    sprite: usize,
+    atlas: usize,
+    slot: usize,
+) -> bool {
+    with(engine, |e| e.set_sprite_slot(sprite, atlas, slot))
+}
+
+/// Return the number of matrices in the joint palette.
+#[no_mangle]
+pub unsafe extern "C" fn state_palette_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.palette().len())
+}
+
+/// Return the generation of the memory layout, which changes whenever the
+/// views returned by `layout_views` do, or the memory grows. The host is
+/// supposed to poll it each frame and recreate its views on a change.
+#[no_mangle]
+pub unsafe extern "C" fn layout_generation(engine: *mut Engine) -> u32 {
+    with(engine, |e| e.layout().generation())
+}
+
+/// Return the views of the memory shared with the host as of the last
+/// `layout_generation` call, see `Engine::layout` for their order.
+#[no_mangle]
+pub unsafe extern "C" fn layout_views(engine: *mut Engine) -> *const View {
+    with(engine, |e| e.state.layout.views().as_ptr())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn layout_views_len(engine: *mut Engine) -> usize {
+    with(engine, |e| e.state.layout.views().len())
+}
+
+/// Advance the world by the time of a frame in milliseconds, simulating it
+/// in fixed steps.
+#[no_mangle]
+pub unsafe extern "C" fn update(engine: *mut Engine, dt: f64) {
+    with(engine, |e| e.update(dt))
+}
+
+/// Set the number of simulation steps per second.
+#[no_mangle]
+pub unsafe extern "C" fn set_tick_rate(engine: *mut Engine, rate: f64) {
+    with(engine, |e| e.state.timestep.set_rate(rate))
+}
+
+/// Set the number of simulation steps a single frame may catch up with,
+/// dropping the time beyond them.
+#[no_mangle]
+pub unsafe extern "C" fn set_max_steps(engine: *mut Engine, max_steps: u32) {
+    with(engine, |e| e.state.timestep.set_max_steps(max_steps))
+}
+
+/// Return the fraction of a simulation step the rendered state is past the
+/// previous one, from 0 to 1, to interpolate the simulation states with.
+#[no_mangle]
+pub unsafe extern "C" fn interpolation_alpha(engine: *mut Engine) -> f32 {
+    with(engine, |e| e.state.timestep.alpha())
+}
+
+#[cfg(all(test, feature = "global"))]
+mod tests {
+    use crate::engine::VERTEX_CAPACITY;
+    use crate::ffi::{engine_create, engine_destroy, with};
+    use std::ptr;
+
+    #[test]
+    fn global() {
+        unsafe {
+            // The global engine starts the same as a created one
+            let engine = engine_create();
+            let state = |e: *mut _| {
+                with(e, |e| {
+                    (e.state.camera.position, e.state.mesh.vertices.capacity())
+                })
+            };
+            assert_eq!(state(engine), state(ptr::null_mut()));
+            assert_eq!(VERTEX_CAPACITY, state(ptr::null_mut()).1);
+            engine_destroy(engine);
+        }
+    }
+}
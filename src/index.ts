@@ -144,7 +144,8 @@ async function main() {
   // const idx = new Uint8Array(cube, 1152, 72)
 
   rusty.initState()
-  const viewMatrix = rusty.viewMatrix;
+  let generation = rusty.layoutGeneration;
+  let viewMatrix = rusty.viewMatrix;
   // const data = rusty.verticesData;
 
   const { update } = spriteRenderer(gl)
@@ -157,8 +158,17 @@ async function main() {
     rusty.screen = { width, height };
   }
 
-  const render = () => {
-    rusty.update()
+  let last = performance.now()
+  const render = (time: number) => {
+    rusty.update(time - last)
+    last = time
+
+    // Views detach when the module memory grows, so they are recreated
+    // whenever the layout changes.
+    if (rusty.layoutGeneration !== generation) {
+      generation = rusty.layoutGeneration
+      viewMatrix = rusty.viewMatrix
+    }
 
     gl.clearColor(0.1, 0.1, 0.1, 1)
     gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT)
@@ -174,17 +184,37 @@ async function main() {
     requestAnimationFrame(render)
   }
 
-  let dragging = false
-  canvas.onmousedown = (e) => { dragging = true }
-  canvas.onmouseup = () => { dragging = false }
-  canvas.onmousemove = e => {
-    if (!dragging) return
-    rusty.rotateCamera(e.movementX)
+  canvas.onmousedown = e => rusty.pointerButton(e.button, true)
+  canvas.onmouseup = e => rusty.pointerButton(e.button, false)
+  canvas.onmousemove = e => rusty.pointerMove(e.offsetX, e.offsetY, e.movementX, e.movementY)
+  canvas.onwheel = e => {
+    e.preventDefault()
+    rusty.pointerWheel(e.deltaY)
+  }
+  canvas.ontouchstart = canvas.ontouchmove = e => {
+    e.preventDefault()
+    // Touches are in the viewport, while the mouse is relative to the canvas
+    const { left, top } = canvas.getBoundingClientRect()
+    for (const t of Array.from(e.changedTouches)) {
+      if (e.type === 'touchstart') {
+        rusty.touchStart(t.identifier, t.clientX - left, t.clientY - top)
+      } else {
+        rusty.touchMove(t.identifier, t.clientX - left, t.clientY - top)
+      }
+    }
+  }
+  canvas.ontouchend = canvas.ontouchcancel = e => {
+    for (const t of Array.from(e.changedTouches)) {
+      rusty.touchEnd(t.identifier)
+    }
   }
+  window.onkeydown = e => rusty.keyDown(e.keyCode)
+  window.onkeyup = e => rusty.keyUp(e.keyCode)
+  window.onblur = () => rusty.releaseInput()
 
   window.onresize = onresize
   onresize()
-  render()
+  requestAnimationFrame(render)
 }
 
 main();
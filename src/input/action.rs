@@ -0,0 +1,118 @@
+use std::ops::Deref;
+
+/// Maximum number of actions, which bounds the memory an action index bound
+/// by the host can take.
+pub const MAX_ACTIONS: usize = 256;
+
+/// Binding is a key or a pointer button an action is triggered by.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(u32),
+    Button(u32),
+}
+
+/// Action is something the player can do, such as jumping, bound to any
+/// number of keys and buttons, so that the gameplay doesn't depend on the
+/// controls, and they can be remapped.
+pub struct Action {
+    pub name: String,
+    pub bindings: Vec<Binding>,
+}
+
+/// Actions are the actions by index, which the host refers to them with,
+/// while the gameplay code can find them by name.
+pub struct Actions(Vec<Action>);
+
+impl Deref for Actions {
+    type Target = [Action];
+
+    #[inline]
+    fn deref(&self) -> &[Action] {
+        &self.0
+    }
+}
+
+impl Default for Actions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Actions {
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Return the index of the action of a name, adding it if there is no
+    /// such action yet.
+    pub fn add(&mut self, name: &str) -> usize {
+        self.find(name).unwrap_or_else(|| {
+            self.0.push(Action {
+                name: name.to_string(),
+                bindings: Vec::new(),
+            });
+            self.0.len() - 1
+        })
+    }
+
+    /// Return the index of the action of a name.
+    #[inline]
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|a| a.name == name)
+    }
+
+    /// Bind an action to a key or a button, adding unnamed actions up to it
+    /// if needed. Return false if the index is not below `MAX_ACTIONS`.
+    pub fn bind(&mut self, action: usize, binding: Binding) -> bool {
+        if action >= MAX_ACTIONS {
+            return false;
+        }
+        if action >= self.0.len() {
+            self.0.resize_with(action + 1, || Action {
+                name: String::new(),
+                bindings: Vec::new(),
+            });
+        }
+        let bindings = &mut self.0[action].bindings;
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        true
+    }
+
+    /// Remove all the bindings of an action, keeping the action itself.
+    #[inline]
+    pub fn unbind(&mut self, action: usize) {
+        if let Some(action) = self.0.get_mut(action) {
+            action.bindings.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{Actions, Binding, MAX_ACTIONS};
+
+    #[test]
+    fn bind() {
+        let mut actions = Actions::new();
+        assert_eq!(0, actions.add("fire"));
+        assert_eq!(0, actions.add("fire"));
+        assert_eq!(None, actions.find("jump"));
+
+        // Binding past the end adds unnamed actions
+        assert!(actions.bind(2, Binding::Key(32)));
+        assert!(actions.bind(2, Binding::Key(32)));
+        assert!(!actions.bind(MAX_ACTIONS, Binding::Key(32)));
+        assert_eq!(3, actions.len());
+        assert_eq!("", actions[1].name);
+        assert_eq!([Binding::Key(32)], *actions[2].bindings);
+
+        actions.unbind(2);
+        actions.unbind(5);
+        assert!(actions[2].bindings.is_empty());
+        assert_eq!(3, actions.len());
+    }
+}
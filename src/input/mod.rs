@@ -0,0 +1,444 @@
+pub mod action;
+
+pub use action::{Action, Actions, Binding, MAX_ACTIONS};
+
+use crate::core::Vec2;
+
+/// State flag of a key or a button being held down.
+pub const HELD: u32 = 1;
+/// State flag of a key or a button having been pressed since the last step.
+pub const PRESSED: u32 = 2;
+/// State flag of a key or a button having been released since the last step.
+pub const RELEASED: u32 = 4;
+
+/// Codes of the commonly used keys, which are the DOM key codes.
+pub mod key {
+    pub const ENTER: u32 = 13;
+    pub const SHIFT: u32 = 16;
+    pub const CONTROL: u32 = 17;
+    pub const ESCAPE: u32 = 27;
+    pub const SPACE: u32 = 32;
+    pub const LEFT: u32 = 37;
+    pub const UP: u32 = 38;
+    pub const RIGHT: u32 = 39;
+    pub const DOWN: u32 = 40;
+    pub const A: u32 = 65;
+    pub const D: u32 = 68;
+    pub const E: u32 = 69;
+    pub const Q: u32 = 81;
+    pub const S: u32 = 83;
+    pub const W: u32 = 87;
+}
+
+/// Switches is the state of a set of keys or buttons, by code, packed into
+/// `N` words of bits. Codes past the set are ignored.
+#[derive(Copy, Clone)]
+pub struct Switches<const N: usize> {
+    held: [u64; N],
+    pressed: [u64; N],
+    released: [u64; N],
+}
+
+/// Pointer is the state of the mouse or the pen, with the movement and the
+/// wheel accumulated since the last step.
+#[derive(Copy, Clone)]
+pub struct Pointer {
+    /// Position in pixels from the top left corner of the canvas.
+    pub position: Vec2,
+    pub delta: Vec2,
+    pub wheel: f32,
+    pub buttons: Switches<1>,
+}
+
+/// Touch is a finger on the screen, kept until the step after it is lifted,
+/// so that short taps are seen too.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Touch {
+    pub id: u32,
+    /// Position in pixels from the top left corner of the canvas.
+    pub position: Vec2,
+    /// Movement since the last step.
+    pub delta: Vec2,
+    /// Whether the touch has started since the last step.
+    pub began: bool,
+    /// Whether the touch has ended since the last step.
+    pub ended: bool,
+}
+
+/// Input buffers the events the host sends between simulation steps, so
+/// that a step sees all that has happened since the previous one, even if
+/// a key has been both pressed and released in between.
+pub struct Input {
+    pub keys: Switches<4>,
+    pub pointer: Pointer,
+    pub touches: Vec<Touch>,
+    pub actions: Actions,
+}
+
+impl<const N: usize> Default for Switches<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Switches<N> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            held: [0; N],
+            pressed: [0; N],
+            released: [0; N],
+        }
+    }
+
+    /// Return the word and the bit of a code, if it is in the set.
+    #[inline]
+    fn bit(code: u32) -> Option<(usize, u64)> {
+        let code = code as usize;
+        (code < N * 64).then(|| (code / 64, 1 << (code % 64)))
+    }
+
+    /// Hold a switch down, which is only a press if it has been up, so that
+    /// key repeats are ignored.
+    #[inline]
+    pub fn press(&mut self, code: u32) {
+        if let Some((i, bit)) = Self::bit(code) {
+            if self.held[i] & bit == 0 {
+                self.held[i] |= bit;
+                self.pressed[i] |= bit;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn release(&mut self, code: u32) {
+        if let Some((i, bit)) = Self::bit(code) {
+            if self.held[i] & bit != 0 {
+                self.held[i] &= !bit;
+                self.released[i] |= bit;
+            }
+        }
+    }
+
+    /// Release all the switches, such as when the canvas loses the focus and
+    /// the releases would be missed.
+    #[inline]
+    pub fn release_all(&mut self) {
+        for i in 0..N {
+            self.released[i] |= self.held[i];
+            self.held[i] = 0;
+        }
+    }
+
+    /// Return the state flags of a switch.
+    #[inline]
+    pub fn state(&self, code: u32) -> u32 {
+        let Some((i, bit)) = Self::bit(code) else {
+            return 0;
+        };
+        let flag = |bits: u64, flag: u32| if bits & bit != 0 { flag } else { 0 };
+        flag(self.held[i], HELD) | flag(self.pressed[i], PRESSED) | flag(self.released[i], RELEASED)
+    }
+
+    #[inline]
+    pub fn held(&self, code: u32) -> bool {
+        self.state(code) & HELD != 0
+    }
+
+    #[inline]
+    pub fn pressed(&self, code: u32) -> bool {
+        self.state(code) & PRESSED != 0
+    }
+
+    #[inline]
+    pub fn released(&self, code: u32) -> bool {
+        self.state(code) & RELEASED != 0
+    }
+
+    /// Forget the presses and the releases seen by the last step.
+    #[inline]
+    pub fn flush(&mut self) {
+        self.pressed = [0; N];
+        self.released = [0; N];
+    }
+}
+
+impl Default for Pointer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pointer {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            position: Vec2(0.0, 0.0),
+            delta: Vec2(0.0, 0.0),
+            wheel: 0.0,
+            buttons: Switches::new(),
+        }
+    }
+}
+
+impl Default for Input {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Input {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            keys: Switches::new(),
+            pointer: Pointer::new(),
+            touches: Vec::new(),
+            actions: Actions::new(),
+        }
+    }
+
+    #[inline]
+    pub fn key_down(&mut self, code: u32) {
+        self.keys.press(code);
+    }
+
+    #[inline]
+    pub fn key_up(&mut self, code: u32) {
+        self.keys.release(code);
+    }
+
+    /// Move the pointer to a position, by the given movement, which is
+    /// passed on its own as it keeps going while the pointer is locked.
+    #[inline]
+    pub fn pointer_move(&mut self, position: Vec2, movement: Vec2) {
+        self.pointer.position = position;
+        self.pointer.delta += movement;
+    }
+
+    #[inline]
+    pub fn pointer_button(&mut self, button: u32, down: bool) {
+        if down {
+            self.pointer.buttons.press(button);
+        } else {
+            self.pointer.buttons.release(button);
+        }
+    }
+
+    #[inline]
+    pub fn pointer_wheel(&mut self, delta: f32) {
+        self.pointer.wheel += delta;
+    }
+
+    /// Start a touch, restarting it if the id is still in use.
+    pub fn touch_start(&mut self, id: u32, position: Vec2) {
+        self.touches.retain(|t| t.id != id);
+        self.touches.push(Touch {
+            id,
+            position,
+            delta: Vec2(0.0, 0.0),
+            began: true,
+            ended: false,
+        });
+    }
+
+    pub fn touch_move(&mut self, id: u32, position: Vec2) {
+        if let Some(touch) = self.touches.iter_mut().find(|t| t.id == id) {
+            touch.delta += position - touch.position;
+            touch.position = position;
+        }
+    }
+
+    pub fn touch_end(&mut self, id: u32) {
+        if let Some(touch) = self.touches.iter_mut().find(|t| t.id == id) {
+            touch.ended = true;
+        }
+    }
+
+    /// Return the touch of the given id, if it is on the screen.
+    #[inline]
+    pub fn touch(&self, id: u32) -> Option<&Touch> {
+        self.touches.iter().find(|t| t.id == id)
+    }
+
+    /// Return the state flags of a binding.
+    #[inline]
+    pub fn binding_state(&self, binding: Binding) -> u32 {
+        match binding {
+            Binding::Key(code) => self.keys.state(code),
+            Binding::Button(button) => self.pointer.buttons.state(button),
+        }
+    }
+
+    /// Return the state flags of an action, combined from all of its
+    /// bindings. The action is only released once none of them is held.
+    pub fn action_state(&self, action: usize) -> u32 {
+        let Some(action) = self.actions.get(action) else {
+            return 0;
+        };
+        let state = action
+            .bindings
+            .iter()
+            .fold(0, |state, &b| state | self.binding_state(b));
+        if state & HELD != 0 {
+            state & !RELEASED
+        } else {
+            state
+        }
+    }
+
+    #[inline]
+    pub fn action_held(&self, action: usize) -> bool {
+        self.action_state(action) & HELD != 0
+    }
+
+    #[inline]
+    pub fn action_pressed(&self, action: usize) -> bool {
+        self.action_state(action) & PRESSED != 0
+    }
+
+    #[inline]
+    pub fn action_released(&self, action: usize) -> bool {
+        self.action_state(action) & RELEASED != 0
+    }
+
+    /// Return -1, 0 or 1 depending on which of two keys is held, such as
+    /// for moving back and forth.
+    #[inline]
+    pub fn axis(&self, negative: u32, positive: u32) -> f32 {
+        let value = |code| if self.keys.held(code) { 1.0 } else { 0.0 };
+        value(positive) - value(negative)
+    }
+
+    /// Release everything that is held, such as when the canvas loses the
+    /// focus and the releases would be missed.
+    pub fn release_all(&mut self) {
+        self.keys.release_all();
+        self.pointer.buttons.release_all();
+        for touch in &mut self.touches {
+            touch.ended = true;
+        }
+    }
+
+    /// Forget the events seen by the last step: the presses and releases,
+    /// the movement and the ended touches.
+    pub fn flush(&mut self) {
+        self.keys.flush();
+        self.pointer.buttons.flush();
+        self.pointer.delta = Vec2(0.0, 0.0);
+        self.pointer.wheel = 0.0;
+        self.touches.retain(|t| !t.ended);
+        for touch in &mut self.touches {
+            touch.delta = Vec2(0.0, 0.0);
+            touch.began = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::Vec2;
+    use crate::input::{key, Binding, Input, Switches, HELD, PRESSED, RELEASED};
+
+    #[test]
+    fn switches() {
+        let mut s = Switches::<1>::new();
+        s.press(3);
+        assert_eq!(HELD | PRESSED, s.state(3));
+        s.flush();
+        assert_eq!(HELD, s.state(3));
+
+        // Repeats are not presses
+        s.press(3);
+        assert_eq!(HELD, s.state(3));
+        s.release(3);
+        assert_eq!(RELEASED, s.state(3));
+        s.flush();
+        assert_eq!(0, s.state(3));
+
+        // A tap between steps is seen as both
+        s.press(63);
+        s.release(63);
+        assert!(s.pressed(63) && s.released(63) && !s.held(63));
+
+        // Codes out of the set are ignored
+        s.press(64);
+        assert_eq!(0, s.state(64));
+
+        s.press(1);
+        s.flush();
+        s.release_all();
+        assert_eq!(RELEASED, s.state(1));
+    }
+
+    #[test]
+    fn pointer() {
+        let mut input = Input::new();
+        input.pointer_move(Vec2(10.0, 10.0), Vec2(2.0, 0.0));
+        input.pointer_move(Vec2(12.0, 11.0), Vec2(2.0, 1.0));
+        input.pointer_wheel(100.0);
+        input.pointer_button(0, true);
+        assert_eq!(Vec2(12.0, 11.0), input.pointer.position);
+        assert_eq!(Vec2(4.0, 1.0), input.pointer.delta);
+        assert_eq!(100.0, input.pointer.wheel);
+        assert!(input.pointer.buttons.pressed(0));
+
+        input.flush();
+        assert_eq!(Vec2(12.0, 11.0), input.pointer.position);
+        assert_eq!(Vec2(0.0, 0.0), input.pointer.delta);
+        assert_eq!(0.0, input.pointer.wheel);
+        assert_eq!(HELD, input.pointer.buttons.state(0));
+    }
+
+    #[test]
+    fn touches() {
+        let mut input = Input::new();
+        input.touch_start(7, Vec2(1.0, 1.0));
+        input.touch_move(7, Vec2(3.0, 1.0));
+        input.touch_move(8, Vec2(3.0, 1.0));
+        let touch = input.touch(7).unwrap();
+        assert!(touch.began);
+        assert_eq!(Vec2(2.0, 0.0), touch.delta);
+        assert!(input.touch(8).is_none());
+
+        input.flush();
+        assert!(!input.touch(7).unwrap().began);
+
+        // An ended touch lasts until the next step
+        input.touch_end(7);
+        assert!(input.touch(7).unwrap().ended);
+        input.flush();
+        assert!(input.touches.is_empty());
+    }
+
+    #[test]
+    fn actions() {
+        let mut input = Input::new();
+        let jump = input.actions.add("jump");
+        input.actions.bind(jump, Binding::Key(key::SPACE));
+        input.actions.bind(jump, Binding::Button(0));
+        assert_eq!(Some(jump), input.actions.find("jump"));
+
+        input.key_down(key::SPACE);
+        assert_eq!(HELD | PRESSED, input.action_state(jump));
+        input.flush();
+
+        // Released only once no binding is held
+        input.pointer_button(0, true);
+        input.key_up(key::SPACE);
+        assert!(input.action_held(jump));
+        assert!(!input.action_released(jump));
+        input.flush();
+        input.pointer_button(0, false);
+        assert!(input.action_released(jump));
+        assert_eq!(0, input.action_state(jump + 1));
+
+        input.key_down(key::W);
+        assert_eq!(1.0, input.axis(key::S, key::W));
+        input.key_down(key::S);
+        assert_eq!(0.0, input.axis(key::S, key::W));
+    }
+}
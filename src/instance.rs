@@ -0,0 +1,149 @@
+use crate::core::{Aabb, Color, Mat4};
+use crate::region::{Region, View};
+use std::mem;
+
+/// Instance is a copy of a mesh drawn with a model matrix, and a color the
+/// vertex colors are multiplied by if the batch is colored.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq)]
+pub struct Instance {
+    pub model: Mat4,
+    pub color: Color,
+}
+
+/// Batch is a range of the indices of the engine mesh, drawn once per
+/// instance with `drawElementsInstanced`. Instances are kept in a region
+/// shared with the host, so that they are uploaded all at once.
+pub struct Batch {
+    /// First index of the range.
+    pub first: usize,
+    /// Number of indices of the range.
+    pub count: usize,
+    /// Bounding box of the vertices of the range, which instances out of
+    /// the view are culled by.
+    pub bounds: Aabb,
+    /// Whether the host binds the color of the instances, rather than
+    /// drawing them with the vertex colors only.
+    pub colored: bool,
+    instances: Region<Instance>,
+}
+
+impl Instance {
+    #[inline]
+    pub const fn new(model: Mat4, color: Color) -> Self {
+        Self { model, color }
+    }
+
+    /// Return the size of an instance in bytes, which is the stride of the
+    /// instance attributes. The color follows the 4 columns of the matrix.
+    #[inline]
+    pub const fn stride() -> usize {
+        mem::size_of::<Self>()
+    }
+}
+
+impl Batch {
+    /// Return a batch of an index range with the bounding box of its
+    /// vertices, with the memory for the given number of instances.
+    #[inline]
+    pub fn new(first: usize, count: usize, bounds: Aabb, capacity: usize) -> Self {
+        Self {
+            first,
+            count,
+            bounds,
+            colored: false,
+            instances: Region::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    #[inline]
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    #[inline]
+    pub fn instances_mut(&mut self) -> &mut [Instance] {
+        &mut self.instances
+    }
+
+    /// Add an instance and return its index.
+    #[inline]
+    pub fn push(&mut self, model: Mat4, color: Color) -> usize {
+        self.instances.push(Instance::new(model, color));
+        self.instances.len() - 1
+    }
+
+    /// Remove an instance, moving the last one into its place. Return false
+    /// if it is out of bounds.
+    pub fn remove(&mut self, instance: usize) -> bool {
+        let len = self.instances.len();
+        if instance >= len {
+            return false;
+        }
+        self.instances.swap(instance, len - 1);
+        self.instances.truncate(len - 1);
+        true
+    }
+
+    /// Keep the first instances and remove the rest, keeping the memory.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.instances.truncate(len);
+    }
+
+    /// Remove all the instances, keeping the memory.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    /// Return the view of the instances for the host.
+    #[inline]
+    pub fn view(&self) -> View {
+        self.instances.view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Color, Mat4, Vec3};
+    use crate::instance::{Batch, Instance};
+
+    #[test]
+    fn batch() {
+        let mut batch = Batch::new(6, 36, Aabb::empty(), 4);
+        let view = batch.view();
+        assert_eq!(4 * 68, view.capacity);
+        assert_eq!(68, Instance::stride());
+
+        let red = Color(255, 0, 0, 255);
+        for x in 0..3 {
+            batch.push(Mat4::translation(Vec3(x as f32, 0.0, 0.0)), red);
+        }
+        assert_eq!(view.ptr, batch.view().ptr);
+        assert_eq!(3 * 68, batch.view().len);
+
+        // The last instance takes the place of the removed one
+        assert!(batch.remove(0));
+        assert!(!batch.remove(2));
+        assert_eq!(2, batch.len());
+        assert_eq!(
+            Mat4::translation(Vec3(2.0, 0.0, 0.0)),
+            batch.instances()[0].model
+        );
+        batch.instances_mut()[1].color = Color(0, 0, 255, 255);
+        assert!(batch.instances()[1].color == Color(0, 0, 255, 255));
+        batch.clear();
+        assert!(batch.is_empty());
+    }
+}
@@ -0,0 +1,1118 @@
+use crate::anim::{Channel, Clip, Interpolation, Joint, Path, Skeleton};
+use crate::core::{Color, Mat4, Quat, Transform, Vec2, Vec3, Vec4};
+use crate::loader::json::Value;
+use crate::loader::{Error, Result};
+use crate::mesh;
+use crate::scene::Scene;
+use crate::state::Vertex;
+use crate::vertex::{Component, Format, Packing, Semantic, VertexBuffer};
+
+/// Primitive topology of triangle lists, the default one.
+pub const TRIANGLES: u32 = 4;
+
+const BYTE: u32 = 5120;
+const UNSIGNED_BYTE: u32 = 5121;
+const SHORT: u32 = 5122;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+/// Gltf is a parsed glTF 2.0 asset, which is a JSON description of the
+/// scene and binary buffers it refers to. The buffers are provided by the
+/// caller in the order of the `buffers` array, as the module has no access
+/// to the files or network on its own.
+pub struct Gltf<'a> {
+    json: Value,
+    buffers: Vec<&'a [u8]>,
+}
+
+/// Accessor is a typed view into a buffer, such as an array of positions.
+pub struct Accessor<'a> {
+    data: &'a [u8],
+    stride: usize,
+    count: usize,
+    component_type: u32,
+    components: usize,
+    normalized: bool,
+}
+
+/// Mesh is a set of primitives, usually one per material.
+pub struct Mesh {
+    pub name: String,
+    pub primitives: Vec<Primitive>,
+}
+
+/// Primitive is a piece of geometry with its vertex attributes unpacked
+/// into the engine types. Attributes missing from the asset are empty.
+pub struct Primitive {
+    pub mode: u32,
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec4>,
+    pub texcoords: Vec<Vec2>,
+    pub colors: Vec<Vec4>,
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<Vec4>,
+    pub indices: Option<Vec<u32>>,
+}
+
+impl<'a> Gltf<'a> {
+    /// Parse the JSON part of an asset and attach the binary buffers to it.
+    pub fn parse(json: &[u8], buffers: &[&'a [u8]]) -> Result<Self> {
+        let json = Value::parse(json)?;
+        let version = json
+            .get("asset")
+            .get("version")
+            .as_str()
+            .ok_or(Error::Missing("asset.version"))?;
+        if !version.starts_with("2.") {
+            return Err(Error::Unsupported("asset.version"));
+        }
+        for (i, buffer) in json.get("buffers").elements().iter().enumerate() {
+            let len = buffer
+                .get("byteLength")
+                .as_usize()
+                .ok_or(Error::Missing("buffer.byteLength"))?;
+            if buffers.get(i).is_none_or(|b| b.len() < len) {
+                return Err(Error::OutOfBounds("buffer"));
+            }
+        }
+        Ok(Self {
+            json,
+            buffers: buffers.to_vec(),
+        })
+    }
+
+    /// Return the JSON description of the asset.
+    #[inline]
+    pub fn json(&self) -> &Value {
+        &self.json
+    }
+
+    /// Return an accessor by its index, resolving the buffer view and
+    /// checking that all of its elements are within the buffer.
+    pub fn accessor(&self, index: usize) -> Result<Accessor<'a>> {
+        let a = self.json.get("accessors").at(index);
+        if a.is_null() {
+            return Err(Error::OutOfBounds("accessor"));
+        }
+        if !a.get("sparse").is_null() {
+            return Err(Error::Unsupported("sparse accessor"));
+        }
+        let count = a
+            .get("count")
+            .as_usize()
+            .ok_or(Error::Missing("accessor.count"))?;
+        let component_type = a
+            .get("componentType")
+            .as_usize()
+            .ok_or(Error::Missing("accessor.componentType"))? as u32;
+        let component_size = match component_type {
+            BYTE | UNSIGNED_BYTE => 1,
+            SHORT | UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT => 4,
+            _ => return Err(Error::Invalid("accessor.componentType")),
+        };
+        let components = match a.get("type").as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") | Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            Some(_) => return Err(Error::Invalid("accessor.type")),
+            None => return Err(Error::Missing("accessor.type")),
+        };
+        // Columns of small matrices are padded to 4 bytes, which would
+        // require a stride per column.
+        if component_size < 4 && matches!(a.get("type").as_str(), Some("MAT2" | "MAT3")) {
+            return Err(Error::Unsupported("padded matrix accessor"));
+        }
+        let normalized = a.get("normalized").as_bool().unwrap_or(false);
+        let element_size = components * component_size;
+
+        let view = self.json.get("bufferViews").at(a
+            .get("bufferView")
+            .as_usize()
+            .ok_or(Error::Unsupported("accessor without bufferView"))?);
+        let buffer = view
+            .get("buffer")
+            .as_usize()
+            .and_then(|i| self.buffers.get(i))
+            .ok_or(Error::OutOfBounds("bufferView.buffer"))?;
+        let view_offset = view.get("byteOffset").as_usize().unwrap_or(0);
+        let view_len = view
+            .get("byteLength")
+            .as_usize()
+            .ok_or(Error::Missing("bufferView.byteLength"))?;
+        let stride = view.get("byteStride").as_usize().unwrap_or(element_size);
+        if stride < element_size {
+            return Err(Error::Invalid("bufferView.byteStride"));
+        }
+        let view = view_offset
+            .checked_add(view_len)
+            .and_then(|end| buffer.get(view_offset..end))
+            .ok_or(Error::OutOfBounds("bufferView"))?;
+
+        // Sizes are checked for overflow, as they come from the asset, so
+        // that the number of elements is bounded by the buffer length.
+        let offset = a.get("byteOffset").as_usize().unwrap_or(0);
+        let len = match count {
+            0 => Some(0),
+            _ => stride
+                .checked_mul(count - 1)
+                .and_then(|len| len.checked_add(element_size)),
+        };
+        let data = len
+            .and_then(|len| offset.checked_add(len))
+            .and_then(|end| view.get(offset..end))
+            .ok_or(Error::OutOfBounds("accessor"))?;
+
+        Ok(Accessor {
+            data,
+            stride,
+            count,
+            component_type,
+            components,
+            normalized,
+        })
+    }
+
+    /// Return all meshes of the asset.
+    pub fn meshes(&self) -> Result<Vec<Mesh>> {
+        (0..self.json.get("meshes").elements().len())
+            .map(|i| self.mesh(i))
+            .collect()
+    }
+
+    /// Return a mesh by its index, with the attributes read from buffers.
+    pub fn mesh(&self, index: usize) -> Result<Mesh> {
+        let m = self.json.get("meshes").at(index);
+        if m.is_null() {
+            return Err(Error::OutOfBounds("mesh"));
+        }
+        let primitives = m
+            .get("primitives")
+            .elements()
+            .iter()
+            .map(|p| self.primitive(p))
+            .collect::<Result<_>>()?;
+        Ok(Mesh {
+            name: String::from(m.get("name").as_str().unwrap_or_default()),
+            primitives,
+        })
+    }
+
+    fn primitive(&self, p: &Value) -> Result<Primitive> {
+        let attributes = p.get("attributes");
+        let position = attributes
+            .get("POSITION")
+            .as_usize()
+            .ok_or(Error::Missing("POSITION attribute"))?;
+        let positions = self.attribute(position, 3)?;
+        let count = positions.len() / 3;
+        let optional = |name: &str, components: usize| match attributes.get(name).as_usize() {
+            Some(i) => self.attribute(i, components).and_then(|v| {
+                if v.len() == count * components {
+                    Ok(v)
+                } else {
+                    Err(Error::Invalid("attribute count"))
+                }
+            }),
+            None => Ok(Vec::new()),
+        };
+
+        let normals = optional("NORMAL", 3)?;
+        let tangents = optional("TANGENT", 4)?;
+        let texcoords = optional("TEXCOORD_0", 2)?;
+        // Colors are either RGB or RGBA, the alpha is opaque in the former
+        let colors = match attributes.get("COLOR_0").as_usize() {
+            Some(i) => match self.accessor(i)?.components {
+                3 => optional("COLOR_0", 3)?
+                    .chunks_exact(3)
+                    .map(|c| Vec4(c[0], c[1], c[2], 1.0))
+                    .collect(),
+                _ => optional("COLOR_0", 4)?
+                    .chunks_exact(4)
+                    .map(|c| Vec4(c[0], c[1], c[2], c[3]))
+                    .collect(),
+            },
+            None => Vec::new(),
+        };
+        let joints = match attributes.get("JOINTS_0").as_usize() {
+            Some(i) => {
+                let joints = self.accessor(i)?.read_u32()?;
+                if joints.len() != count * 4 {
+                    return Err(Error::Invalid("attribute count"));
+                }
+                if joints.iter().any(|&j| j > u16::MAX as u32) {
+                    return Err(Error::OutOfBounds("joint"));
+                }
+                joints
+                    .chunks_exact(4)
+                    .map(|c| [c[0] as u16, c[1] as u16, c[2] as u16, c[3] as u16])
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let weights = optional("WEIGHTS_0", 4)?;
+
+        let indices = match p.get("indices").as_usize() {
+            Some(i) => {
+                let indices = self.accessor(i)?.read_u32()?;
+                if indices.iter().any(|&i| i as usize >= count) {
+                    return Err(Error::OutOfBounds("index"));
+                }
+                Some(indices)
+            }
+            None => None,
+        };
+
+        Ok(Primitive {
+            mode: p.get("mode").as_usize().unwrap_or(TRIANGLES as usize) as u32,
+            positions: positions
+                .chunks_exact(3)
+                .map(|c| Vec3(c[0], c[1], c[2]))
+                .collect(),
+            normals: normals
+                .chunks_exact(3)
+                .map(|c| Vec3(c[0], c[1], c[2]))
+                .collect(),
+            tangents: tangents
+                .chunks_exact(4)
+                .map(|c| Vec4(c[0], c[1], c[2], c[3]))
+                .collect(),
+            texcoords: texcoords
+                .chunks_exact(2)
+                .map(|c| Vec2(c[0], c[1]))
+                .collect(),
+            colors,
+            joints,
+            weights: weights
+                .chunks_exact(4)
+                .map(|c| Vec4(c[0], c[1], c[2], c[3]))
+                .collect(),
+            indices,
+        })
+    }
+
+    /// Read an attribute accessor, checking its number of components.
+    fn attribute(&self, index: usize, components: usize) -> Result<Vec<f32>> {
+        let accessor = self.accessor(index)?;
+        if accessor.components != components {
+            return Err(Error::Invalid("accessor.type"));
+        }
+        Ok(accessor.read_f32())
+    }
+
+    /// Return the node hierarchy of a scene, or of the default one, which is
+    /// the first scene if there is no default, or all the nodes if there are
+    /// no scenes at all. Nodes are in the order of the hierarchy, parents
+    /// first.
+    pub fn scene(&self, index: Option<usize>) -> Result<Scene> {
+        let nodes = self.json.get("nodes").elements();
+        let parents = self.parents()?;
+        let scenes = self.json.get("scenes");
+        let index = index.or(self.json.get("scene").as_usize());
+        let roots: Vec<usize> = if scenes.elements().is_empty() && index.is_none() {
+            (0..nodes.len()).filter(|&n| parents[n].is_none()).collect()
+        } else {
+            let s = scenes.at(index.unwrap_or(0));
+            if s.is_null() {
+                return Err(Error::OutOfBounds("scene"));
+            }
+            s.get("nodes")
+                .elements()
+                .iter()
+                .map(|n| match n.as_usize() {
+                    Some(n) if n >= nodes.len() => Err(Error::OutOfBounds("scene.nodes")),
+                    Some(n) if parents[n].is_some() => Err(Error::Invalid("scene.nodes")),
+                    Some(n) => Ok(n),
+                    None => Err(Error::Missing("scene.nodes")),
+                })
+                .collect::<Result<_>>()?
+        };
+
+        // Every node has a single parent and the roots have none, so the
+        // hierarchy below them is a tree.
+        let mut scene = Scene::new();
+        let mut stack: Vec<(usize, Option<usize>)> =
+            roots.iter().rev().map(|&r| (r, None)).collect();
+        while let Some((n, parent)) = stack.pop() {
+            let node = &nodes[n];
+            let name = node.get("name").as_str().unwrap_or_default();
+            let index = scene
+                .add(name, self.local_transform(node)?, parent)
+                .ok_or(Error::Invalid("node.children"))?;
+            scene.node_mut(index).unwrap().mesh = node.get("mesh").as_usize();
+            for child in node.get("children").elements().iter().rev() {
+                stack.push((child.as_usize().unwrap_or_default(), Some(index)));
+            }
+        }
+        Ok(scene)
+    }
+
+    /// Return the skeleton of a skin. Nodes above the root joints are folded
+    /// into the skeleton root matrix.
+    pub fn skeleton(&self, skin: usize) -> Result<Skeleton> {
+        let s = self.json.get("skins").at(skin);
+        if s.is_null() {
+            return Err(Error::OutOfBounds("skin"));
+        }
+        let nodes = self.skin_joints(s)?;
+        let parents = self.parents()?;
+
+        let inverse_binds = match s.get("inverseBindMatrices").as_usize() {
+            Some(i) => {
+                let m = self.attribute(i, 16)?;
+                if m.len() != nodes.len() * 16 {
+                    return Err(Error::Invalid("skin.inverseBindMatrices"));
+                }
+                m.chunks_exact(16).map(mat4).collect()
+            }
+            None => vec![Mat4::identity(); nodes.len()],
+        };
+
+        let mut root = None;
+        let mut joints = Vec::with_capacity(nodes.len());
+        for (&node, inverse_bind) in nodes.iter().zip(inverse_binds) {
+            let n = self.json.get("nodes").at(node);
+            let parent = parents[node].and_then(|p| nodes.iter().position(|&j| j == p));
+            if parent.is_none() && root.is_none() {
+                root = Some(self.global_transform(parents[node], &parents)?);
+            }
+            joints.push(Joint {
+                name: String::from(n.get("name").as_str().unwrap_or_default()),
+                parent,
+                rest: self.local_transform(n)?,
+                inverse_bind,
+            });
+        }
+
+        Skeleton::new(joints, root.unwrap_or(Mat4::identity())).ok_or(Error::Invalid("skin.joints"))
+    }
+
+    /// Return the animations of a skin. Channels of nodes which are not its
+    /// joints are skipped, as well as the weights of morph targets.
+    pub fn clips(&self, skin: usize) -> Result<Vec<Clip>> {
+        let s = self.json.get("skins").at(skin);
+        if s.is_null() {
+            return Err(Error::OutOfBounds("skin"));
+        }
+        let nodes = self.skin_joints(s)?;
+        self.json
+            .get("animations")
+            .elements()
+            .iter()
+            .map(|a| {
+                let mut channels = Vec::new();
+                for c in a.get("channels").elements() {
+                    let target = c.get("target");
+                    let Some(joint) = target
+                        .get("node")
+                        .as_usize()
+                        .and_then(|n| nodes.iter().position(|&j| j == n))
+                    else {
+                        continue;
+                    };
+                    let (path, components) = match target.get("path").as_str() {
+                        Some("translation") => (Path::Translation, 3),
+                        Some("rotation") => (Path::Rotation, 4),
+                        Some("scale") => (Path::Scale, 3),
+                        Some(_) => continue,
+                        None => return Err(Error::Missing("channel.target.path")),
+                    };
+
+                    let sampler = c
+                        .get("sampler")
+                        .as_usize()
+                        .map(|i| a.get("samplers").at(i))
+                        .filter(|s| !s.is_null())
+                        .ok_or(Error::OutOfBounds("channel.sampler"))?;
+                    let interpolation = match sampler.get("interpolation").as_str() {
+                        None | Some("LINEAR") => Interpolation::Linear,
+                        Some("STEP") => Interpolation::Step,
+                        Some("CUBICSPLINE") => Interpolation::CubicSpline,
+                        Some(_) => return Err(Error::Invalid("sampler.interpolation")),
+                    };
+                    let input = sampler
+                        .get("input")
+                        .as_usize()
+                        .ok_or(Error::Missing("sampler.input"))?;
+                    let output = sampler
+                        .get("output")
+                        .as_usize()
+                        .ok_or(Error::Missing("sampler.output"))?;
+
+                    let times = self.attribute(input, 1)?;
+                    if times.windows(2).any(|t| t[0] > t[1]) {
+                        return Err(Error::Invalid("sampler.input"));
+                    }
+                    let values: Vec<Vec4> = self
+                        .attribute(output, components)?
+                        .chunks_exact(components)
+                        .map(|c| match path {
+                            Path::Rotation => Vec4(c[0], c[1], c[2], c[3]),
+                            _ => Vec4(c[0], c[1], c[2], 0.0),
+                        })
+                        .collect();
+                    let keys = match interpolation {
+                        Interpolation::CubicSpline => values.len() / 3,
+                        _ => values.len(),
+                    };
+                    if keys != times.len() {
+                        return Err(Error::Invalid("sampler.output"));
+                    }
+
+                    channels.push(Channel {
+                        joint,
+                        path,
+                        interpolation,
+                        times,
+                        values,
+                    });
+                }
+                Ok(Clip::new(
+                    String::from(a.get("name").as_str().unwrap_or_default()),
+                    channels,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return node indices of the skin joints.
+    fn skin_joints(&self, skin: &Value) -> Result<Vec<usize>> {
+        let count = self.json.get("nodes").elements().len();
+        skin.get("joints")
+            .elements()
+            .iter()
+            .map(|j| match j.as_usize() {
+                Some(j) if j < count => Ok(j),
+                Some(_) => Err(Error::OutOfBounds("skin.joints")),
+                None => Err(Error::Missing("skin.joints")),
+            })
+            .collect()
+    }
+
+    /// Return the parent of each node, checking that no node has two of them.
+    fn parents(&self) -> Result<Vec<Option<usize>>> {
+        let nodes = self.json.get("nodes").elements();
+        let mut parents = vec![None; nodes.len()];
+        for (i, n) in nodes.iter().enumerate() {
+            for child in n.get("children").elements() {
+                match child.as_usize().and_then(|c| parents.get_mut(c)) {
+                    Some(p @ None) => *p = Some(i),
+                    Some(Some(_)) => return Err(Error::Invalid("node.children")),
+                    None => return Err(Error::OutOfBounds("node.children")),
+                }
+            }
+        }
+        Ok(parents)
+    }
+
+    /// Return the transformation of a node relative to its parent.
+    fn local_transform(&self, node: &Value) -> Result<Transform> {
+        let floats = |key: &'static str, len: usize| -> Result<Option<Vec<f32>>> {
+            let v = node.get(key);
+            if v.is_null() {
+                return Ok(None);
+            }
+            let f: Option<Vec<f32>> = v.elements().iter().map(Value::as_f32).collect();
+            match f {
+                Some(f) if f.len() == len => Ok(Some(f)),
+                _ => Err(Error::Invalid(key)),
+            }
+        };
+
+        if let Some(m) = floats("matrix", 16)? {
+            return Ok(Transform::from(mat4(&m)));
+        }
+        let mut t = Transform::identity();
+        if let Some(v) = floats("translation", 3)? {
+            t.translation = Vec3(v[0], v[1], v[2]);
+        }
+        if let Some(v) = floats("rotation", 4)? {
+            t.rotation = Quat(v[0], v[1], v[2], v[3]);
+        }
+        if let Some(v) = floats("scale", 3)? {
+            t.scale = Vec3(v[0], v[1], v[2]);
+        }
+        Ok(t)
+    }
+
+    /// Return the transformation of a node relative to the scene, following
+    /// its parents up to a root node.
+    fn global_transform(&self, mut node: Option<usize>, parents: &[Option<usize>]) -> Result<Mat4> {
+        let mut m = Mat4::identity();
+        for _ in 0..=parents.len() {
+            let Some(n) = node else {
+                return Ok(m);
+            };
+            m = m * self
+                .local_transform(self.json.get("nodes").at(n))?
+                .to_mat4();
+            node = parents[n];
+        }
+        Err(Error::Invalid("node.children"))
+    }
+}
+
+/// Return a matrix of 16 column-major floats, the layout glTF and GL share.
+#[inline]
+fn mat4(m: &[f32]) -> Mat4 {
+    Mat4(
+        Vec4(m[0], m[1], m[2], m[3]),
+        Vec4(m[4], m[5], m[6], m[7]),
+        Vec4(m[8], m[9], m[10], m[11]),
+        Vec4(m[12], m[13], m[14], m[15]),
+    )
+}
+
+impl Accessor<'_> {
+    /// Number of elements.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Number of components per element, such as 3 for VEC3.
+    #[inline]
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Return all components of all elements as floats. Normalized integers
+    /// are mapped to the [0, 1] or [-1, 1] range, others are converted as is.
+    pub fn read_f32(&self) -> Vec<f32> {
+        let mut v = Vec::with_capacity(self.count * self.components);
+        for element in self.elements() {
+            for i in 0..self.components {
+                v.push(match (self.component_type, self.normalized) {
+                    (BYTE, true) => (element[i] as i8 as f32 / 127.0).max(-1.0),
+                    (BYTE, false) => element[i] as i8 as f32,
+                    (UNSIGNED_BYTE, true) => element[i] as f32 / 255.0,
+                    (UNSIGNED_BYTE, false) => element[i] as f32,
+                    (SHORT, true) => (le_u16(element, i) as i16 as f32 / 32767.0).max(-1.0),
+                    (SHORT, false) => le_u16(element, i) as i16 as f32,
+                    (UNSIGNED_SHORT, true) => le_u16(element, i) as f32 / 65535.0,
+                    (UNSIGNED_SHORT, false) => le_u16(element, i) as f32,
+                    (UNSIGNED_INT, _) => le_u32(element, i) as f32,
+                    _ => f32::from_bits(le_u32(element, i)),
+                });
+            }
+        }
+        v
+    }
+
+    /// Return all components of all elements as unsigned integers, which is
+    /// how indices and joints are stored.
+    pub fn read_u32(&self) -> Result<Vec<u32>> {
+        let mut v = Vec::with_capacity(self.count * self.components);
+        for element in self.elements() {
+            for i in 0..self.components {
+                v.push(match self.component_type {
+                    UNSIGNED_BYTE => element[i] as u32,
+                    UNSIGNED_SHORT => le_u16(element, i) as u32,
+                    UNSIGNED_INT => le_u32(element, i),
+                    _ => return Err(Error::Invalid("accessor.componentType")),
+                });
+            }
+        }
+        Ok(v)
+    }
+
+    /// Iterate over the bytes of each element, skipping the stride padding.
+    #[inline]
+    fn elements(&self) -> impl Iterator<Item = &[u8]> {
+        (0..self.count).map(move |i| &self.data[i * self.stride..])
+    }
+}
+
+#[inline]
+fn le_u16(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([bytes[2 * i], bytes[2 * i + 1]])
+}
+
+#[inline]
+fn le_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[4 * i],
+        bytes[4 * i + 1],
+        bytes[4 * i + 2],
+        bytes[4 * i + 3],
+    ])
+}
+
+impl Primitive {
+    /// Return the engine vertex at an index. Vertices without a color are
+    /// white.
+    fn vertex(&self, i: usize) -> Vertex {
+        let color = match self.colors.get(i) {
+            Some(&c) => Color::from(c * 255.0_f32 + Vec4(0.5, 0.5, 0.5, 0.5)),
+            None => Color(255, 255, 255, 255),
+        };
+        Vertex(self.positions[i], color)
+    }
+
+    /// Return engine vertices of the primitive, unrolled by the indices if
+    /// there are any, so that they can be drawn without an index buffer.
+    pub fn vertices(&self) -> Vec<Vertex> {
+        match &self.indices {
+            Some(indices) => indices.iter().map(|&i| self.vertex(i as usize)).collect(),
+            None => (0..self.positions.len()).map(|i| self.vertex(i)).collect(),
+        }
+    }
+
+    /// Return the format of the attributes the primitive has, with floats
+    /// for all but the colors and the joints.
+    pub fn format(&self) -> Format {
+        let mut format = Format::default();
+        let attributes = [
+            (
+                !self.positions.is_empty(),
+                Semantic::Position,
+                Component::F32,
+                3,
+            ),
+            (
+                !self.normals.is_empty(),
+                Semantic::Normal,
+                Component::F32,
+                3,
+            ),
+            (
+                !self.tangents.is_empty(),
+                Semantic::Tangent,
+                Component::F32,
+                4,
+            ),
+            (
+                !self.texcoords.is_empty(),
+                Semantic::Texcoord,
+                Component::F32,
+                2,
+            ),
+            (!self.colors.is_empty(), Semantic::Color, Component::U8, 4),
+            (!self.joints.is_empty(), Semantic::Joints, Component::U16, 4),
+            (
+                !self.weights.is_empty(),
+                Semantic::Weights,
+                Component::F32,
+                4,
+            ),
+        ];
+        for (present, semantic, component, count) in attributes {
+            if present {
+                format = format.with(semantic, component, count, semantic == Semantic::Color);
+            }
+        }
+        format
+    }
+
+    /// Return the values of an attribute, with the components of a vertex
+    /// after each other.
+    pub fn attribute(&self, semantic: Semantic) -> Vec<f32> {
+        match semantic {
+            Semantic::Position => self
+                .positions
+                .iter()
+                .flat_map(|v| [v.0, v.1, v.2])
+                .collect(),
+            Semantic::Normal => self.normals.iter().flat_map(|v| [v.0, v.1, v.2]).collect(),
+            Semantic::Tangent => self
+                .tangents
+                .iter()
+                .flat_map(|v| [v.0, v.1, v.2, v.3])
+                .collect(),
+            Semantic::Texcoord => self.texcoords.iter().flat_map(|v| [v.0, v.1]).collect(),
+            Semantic::Color => self
+                .colors
+                .iter()
+                .flat_map(|v| [v.0, v.1, v.2, v.3])
+                .collect(),
+            Semantic::Joints => self.joints.iter().flatten().map(|&j| j as f32).collect(),
+            Semantic::Weights => self
+                .weights
+                .iter()
+                .flat_map(|v| [v.0, v.1, v.2, v.3])
+                .collect(),
+        }
+    }
+
+    /// Return the engine mesh of the primitive, indexed in order if it has
+    /// no indices of its own.
+    pub fn mesh(&self) -> Result<mesh::Mesh> {
+        let mut mesh = mesh::Mesh::new();
+        let vertices = (0..self.positions.len()).map(|i| self.vertex(i));
+        let appended = match &self.indices {
+            Some(indices) => mesh.append(vertices, indices.iter().copied()),
+            None => {
+                u32::try_from(self.positions.len()).is_ok_and(|len| mesh.append(vertices, 0..len))
+            }
+        };
+        if !appended {
+            return Err(Error::OutOfBounds("index"));
+        }
+        Ok(mesh)
+    }
+}
+
+/// Return a buffer of the vertices of primitives after each other, in the
+/// order of their meshes, with all the attributes any of them has. The
+/// attributes a primitive doesn't have are zeroed.
+pub fn vertex_buffer(primitives: &[&Primitive], packing: Packing) -> VertexBuffer {
+    let mut format = Format::default();
+    for a in primitives.iter().flat_map(|p| p.format().0) {
+        if format.attribute(a.semantic).is_none() {
+            format.0.push(a);
+        }
+    }
+    format.0.sort_by_key(|a| a.semantic as u32);
+
+    let len = primitives.iter().map(|p| p.positions.len()).sum();
+    let mut buffer = VertexBuffer::new(format, len, packing);
+    let mut first = 0;
+    for p in primitives {
+        for a in p.format().0 {
+            buffer.write(a.semantic, first, &p.attribute(a.semantic));
+        }
+        first += p.positions.len();
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Color, Mat4, Vec2, Vec3};
+    use crate::loader::gltf::{Gltf, TRIANGLES};
+    use crate::loader::Error;
+    use crate::vertex::{Packing, Semantic};
+
+    const CUBE: (&[u8], &[u8]) = (
+        include_bytes!("../../public/assets/models/cube.gltf"),
+        include_bytes!("../../public/assets/models/cube.bin"),
+    );
+    const WIZARD: (&[u8], &[u8]) = (
+        include_bytes!("../../public/assets/models/wizard.gltf"),
+        include_bytes!("../../public/assets/models/wizard.bin"),
+    );
+    const TRIS: (&[u8], &[u8]) = (
+        include_bytes!("../../public/assets/models/tris.gltf"),
+        include_bytes!("../../public/assets/models/tris.bin"),
+    );
+
+    #[test]
+    fn cube() {
+        let gltf = Gltf::parse(CUBE.0, &[CUBE.1]).unwrap();
+        let meshes = gltf.meshes().unwrap();
+        assert_eq!(1, meshes.len());
+        assert_eq!("cube", meshes[0].name);
+
+        let p = &meshes[0].primitives[0];
+        assert_eq!(TRIANGLES, p.mode);
+        assert_eq!(24, p.positions.len());
+        assert_eq!(24, p.normals.len());
+        assert_eq!(24, p.tangents.len());
+        assert_eq!(24, p.texcoords.len());
+        assert!(p.colors.is_empty());
+        assert_eq!(36, p.indices.as_ref().unwrap().len());
+
+        // Positions are within the accessor bounds, and normals are unit
+        for &v in &p.positions {
+            assert!(v.0 >= -0.5 && v.0 <= 0.5 && v.1 >= -0.5 && v.1 <= 0.5);
+            assert!(v.2 >= 0.0 && v.2 <= 1.0);
+        }
+        for &n in &p.normals {
+            assert_eq!(1.0, n.len());
+        }
+        for &t in &p.texcoords {
+            assert!(t.0 >= 0.0 && t.0 <= 1.0 && t.1 >= 0.0 && t.1 <= 1.0);
+        }
+        for &t in &p.tangents {
+            assert!(t.3 == 1.0 || t.3 == -1.0);
+        }
+
+        let v = p.vertices();
+        assert_eq!(36, v.len());
+        assert_eq!(Color(255, 255, 255, 255), v[0].1);
+    }
+
+    #[test]
+    fn normalized_colors() {
+        let gltf = Gltf::parse(TRIS.0, &[TRIS.1]).unwrap();
+        let p = &gltf.mesh(0).unwrap().primitives[0];
+        assert_eq!(9, p.positions.len());
+        assert_eq!(9, p.colors.len());
+
+        // Unsigned shorts are normalized to [0, 1]
+        let c = gltf.accessor(0).unwrap();
+        let raw = u16::from_le_bytes([TRIS.1[0], TRIS.1[1]]);
+        assert_eq!(raw as f32 / 65535.0, c.read_f32()[0]);
+        for &c in &p.colors {
+            assert!(c.0 >= 0.0 && c.0 <= 1.0 && c.3 >= 0.0 && c.3 <= 1.0);
+        }
+        assert_eq!(9, p.vertices().len());
+    }
+
+    #[test]
+    fn bundled_models() {
+        let models: [(&[u8], &[u8], usize, usize); 6] = [
+            (
+                include_bytes!("../../public/assets/models/crate.gltf"),
+                include_bytes!("../../public/assets/models/crate.bin"),
+                224,
+                324,
+            ),
+            (
+                include_bytes!("../../public/assets/models/house.gltf"),
+                include_bytes!("../../public/assets/models/house.bin"),
+                648,
+                987,
+            ),
+            (
+                include_bytes!("../../public/assets/models/prism.gltf"),
+                include_bytes!("../../public/assets/models/prism.bin"),
+                9,
+                9,
+            ),
+            (
+                include_bytes!("../../public/assets/models/suzanne.gltf"),
+                include_bytes!("../../public/assets/models/suzanne.bin"),
+                11808,
+                11808,
+            ),
+            (
+                include_bytes!("../../public/assets/models/wizard.gltf"),
+                include_bytes!("../../public/assets/models/wizard.bin"),
+                0,
+                0,
+            ),
+            (CUBE.0, CUBE.1, 24, 36),
+        ];
+        for (json, bin, vertices, indices) in models {
+            let gltf = Gltf::parse(json, &[bin]).unwrap();
+            for mesh in gltf.meshes().unwrap() {
+                for p in mesh.primitives {
+                    if vertices > 0 {
+                        assert_eq!(vertices, p.positions.len());
+                        assert_eq!(indices, p.indices.as_ref().unwrap().len());
+                    }
+                    assert_eq!(p.positions.len(), p.normals.len());
+                    assert_eq!(p.indices.as_ref().unwrap().len(), p.vertices().len());
+
+                    // The mesh draws the same triangles as the unrolled vertices
+                    let m = p.mesh().unwrap();
+                    assert_eq!(p.positions.len(), m.vertices.len());
+                    assert!(m
+                        .indices
+                        .iter()
+                        .map(|i| m.vertices[i as usize])
+                        .eq(p.vertices()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn interleaved() {
+        // Two vertices of a position and a texture coordinate each, stored
+        // interleaved with a 4 bytes gap, followed by u8 indices
+        let json = br#"{
+            "asset": { "version": "2.0" },
+            "meshes": [{ "primitives": [{
+                "attributes": { "POSITION": 0, "TEXCOORD_0": 1 },
+                "indices": 2
+            }] }],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3" },
+                { "bufferView": 0, "byteOffset": 12, "componentType": 5121,
+                  "normalized": true, "count": 2, "type": "VEC2" },
+                { "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" }
+            ],
+            "bufferViews": [
+                { "buffer": 0, "byteLength": 36, "byteStride": 18 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 3 }
+            ],
+            "buffers": [{ "byteLength": 39 }]
+        }"#;
+        let mut bin = Vec::new();
+        for (p, t) in [
+            ([1.0_f32, 2.0, 3.0], [255_u8, 0]),
+            ([4.0, 5.0, 6.0], [0, 51]),
+        ] {
+            p.iter()
+                .for_each(|f| bin.extend_from_slice(&f.to_le_bytes()));
+            bin.extend_from_slice(&t);
+            bin.extend_from_slice(&[0; 4]);
+        }
+        bin.extend_from_slice(&[1, 0, 1]);
+
+        let gltf = Gltf::parse(json, &[&bin]).unwrap();
+        let p = &gltf.mesh(0).unwrap().primitives[0];
+        assert_eq!(vec![Vec3(1.0, 2.0, 3.0), Vec3(4.0, 5.0, 6.0)], p.positions);
+        assert_eq!(vec![Vec2(1.0, 0.0), Vec2(0.0, 0.2)], p.texcoords);
+        assert_eq!(Some(vec![1, 0, 1]), p.indices);
+        assert!(p.normals.is_empty() && p.tangents.is_empty());
+        assert_eq!(Vec3(4.0, 5.0, 6.0), p.vertices()[0].0);
+    }
+
+    #[test]
+    fn vertex_buffer() {
+        let cube = Gltf::parse(CUBE.0, &[CUBE.1]).unwrap();
+        let wizard = Gltf::parse(WIZARD.0, &[WIZARD.1]).unwrap();
+        let c = &cube.mesh(0).unwrap().primitives[0];
+        let w = &wizard.mesh(0).unwrap().primitives[0];
+        assert!(c.format().attribute(Semantic::Joints).is_none());
+
+        let buffer = super::vertex_buffer(&[c, w], Packing::Interleaved);
+        assert_eq!(c.positions.len() + w.positions.len(), buffer.len());
+        let format = buffer.format();
+        assert_eq!(Semantic::Position, format.0[0].semantic);
+        assert!(format
+            .0
+            .windows(2)
+            .all(|a| (a[0].semantic as u32) < (a[1].semantic as u32)));
+
+        // Joints of the wizard follow the zeroed ones of the cube
+        let joints = format.attribute(Semantic::Joints).unwrap();
+        let joint = |i: usize| {
+            let at = (joints.offset + i as u32 * joints.stride) as usize;
+            u16::from_le_bytes([buffer.data()[at], buffer.data()[at + 1]])
+        };
+        assert_eq!(0, joint(0));
+        assert_eq!(w.joints[5][0], joint(c.positions.len() + 5));
+    }
+
+    #[test]
+    fn scene() {
+        let gltf = Gltf::parse(WIZARD.0, &[WIZARD.1]).unwrap();
+        let mut scene = gltf.scene(None).unwrap();
+        assert_eq!(32, scene.len());
+        assert_eq!([0], scene.roots());
+        assert_eq!("Armature", scene.nodes()[0].name);
+        let wizard = scene.find("wizard").unwrap();
+        assert_eq!(Some(0), scene.nodes()[wizard].mesh);
+        assert_eq!(Some(0), scene.nodes()[wizard].parent());
+
+        // World matrices match the ones the skeleton is built from
+        scene.update();
+        let skeleton = gltf.skeleton(0).unwrap();
+        let root = scene.find(&skeleton.joints[0].name).unwrap();
+        assert_eq!(
+            skeleton.joints[0].rest.to_mat4() * skeleton.root,
+            *scene.nodes()[root].world()
+        );
+        assert_eq!(
+            Err(Error::OutOfBounds("scene")),
+            gltf.scene(Some(1)).map(|_| ())
+        );
+
+        // Without scenes, all the nodes without a parent are roots
+        let json = br#"{
+            "asset": {"version": "2.0"},
+            "nodes": [{"children": [2]}, {"translation": [1, 2, 3]}, {}]
+        }"#;
+        let mut scene = Gltf::parse(json, &[]).unwrap().scene(None).unwrap();
+        assert_eq!([0, 2], scene.roots());
+        assert_eq!(Some(0), scene.nodes()[1].parent());
+        assert_eq!(3, scene.update());
+        assert_eq!(
+            Mat4::translation(Vec3(1.0, 2.0, 3.0)),
+            *scene.nodes()[2].world()
+        );
+    }
+
+    #[test]
+    fn skin() {
+        let gltf = Gltf::parse(WIZARD.0, &[WIZARD.1]).unwrap();
+        let p = &gltf.mesh(0).unwrap().primitives[0];
+        assert_eq!(p.positions.len(), p.joints.len());
+        assert_eq!(p.positions.len(), p.weights.len());
+        for (j, w) in p.joints.iter().zip(&p.weights) {
+            assert!(j.iter().all(|&j| j < 30));
+            assert!((w.0 + w.1 + w.2 + w.3 - 1.0).abs() < 0.01);
+        }
+
+        let skeleton = gltf.skeleton(0).unwrap();
+        assert_eq!(30, skeleton.joints.len());
+        assert_eq!("origin", skeleton.joints[0].name);
+        assert_eq!(None, skeleton.joints[0].parent);
+        assert_eq!(Some(0), skeleton.joints[1].parent);
+        assert_eq!(Mat4::identity(), skeleton.root);
+
+        // Inverse bind matrices undo the rest pose
+        let mut palette = Vec::new();
+        skeleton.palette(&skeleton.rest_pose(), &mut palette);
+        for m in palette {
+            assert_eq!(Mat4::identity(), m);
+        }
+
+        let clips = gltf.clips(0).unwrap();
+        assert_eq!(2, clips.len());
+        assert_eq!("0idle", clips[0].name);
+        assert_eq!("1walk", clips[1].name);
+        for clip in &clips {
+            assert_eq!(1.0, clip.duration);
+            assert_eq!(90, clip.channels.len());
+            assert!(clip.channels.iter().all(|c| c.times.len() == 61));
+        }
+        assert_eq!(Some(Error::OutOfBounds("skin")), gltf.skeleton(1).err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Some(Error::OutOfBounds("buffer")),
+            Gltf::parse(CUBE.0, &[]).err()
+        );
+        assert_eq!(
+            Some(Error::OutOfBounds("buffer")),
+            Gltf::parse(CUBE.0, &[&CUBE.1[..100]]).err()
+        );
+        assert_eq!(
+            Some(Error::Missing("asset.version")),
+            Gltf::parse(b"{}", &[]).err()
+        );
+        assert_eq!(
+            Some(Error::Unsupported("asset.version")),
+            Gltf::parse(br#"{ "asset": { "version": "1.0" } }"#, &[]).err()
+        );
+        assert_eq!(Some(Error::Json(0)), Gltf::parse(b"", &[]).err());
+
+        let gltf = Gltf::parse(CUBE.0, &[CUBE.1]).unwrap();
+        assert_eq!(Some(Error::OutOfBounds("mesh")), gltf.mesh(1).err());
+        assert_eq!(Some(Error::OutOfBounds("accessor")), gltf.accessor(5).err());
+
+        // Accessor that is larger than its buffer view
+        let json = br#"{
+            "asset": { "version": "2.0" },
+            "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }],
+            "bufferViews": [{ "buffer": 0, "byteLength": 24 }],
+            "buffers": [{ "byteLength": 24 }]
+        }"#;
+        let gltf = Gltf::parse(json, &[&[0; 24]]).unwrap();
+        assert_eq!(Some(Error::OutOfBounds("accessor")), gltf.accessor(0).err());
+
+        // Sizes that overflow are out of bounds rather than wrapping around
+        let json = br#"{
+            "asset": { "version": "2.0" },
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 4611686018427387904, "type": "VEC4" },
+                { "bufferView": 0, "byteOffset": 18446744073709549568, "componentType": 5126, "count": 1, "type": "VEC3" },
+                { "bufferView": 1, "componentType": 5126, "count": 1, "type": "SCALAR" }
+            ],
+            "bufferViews": [
+                { "buffer": 0, "byteLength": 24 },
+                { "buffer": 0, "byteOffset": 18446744073709549568, "byteLength": 4096 }
+            ],
+            "buffers": [{ "byteLength": 24 }]
+        }"#;
+        let gltf = Gltf::parse(json, &[&[0; 24]]).unwrap();
+        assert_eq!(Some(Error::OutOfBounds("accessor")), gltf.accessor(0).err());
+        assert_eq!(Some(Error::OutOfBounds("accessor")), gltf.accessor(1).err());
+        assert_eq!(
+            Some(Error::OutOfBounds("bufferView")),
+            gltf.accessor(2).err()
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use crate::loader::{Error, Result};
+
+/// Value is a parsed JSON document. It is a minimal implementation that
+/// is just enough to read asset descriptions, such as glTF, without
+/// pulling in a serialization framework into the WASM module.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Object members in the order of the document.
+    Object(Vec<(String, Value)>),
+}
+
+/// Shared null, returned when a member or an element is missing, so that
+/// lookups can be chained without checking each step.
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Parse a JSON document from its UTF-8 bytes.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut parser = Parser { bytes, pos: 0 };
+        let value = parser.value(0)?;
+        parser.whitespace();
+        if parser.pos < bytes.len() {
+            return Err(parser.error());
+        }
+        Ok(value)
+    }
+
+    /// Return a member of an object, or null if there is no such member or
+    /// the value is not an object.
+    #[inline]
+    pub fn get(&self, key: &str) -> &Value {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or(&NULL, |(_, v)| v),
+            _ => &NULL,
+        }
+    }
+
+    /// Return an element of an array, or null if it is out of bounds or the
+    /// value is not an array.
+    #[inline]
+    pub fn at(&self, index: usize) -> &Value {
+        match self {
+            Value::Array(elements) => elements.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|n| n as f32)
+    }
+
+    /// Return the number if it is a non-negative integer, which is what
+    /// indices, counts and offsets are.
+    #[inline]
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 && *n <= usize::MAX as f64 => {
+                Some(*n as usize)
+            }
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return elements of an array, or an empty slice if the value is not
+    /// an array, which is how optional arrays are treated.
+    #[inline]
+    pub fn elements(&self) -> &[Value] {
+        match self {
+            Value::Array(elements) => elements,
+            _ => &[],
+        }
+    }
+
+    /// Return members of an object, or an empty slice if the value is not
+    /// an object.
+    #[inline]
+    pub fn members(&self) -> &[(String, Value)] {
+        match self {
+            Value::Object(members) => members,
+            _ => &[],
+        }
+    }
+}
+
+/// Nesting limit, which keeps malicious documents from overflowing the
+/// stack, as the parser is recursive.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    #[inline]
+    fn error(&self) -> Error {
+        Error::Json(self.pos)
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    #[inline]
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    #[inline]
+    fn expect(&mut self, literal: &[u8]) -> Result<()> {
+        if self.bytes[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect(b"null").map(|_| Value::Null),
+            Some(b't') => self.expect(b"true").map(|_| Value::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => self.array(depth),
+            Some(b'{') => self.object(depth),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value> {
+        self.pos += 1;
+        let mut elements = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(elements));
+        }
+        loop {
+            elements.push(self.value(depth + 1)?);
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(elements));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error());
+            }
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(b":")?;
+            members.push((key, self.value(depth + 1)?));
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        // Leading zeros are not allowed, so 0 is either alone or followed
+        // by a fraction or an exponent.
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(self.error()),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error());
+            }
+            self.digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error());
+            }
+            self.digits();
+        }
+        // The slice only contains ASCII digits and signs at this point
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Value::Number)
+            .ok_or(Error::Json(start))
+    }
+
+    #[inline]
+    fn digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(s).map_err(|_| self.error());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode()?,
+                        _ => return Err(self.error()),
+                    };
+                    self.pos += 1;
+                    s.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(0x00..=0x1f) | None => return Err(self.error()),
+                Some(b) => {
+                    self.pos += 1;
+                    s.push(b);
+                }
+            }
+        }
+    }
+
+    /// Decode a `\uXXXX` escape, or a pair of them for characters outside
+    /// of the Basic Multilingual Plane, leaving the position at the last
+    /// hex digit.
+    fn unicode(&mut self) -> Result<char> {
+        let high = self.hex()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            self.pos += 1;
+            self.expect(b"\\u")?;
+            self.pos -= 1;
+            let low = self.hex()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error());
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or(self.error())
+    }
+
+    /// Read 4 hex digits following the current position.
+    fn hex(&mut self) -> Result<u32> {
+        let digits = self
+            .bytes
+            .get(self.pos + 1..self.pos + 5)
+            .ok_or(self.error())?;
+        let mut code = 0;
+        for &d in digits {
+            self.pos += 1;
+            code = code * 16 + (d as char).to_digit(16).ok_or(self.error())?;
+        }
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::loader::json::Value;
+    use crate::loader::Error;
+
+    #[test]
+    fn literals() {
+        assert_eq!(Ok(Value::Null), Value::parse(b"null"));
+        assert_eq!(Ok(Value::Bool(true)), Value::parse(b" true "));
+        assert_eq!(Ok(Value::Bool(false)), Value::parse(b"\nfalse"));
+        assert_eq!(Err(Error::Json(0)), Value::parse(b"nul"));
+        assert_eq!(Err(Error::Json(5)), Value::parse(b"true false"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Ok(Value::Number(0.0)), Value::parse(b"0"));
+        assert_eq!(Ok(Value::Number(-12.5)), Value::parse(b"-12.5"));
+        assert_eq!(Ok(Value::Number(2.5e-3)), Value::parse(b"2.5e-3"));
+        assert_eq!(Ok(Value::Number(1e10)), Value::parse(b"1E+10"));
+        assert!(Value::parse(b"01").is_err());
+        assert!(Value::parse(b"1.").is_err());
+        assert!(Value::parse(b"-").is_err());
+        assert!(Value::parse(b"+1").is_err());
+    }
+
+    #[test]
+    fn strings() {
+        let a = Value::parse(br#""a\"b\\c\/\n\u00e9\ud83d\ude00""#);
+        assert_eq!(
+            Ok(Value::String(String::from("a\"b\\c/\n\u{e9}\u{1f600}"))),
+            a
+        );
+        let a = Value::parse("\"Möller\"".as_bytes());
+        assert_eq!(Ok(Value::String(String::from("Möller"))), a);
+        assert!(Value::parse(b"\"abc").is_err());
+        assert!(Value::parse(b"\"\\x\"").is_err());
+        assert!(Value::parse(b"\"\\ud83d\"").is_err());
+        assert!(Value::parse(b"\"a\nb\"").is_err());
+    }
+
+    #[test]
+    fn arrays() {
+        let a = Value::parse(b"[1, [true], [], \"x\"]").unwrap();
+        assert_eq!(4, a.elements().len());
+        assert_eq!(Some(1.0), a.at(0).as_f64());
+        assert_eq!(Some(true), a.at(1).at(0).as_bool());
+        assert!(a.at(2).elements().is_empty());
+        assert_eq!(Some("x"), a.at(3).as_str());
+        assert!(a.at(4).is_null());
+        assert!(Value::parse(b"[1,]").is_err());
+        assert!(Value::parse(b"[1 2]").is_err());
+    }
+
+    #[test]
+    fn objects() {
+        let a =
+            Value::parse(br#"{ "count": 24, "min": [-0.5, 0], "name": "cube", "e": {} }"#).unwrap();
+        assert_eq!(Some(24), a.get("count").as_usize());
+        assert_eq!(Some(-0.5), a.get("min").at(0).as_f32());
+        assert_eq!(Some("cube"), a.get("name").as_str());
+        assert!(a.get("e").members().is_empty());
+        assert!(a.get("missing").get("deeper").at(3).is_null());
+        assert_eq!(None, a.get("min").at(0).as_usize());
+        assert!(Value::parse(b"{\"a\" 1}").is_err());
+        assert!(Value::parse(b"{1: 1}").is_err());
+        assert!(Value::parse(b"{\"a\": 1,}").is_err());
+    }
+
+    #[test]
+    fn depth() {
+        let a = [b'['; 200];
+        assert!(Value::parse(&a).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+pub mod gltf;
+pub mod json;
+
+use std::fmt::{Display, Formatter};
+
+/// Error of loading an asset. Assets are handed over FFI as plain bytes,
+/// so the errors point at what is wrong with the data rather than at IO.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Error {
+    /// Malformed JSON, at the byte offset.
+    Json(usize),
+    /// Required property is missing or has a wrong type.
+    Missing(&'static str),
+    /// Property has a value that is not allowed by the format.
+    Invalid(&'static str),
+    /// Data refers outside of a buffer, or to a buffer that is not provided.
+    OutOfBounds(&'static str),
+    /// Feature of the format that is valid, but is not supported.
+    Unsupported(&'static str),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(pos) => write!(f, "malformed JSON at byte {}", pos),
+            Error::Missing(what) => write!(f, "missing {}", what),
+            Error::Invalid(what) => write!(f, "invalid {}", what),
+            Error::OutOfBounds(what) => write!(f, "{} is out of bounds", what),
+            Error::Unsupported(what) => write!(f, "{} is not supported", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,351 @@
+use crate::core::Aabb;
+use crate::region::{Region, View};
+use crate::state::Vertex;
+use std::collections::HashMap;
+
+/// Format of the indices, which is the WebGL type to draw them with.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IndexFormat {
+    U16 = 0x1403,
+    U32 = 0x1405,
+}
+
+/// Indices is an index buffer, 16 bits wide as long as all the indices fit,
+/// which halves its size for most meshes.
+pub enum Indices {
+    U16(Region<u16>),
+    U32(Region<u32>),
+}
+
+/// Mesh is a vertex buffer with an index buffer of the triangles. A mesh
+/// without indices is drawn in the order of its vertices.
+pub struct Mesh {
+    pub vertices: Region<Vertex>,
+    pub indices: Indices,
+}
+
+impl Default for Indices {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Indices {
+    #[inline]
+    pub const fn new() -> Self {
+        Indices::U16(Region::new())
+    }
+
+    /// Return an index buffer with the memory for the given number of 16
+    /// bit indices.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Indices::U16(Region::with_capacity(capacity))
+    }
+
+    #[inline]
+    pub fn format(&self) -> IndexFormat {
+        match self {
+            Indices::U16(_) => IndexFormat::U16,
+            Indices::U32(_) => IndexFormat::U32,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(i) => i.len(),
+            Indices::U32(i) => i.len(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the index at a position.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<u32> {
+        match self {
+            Indices::U16(indices) => indices.get(i).map(|&i| i as u32),
+            Indices::U32(indices) => indices.get(i).copied(),
+        }
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+
+    /// Append an index, widening the buffer if it doesn't fit into 16 bits.
+    /// Widening moves the data, which is reported by the layout.
+    pub fn push(&mut self, index: u32) {
+        match self {
+            Indices::U16(indices) => match u16::try_from(index) {
+                Ok(index) => indices.push(index),
+                Err(_) => {
+                    let mut wide = Region::with_capacity(indices.capacity().max(indices.len() + 1));
+                    wide.extend(indices.iter().map(|&i| i as u32));
+                    wide.push(index);
+                    *self = Indices::U32(wide);
+                }
+            },
+            Indices::U32(indices) => indices.push(index),
+        }
+    }
+
+    /// Remove all the indices, keeping the memory and the format.
+    #[inline]
+    pub fn clear(&mut self) {
+        match self {
+            Indices::U16(i) => i.clear(),
+            Indices::U32(i) => i.clear(),
+        }
+    }
+
+    /// Return the view of the buffer for the host.
+    #[inline]
+    pub fn view(&self) -> View {
+        match self {
+            Indices::U16(i) => i.view(),
+            Indices::U32(i) => i.view(),
+        }
+    }
+}
+
+impl Extend<u32> for Indices {
+    #[inline]
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        for index in iter {
+            self.push(index);
+        }
+    }
+}
+
+impl Default for Mesh {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mesh {
+    /// Return an empty mesh without any memory allocated.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            vertices: Region::new(),
+            indices: Indices::new(),
+        }
+    }
+
+    /// Return an empty mesh with the memory for the given numbers of
+    /// vertices and 16 bit indices.
+    #[inline]
+    pub fn with_capacity(vertices: usize, indices: usize) -> Self {
+        Self {
+            vertices: Region::with_capacity(vertices),
+            indices: Indices::with_capacity(indices),
+        }
+    }
+
+    /// Remove all the vertices and indices, keeping the memory.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Append vertices with the indices of their own, which are offset past
+    /// the existing vertices. An unindexed mesh is indexed first, so that
+    /// both are drawn. Return false and keep the mesh intact if an index is
+    /// past the appended vertices or doesn't fit in 32 bits once offset.
+    pub fn append<V, I>(&mut self, vertices: V, indices: I) -> bool
+    where
+        V: IntoIterator<Item = Vertex>,
+        I: IntoIterator<Item = u32>,
+    {
+        let first = self.vertices.len();
+        self.vertices.extend(vertices);
+        let offset_indices = match (u32::try_from(first), u32::try_from(self.vertices.len())) {
+            (Ok(offset), Ok(len)) => indices
+                .into_iter()
+                .map(|i| i.checked_add(offset).filter(|&i| i < len))
+                .collect::<Option<Vec<u32>>>()
+                .map(|indices| (offset, indices)),
+            _ => None,
+        };
+        let Some((offset, indices)) = offset_indices else {
+            self.vertices.truncate(first);
+            return false;
+        };
+
+        if self.indices.is_empty() {
+            self.indices.extend(0..offset);
+        }
+        self.indices.extend(indices);
+        true
+    }
+
+    /// Return the bounding box of the vertices a range of the indices
+    /// points at. Indices past the vertices are skipped.
+    pub fn bounds(&self, first: usize, count: usize) -> Aabb {
+        (first..first.saturating_add(count))
+            .map_while(|i| self.indices.get(i))
+            .filter_map(|i| self.vertices.get(i as usize))
+            .fold(Aabb::empty(), |b, v| b.expand(v.0))
+    }
+
+    /// Merge identical vertices into one, pointing the indices at it, and
+    /// index an unindexed mesh. Vertices are compared bit by bit, except
+    /// for the sign of zero. Return false and keep the mesh intact if an
+    /// index is past the vertices.
+    pub fn weld(&mut self) -> bool {
+        if self
+            .indices
+            .iter()
+            .any(|i| i as usize >= self.vertices.len())
+        {
+            return false;
+        }
+
+        let key = |v: &Vertex| {
+            let p = v.0;
+            let c = v.1;
+            (
+                [
+                    (p.0 + 0.0).to_bits(),
+                    (p.1 + 0.0).to_bits(),
+                    (p.2 + 0.0).to_bits(),
+                ],
+                [c.0, c.1, c.2, c.3],
+            )
+        };
+
+        let mut unique = HashMap::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        let mut len = 0;
+        for i in 0..self.vertices.len() {
+            let vertex = self.vertices[i];
+            let index = *unique.entry(key(&vertex)).or_insert_with(|| {
+                self.vertices[len] = vertex;
+                len += 1;
+                len as u32 - 1
+            });
+            remap.push(index);
+        }
+        self.vertices.truncate(len);
+
+        if self.indices.is_empty() {
+            self.indices.extend(remap);
+        } else {
+            let indices: Vec<u32> = self.indices.iter().map(|i| remap[i as usize]).collect();
+            self.indices.clear();
+            self.indices.extend(indices);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Color, Vec3};
+    use crate::mesh::{IndexFormat, Indices, Mesh};
+    use crate::state::Vertex;
+
+    fn vertex(x: f32, y: f32) -> Vertex {
+        Vertex(Vec3(x, y, 0.0), Color(255, 255, 255, 255))
+    }
+
+    #[test]
+    fn indices() {
+        let mut indices = Indices::with_capacity(4);
+        indices.extend([0, 1, 65535]);
+        assert_eq!(IndexFormat::U16, indices.format());
+        assert_eq!(6, indices.view().len);
+
+        // An index past 16 bits widens the buffer
+        indices.push(65536);
+        assert_eq!(IndexFormat::U32, indices.format());
+        assert_eq!(vec![0, 1, 65535, 65536], indices.iter().collect::<Vec<_>>());
+        assert_eq!(16, indices.view().len);
+        assert_eq!(None, indices.get(4));
+
+        indices.clear();
+        assert!(indices.is_empty());
+        assert_eq!(IndexFormat::U32, indices.format());
+    }
+
+    #[test]
+    fn append() {
+        let mut mesh = Mesh::new();
+        mesh.vertices
+            .extend([vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)]);
+        assert!(mesh.append([vertex(1.0, 1.0), vertex(2.0, 1.0)], [1, 0, 0]));
+        assert_eq!(5, mesh.vertices.len());
+        assert_eq!(
+            vec![0, 1, 2, 4, 3, 3],
+            mesh.indices.iter().collect::<Vec<_>>()
+        );
+
+        // Indices past the appended vertices keep the mesh intact
+        assert!(!mesh.append([vertex(3.0, 1.0)], [0, 1]));
+        assert!(!mesh.append([vertex(3.0, 1.0)], [u32::MAX]));
+        assert_eq!(5, mesh.vertices.len());
+        assert_eq!(6, mesh.indices.len());
+
+        // Bounds are of the vertices the range points at
+        let b = Aabb(Vec3(1.0, 1.0, 0.0), Vec3(2.0, 1.0, 0.0));
+        assert_eq!(b, mesh.bounds(3, 3));
+        assert!(mesh.bounds(6, 1).is_empty());
+
+        mesh.clear();
+        assert!(mesh.vertices.is_empty());
+        assert!(mesh.indices.is_empty());
+    }
+
+    #[test]
+    fn weld() {
+        // A quad of two triangles, unrolled
+        let mut mesh = Mesh::new();
+        mesh.vertices.extend([
+            vertex(0.0, 0.0),
+            vertex(1.0, 0.0),
+            vertex(1.0, 1.0),
+            vertex(1.0, 1.0),
+            vertex(0.0, 1.0),
+            vertex(-0.0, 0.0),
+        ]);
+        assert!(mesh.weld());
+        assert_eq!(4, mesh.vertices.len());
+        assert_eq!(
+            vec![0, 1, 2, 2, 3, 0],
+            mesh.indices.iter().collect::<Vec<_>>()
+        );
+        assert!(mesh.vertices[3] == vertex(0.0, 1.0));
+
+        // Indexed meshes keep their triangles, and colors tell vertices apart
+        assert!(mesh.append(
+            [
+                vertex(0.0, 0.0),
+                Vertex(Vec3(1.0, 0.0, 0.0), Color(0, 0, 0, 255)),
+            ],
+            [0, 1, 1],
+        ));
+        assert!(mesh.weld());
+        assert_eq!(5, mesh.vertices.len());
+        assert_eq!(
+            vec![0, 1, 2, 2, 3, 0, 0, 4, 4],
+            mesh.indices.iter().collect::<Vec<_>>()
+        );
+
+        // Indices past the vertices keep the mesh intact
+        mesh.indices.push(5);
+        assert!(!mesh.weld());
+        assert_eq!(5, mesh.vertices.len());
+        assert_eq!(10, mesh.indices.len());
+    }
+}
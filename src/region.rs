@@ -0,0 +1,246 @@
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// Size of a wasm memory page in bytes.
+pub const PAGE_SIZE: usize = 65536;
+
+/// View is a range of the module memory as the host sees it, in bytes. The
+/// address is kept as an integer, as it is only meaningful to the host.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct View {
+    pub ptr: usize,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+/// Region is an array with a capacity allocated up front, so that its data
+/// doesn't move while it fits, and the host can keep a view into it across
+/// frames. Growing past the capacity is allowed, but moves the data, which
+/// is reported by the layout.
+pub struct Region<T>(Vec<T>);
+
+/// Layout keeps track of the views of the regions shared with the host, and
+/// of the memory size. Any change of them bumps the generation, which the
+/// host polls to know when its views have to be recreated: either because a
+/// region has moved or resized, or because the memory has grown, which
+/// detaches all the existing JS buffers.
+pub struct Layout {
+    generation: u32,
+    memory: usize,
+    views: Vec<View>,
+}
+
+impl<T> Deref for Region<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Region<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+impl<T> Default for Region<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Region<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl View {
+    /// Return a view of a slice, with the capacity given in elements.
+    #[inline]
+    pub fn of<T>(slice: &[T], capacity: usize) -> Self {
+        Self {
+            ptr: slice.as_ptr() as usize,
+            len: mem::size_of_val(slice),
+            capacity: capacity * mem::size_of::<T>(),
+        }
+    }
+}
+
+impl<T> Region<T> {
+    /// Return a region without any memory allocated.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Return a region with the memory for the given number of elements.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// Return the number of elements the region holds without moving.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        self.0.push(value)
+    }
+
+    /// Keep the first elements and remove the rest, keeping the memory.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+
+    /// Remove all the elements, keeping the memory.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Replace the elements, keeping the memory if they fit.
+    #[inline]
+    pub fn assign<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.clear();
+        self.0.extend(iter);
+    }
+
+    /// Return the view of the region for the host.
+    #[inline]
+    pub fn view(&self) -> View {
+        View::of(&self.0, self.0.capacity())
+    }
+}
+
+impl Default for Layout {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            generation: 0,
+            memory: 0,
+            views: Vec::new(),
+        }
+    }
+
+    /// Return the number of times the layout has changed.
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Return the views as of the last update.
+    #[inline]
+    pub fn views(&self) -> &[View] {
+        &self.views
+    }
+
+    /// Compare the views and the memory size in bytes with the last ones,
+    /// and bump the generation if any of them has changed. Return whether
+    /// it has been bumped.
+    pub fn update<I>(&mut self, memory: usize, views: I) -> bool
+    where
+        I: IntoIterator<Item = View>,
+        I::IntoIter: Clone,
+    {
+        let views = views.into_iter();
+        if memory == self.memory && views.clone().eq(self.views.iter().copied()) {
+            return false;
+        }
+        self.memory = memory;
+        self.views.clear();
+        self.views.extend(views);
+        self.generation = self.generation.wrapping_add(1);
+        true
+    }
+}
+
+/// Return the size of the module memory in bytes, which is zero outside of
+/// wasm, where the memory never detaches.
+#[inline]
+pub fn memory_size() -> usize {
+    #[cfg(target_arch = "wasm32")]
+    {
+        core::arch::wasm32::memory_size::<0>() * PAGE_SIZE
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::region::{Layout, Region, View, PAGE_SIZE};
+
+    #[test]
+    fn region() {
+        let mut r = Region::with_capacity(4);
+        let view = r.view();
+        assert_eq!(0, view.len);
+        assert_eq!(16, view.capacity);
+
+        // Data doesn't move while it fits into the capacity
+        r.extend([1_u32, 2, 3]);
+        assert_eq!(view.ptr, r.view().ptr);
+        assert_eq!(12, r.view().len);
+        r.assign([4, 5, 6, 7]);
+        assert_eq!(view.ptr, r.view().ptr);
+        assert_eq!([4, 5, 6, 7], *r);
+        r.clear();
+        assert_eq!(4, r.capacity());
+    }
+
+    #[test]
+    fn generation() {
+        let mut layout = Layout::new();
+        let mut r = Region::with_capacity(2);
+        assert!(layout.update(PAGE_SIZE, [r.view()]));
+        assert_eq!(1, layout.generation());
+        assert!(!layout.update(PAGE_SIZE, [r.view()]));
+        assert_eq!(1, layout.generation());
+
+        // A length change is reported, even if the data stays in place
+        r.push(1_u64);
+        assert!(layout.update(PAGE_SIZE, [r.view()]));
+        assert_eq!(2, layout.generation());
+        assert_eq!(8, layout.views()[0].len);
+
+        // Growing past the capacity may move the data
+        r.extend(0..1024);
+        assert!(r.capacity() >= 1025);
+        assert!(layout.update(PAGE_SIZE, [r.view()]));
+        assert_eq!(3, layout.generation());
+
+        // Memory growth detaches the host buffers, while the regions stay
+        assert!(layout.update(2 * PAGE_SIZE, [r.view()]));
+        assert_eq!(4, layout.generation());
+        assert_eq!(r.view(), layout.views()[0]);
+    }
+
+    #[test]
+    fn view() {
+        let a = [1.0_f32, 2.0, 3.0];
+        let v = View::of(&a[..2], 3);
+        assert_eq!(a.as_ptr() as usize, v.ptr);
+        assert_eq!(8, v.len);
+        assert_eq!(12, v.capacity);
+    }
+}
@@ -6,11 +6,26 @@ export async function loadModule(src: string): Promise<Module> {
 export class Module {
   private memory: WebAssembly.Memory;
   private ffi: { [key: string]: Function };
+  private engine: number;
+  private actions = new Map<string, number>();
 
+  /**
+   * Creates an engine of its own, so that several modules can run side by
+   * side on the same wasm instance, such as an editor preview and the game.
+   */
   constructor (wasm: WebAssembly.Instance) {
     const { memory, ...ffi } = wasm.exports;
     this.memory = memory as WebAssembly.Memory;
     this.ffi = ffi as { [key: string]: Function };
+    this.engine = this.ffi.engine_create();
+  }
+
+  /**
+   * Releases the engine, the module must not be used afterwards.
+   */
+  destroy() {
+    this.ffi.engine_destroy(this.engine)
+    this.engine = 0
   }
 
   /**
@@ -43,30 +58,701 @@ export class Module {
   }
 
   initState() {
-    this.ffi.gen()
+    this.ffi.gen(this.engine)
   }
 
-  update() {
-    this.ffi.update()
+  /**
+   * Loads a glTF asset, given the JSON and the binary buffers it refers to,
+   * replacing the state vertices with its meshes.
+   *
+   * @returns false if the asset is invalid or there is no memory for it
+   */
+  loadGltf(json: ArrayBuffer, buffers: ArrayBuffer[]): boolean {
+    for (const data of [json, ...buffers]) {
+      const ptr = this.ffi.upload_buffer(this.engine, data.byteLength)
+      if (ptr === 0) {
+        return false
+      }
+      new Uint8Array(this.memory.buffer, ptr, data.byteLength).set(new Uint8Array(data))
+    }
+    return Boolean(this.ffi.load_gltf(this.engine))
+  }
+
+  /**
+   * Starts playing an animation clip of the loaded asset from the beginning.
+   *
+   * @returns false if there is no such clip
+   */
+  playAnimation(clip: number): boolean {
+    return Boolean(this.ffi.play_animation(this.engine, clip))
+  }
+
+  /**
+   * Moves the animation on top of the simulation, such as while paused.
+   */
+  advanceAnimation(ms: number) {
+    this.ffi.advance_animation(this.engine, ms)
+  }
+
+  /**
+   * Sets a parameter of the animation graph.
+   *
+   * @returns false if no animated asset is loaded or the index is too large
+   */
+  setAnimationParameter(index: number, value: number): boolean {
+    return Boolean(this.ffi.set_animation_parameter(this.engine, index, value))
+  }
+
+  /**
+   * Adds an animation layer with a state per clip on top of the others,
+   * limited to a joint and its descendants if one is given.
+   *
+   * @returns index of the layer, or -1 if no animated asset is loaded
+   */
+  addAnimationLayer(additive: boolean, maskJoint: number = -1): number {
+    return this.ffi.add_animation_layer(this.engine, additive, maskJoint)
+  }
+
+  setAnimationLayerWeight(layer: number, weight: number): boolean {
+    return Boolean(this.ffi.set_animation_layer_weight(this.engine, layer, weight))
+  }
+
+  /**
+   * Crossfades a layer to the state of a clip over the given milliseconds.
+   */
+  crossfadeAnimation(layer: number, clip: number, ms: number): boolean {
+    return Boolean(this.ffi.crossfade_animation(this.engine, layer, clip, ms))
+  }
+
+  /**
+   * Adds a transition of a layer, taken once the parameter is within
+   * `[min, max)`, from a state or from any one if `from` is -1.
+   */
+  addAnimationTransition(layer: number, from: number, to: number, parameter: number, min: number, max: number, ms: number): boolean {
+    return Boolean(this.ffi.add_animation_transition(this.engine, layer, from, to, parameter, min, max, ms))
+  }
+
+  /**
+   * Joint matrices of the current animation pose, 16 floats each.
+   */
+  get palette(): Float32Array {
+    return new Float32Array(this.memory.buffer, this.ffi.state_palette(this.engine), this.ffi.state_palette_len(this.engine) * 16)
+  }
+
+  /**
+   * Advances the world by the time of a frame, simulating it in fixed steps.
+   */
+  update(ms: number) {
+    this.ffi.update(this.engine, ms)
+  }
+
+  set tickRate(rate: number) {
+    this.ffi.set_tick_rate(this.engine, rate)
+  }
+
+  set maxSteps(steps: number) {
+    this.ffi.set_max_steps(this.engine, steps)
+  }
+
+  /**
+   * Fraction of a simulation step the frame is past the previous one, to
+   * interpolate the last two simulation states with.
+   */
+  get interpolationAlpha(): number {
+    return this.ffi.interpolation_alpha(this.engine)
   }
 
   set screen({ width, height }: {
     width: number,
     height: number,
   }) {
-    this.ffi.set_screen_width(width)
-    this.ffi.set_screen_height(height)
+    this.ffi.set_screen_width(this.engine, width)
+    this.ffi.set_screen_height(this.engine, height)
   }
 
   get viewMatrix(): Float32Array {
-    return new Float32Array(this.memory.buffer, this.ffi.state_view_matrix(), 16)
+    return new Float32Array(this.memory.buffer, this.ffi.state_view_matrix(this.engine), 16)
+  }
+
+  /**
+   * Casts a ray from the camera through a point of the canvas, returning
+   * its origin followed by the direction.
+   */
+  rayFromScreen(x: number, y: number): Float32Array {
+    return new Float32Array(this.memory.buffer, this.ffi.ray_from_screen(this.engine, x, y), 6)
+  }
+
+  /**
+   * Changes whenever the views into the module memory have to be recreated,
+   * either because the data has moved or resized, or because the memory has
+   * grown and detached the existing buffers. Supposed to be polled each frame.
+   */
+  get layoutGeneration(): number {
+    return this.ffi.layout_generation(this.engine)
+  }
+
+  /**
+   * Byte ranges of the vertices, the joint palette, the view matrix, the
+   * indices, the vertex buffer, the sprite vertices and indices, and the
+   * instances of every batch, as of the last `layoutGeneration` poll.
+   */
+  get layoutViews(): { ptr: number, len: number, capacity: number }[] {
+    const views = new Uint32Array(this.memory.buffer, this.ffi.layout_views(this.engine), this.ffi.layout_views_len(this.engine) * 3)
+    const result = []
+    for (let i = 0; i < views.length; i += 3) {
+      result.push({ ptr: views[i], len: views[i + 1], capacity: views[i + 2] })
+    }
+    return result
   }
 
   get verticesData(): ArrayBuffer {
-    return new Uint8Array(this.memory.buffer, this.ffi.state_data(), this.ffi.state_len())
+    return new Uint8Array(this.memory.buffer, this.ffi.state_data(this.engine), this.ffi.state_len(this.engine))
+  }
+
+  /**
+   * Indices of the mesh triangles, 16 or 32 bits wide depending on the
+   * number of vertices, see `indexFormat`.
+   */
+  get indicesData(): Uint16Array | Uint32Array {
+    const ptr = this.ffi.state_indices(this.engine)
+    const len = this.ffi.state_indices_len(this.engine)
+    return this.indexFormat === WebGLRenderingContext.UNSIGNED_INT
+      ? new Uint32Array(this.memory.buffer, ptr, len)
+      : new Uint16Array(this.memory.buffer, ptr, len)
+  }
+
+  /**
+   * Whether the vertex buffer is laid out in a block per attribute rather
+   * than interleaved, from the next mesh loaded on.
+   */
+  set vertexPacking(packing: 'interleaved' | 'deinterleaved') {
+    this.ffi.set_vertex_packing(this.engine, packing === 'deinterleaved')
+  }
+
+  /**
+   * Attributes of the vertex buffer, as `vertexAttribPointer` takes them.
+   * The semantic is the position, normal, tangent, texcoord, color, joints
+   * or weights, from 0 to 6.
+   */
+  get vertexFormat(): {
+    semantic: number,
+    type: number,
+    size: number,
+    offset: number,
+    stride: number,
+    normalized: boolean,
+  }[] {
+    const view = new DataView(this.memory.buffer, this.ffi.vertex_format(this.engine))
+    const result = []
+    for (let i = 0; i < this.ffi.vertex_format_len(this.engine); i++) {
+      const at = i * 24
+      result.push({
+        semantic: view.getUint32(at, true),
+        type: view.getUint32(at + 4, true),
+        size: view.getUint32(at + 8, true),
+        offset: view.getUint32(at + 12, true),
+        stride: view.getUint32(at + 16, true),
+        normalized: view.getUint8(at + 20) !== 0,
+      })
+    }
+    return result
+  }
+
+  get vertexBufferData(): Uint8Array {
+    return new Uint8Array(this.memory.buffer, this.ffi.vertex_buffer(this.engine), this.ffi.vertex_buffer_len(this.engine))
+  }
+
+  /**
+   * Points the attributes of the vertex buffer bound to `ARRAY_BUFFER` to
+   * the shader locations of their semantics, skipping those without one.
+   */
+  pointVertexAttributes(gl: WebGLRenderingContext, locations: { [semantic: number]: number }) {
+    for (const a of this.vertexFormat) {
+      const location = locations[a.semantic]
+      if (location === undefined || location < 0) continue
+      gl.enableVertexAttribArray(location)
+      gl.vertexAttribPointer(location, a.size, a.type, a.normalized, a.stride, a.offset)
+    }
+  }
+
+  /**
+   * WebGL type of the indices to draw the mesh with, either `UNSIGNED_SHORT`
+   * or `UNSIGNED_INT`.
+   */
+  get indexFormat(): number {
+    return this.ffi.state_index_format(this.engine)
+  }
+
+  /**
+   * Switches the camera to orbiting a target from where it is.
+   */
+  orbitCamera(x: number, y: number, z: number) {
+    this.ffi.orbit_camera(this.engine, x, y, z)
+  }
+
+  /**
+   * Switches the camera to flying freely from where it is.
+   */
+  flyCamera() {
+    this.ffi.fly_camera(this.engine)
+  }
+
+  /**
+   * Switches the camera to following a target from behind, turned to the
+   * heading in radians.
+   */
+  followCamera(x: number, y: number, z: number, heading: number) {
+    this.ffi.follow_camera(this.engine, x, y, z, heading)
+  }
+
+  /**
+   * Turns the camera by the pointer movement in pixels.
+   */
+  lookCamera(dx: number, dy: number) {
+    this.ffi.look_camera(this.engine, dx, dy)
+  }
+
+  /**
+   * Zooms an orbiting camera by the wheel movement.
+   */
+  zoomCamera(delta: number) {
+    this.ffi.zoom_camera(this.engine, delta)
+  }
+
+  /**
+   * Moves the target of a following or orbiting camera.
+   */
+  setCameraTarget(x: number, y: number, z: number, heading = 0) {
+    this.ffi.set_camera_target(this.engine, x, y, z, heading)
+  }
+
+  set cameraProjection({ fov, near, far }: {
+    fov: number,
+    near: number,
+    far: number,
+  }) {
+    this.ffi.set_camera_projection(this.engine, fov, near, far)
+  }
+
+  /**
+   * Holds a key down, by its DOM key code.
+   */
+  keyDown(code: number) {
+    this.ffi.key_down(this.engine, code)
+  }
+
+  keyUp(code: number) {
+    this.ffi.key_up(this.engine, code)
+  }
+
+  /**
+   * Moves the pointer to a position on the canvas, by the given movement,
+   * which keeps going while the pointer is locked.
+   */
+  pointerMove(x: number, y: number, dx: number, dy: number) {
+    this.ffi.pointer_move(this.engine, x, y, dx, dy)
+  }
+
+  pointerButton(button: number, down: boolean) {
+    this.ffi.pointer_button(this.engine, button, down)
+  }
+
+  pointerWheel(delta: number) {
+    this.ffi.pointer_wheel(this.engine, delta)
+  }
+
+  touchStart(id: number, x: number, y: number) {
+    this.ffi.touch_start(this.engine, id, x, y)
+  }
+
+  touchMove(id: number, x: number, y: number) {
+    this.ffi.touch_move(this.engine, id, x, y)
+  }
+
+  touchEnd(id: number) {
+    this.ffi.touch_end(this.engine, id)
+  }
+
+  /**
+   * Releases everything that is held, supposed to be called when the canvas
+   * loses the focus and the releases would be missed.
+   */
+  releaseInput() {
+    this.ffi.release_input(this.engine)
+  }
+
+  /**
+   * State flags of a key: 1 if it is held, 2 if it has been pressed and 4 if
+   * it has been released since the last simulation step.
+   */
+  keyState(code: number): number {
+    return this.ffi.key_state(this.engine, code)
+  }
+
+  buttonState(button: number): number {
+    return this.ffi.button_state(this.engine, button)
+  }
+
+  /**
+   * Binds a named action to a key or a pointer button, so that it can be
+   * queried regardless of the controls.
+   *
+   * @returns false if there are too many actions
+   */
+  bindAction(name: string, binding: { key: number } | { button: number }): boolean {
+    const device = 'key' in binding ? 0 : 1
+    const code = 'key' in binding ? binding.key : binding.button
+    return Boolean(this.ffi.bind_action(this.engine, this.action(name), device, code))
+  }
+
+  unbindAction(name: string) {
+    this.ffi.unbind_action(this.engine, this.action(name))
+  }
+
+  /**
+   * State flags of an action, combined from all of its bindings, see
+   * `keyState`.
+   */
+  actionState(name: string): number {
+    return this.ffi.action_state(this.engine, this.action(name))
+  }
+
+  private action(name: string): number {
+    let action = this.actions.get(name)
+    if (action === undefined) {
+      action = this.actions.size
+      this.actions.set(name, action)
+    }
+    return action
+  }
+
+  get sceneLength(): number {
+    return this.ffi.scene_len(this.engine)
+  }
+
+  /**
+   * Adds a node to the scene under a parent, or as a root, returning its
+   * index, or -1 if there is no such parent.
+   */
+  addNode(parent = -1): number {
+    return this.ffi.add_node(this.engine, parent)
+  }
+
+  nodeParent(node: number): number {
+    return this.ffi.node_parent(this.engine, node)
+  }
+
+  /**
+   * Moves a node under a parent, or makes it a root if the parent is
+   * negative. Fails if the parent is below the node.
+   */
+  setNodeParent(node: number, parent: number): boolean {
+    return Boolean(this.ffi.set_node_parent(this.engine, node, parent))
+  }
+
+  nodeMesh(node: number): number {
+    return this.ffi.node_mesh(this.engine, node)
+  }
+
+  setNodeTranslation(node: number, x: number, y: number, z: number): boolean {
+    return Boolean(this.ffi.set_node_translation(this.engine, node, x, y, z))
+  }
+
+  setNodeRotation(node: number, x: number, y: number, z: number, w: number): boolean {
+    return Boolean(this.ffi.set_node_rotation(this.engine, node, x, y, z, w))
+  }
+
+  setNodeScale(node: number, x: number, y: number, z: number): boolean {
+    return Boolean(this.ffi.set_node_scale(this.engine, node, x, y, z))
+  }
+
+  /**
+   * Sets the color the instance of the node mesh is drawn with, which the
+   * instances of the scene take on every `instanceScene`.
+   */
+  setNodeColor(node: number, r: number, g: number, b: number, a = 255): boolean {
+    return Boolean(this.ffi.set_node_color(this.engine, node, r, g, b, a))
+  }
+
+  /**
+   * World matrix of a node as of the last update, to draw its mesh with.
+   */
+  nodeWorldMatrix(node: number): Float32Array {
+    return new Float32Array(this.memory.buffer, this.ffi.node_world_matrix(this.engine, node), 16)
+  }
+
+  /**
+   * Adds a batch of instances of an index range of the mesh, returning its
+   * index, or -1 if the range is past the indices.
+   */
+  addBatch(first: number, count: number): number {
+    return this.ffi.add_batch(this.engine, first, count)
+  }
+
+  /**
+   * Number of batches, one per mesh of the loaded asset, followed by the
+   * added ones.
+   */
+  get batchesLength(): number {
+    return this.ffi.batches_len(this.engine)
+  }
+
+  batch(batch: number): { first: number, count: number, colored: boolean, instances: number } {
+    return {
+      first: this.ffi.batch_first(this.engine, batch),
+      count: this.ffi.batch_count(this.engine, batch),
+      colored: Boolean(this.ffi.batch_colored(this.engine, batch)),
+      instances: this.ffi.batch_instances_len(this.engine, batch),
+    }
+  }
+
+  /**
+   * Sets whether the instances of a batch are drawn with their colors.
+   */
+  setBatchColored(batch: number, colored: boolean) {
+    this.ffi.set_batch_colored(this.engine, batch, colored)
+  }
+
+  /**
+   * Instances of a batch: a model matrix in columns followed by an RGBA
+   * color each, `instanceStride` bytes apart.
+   */
+  batchInstancesData(batch: number): Uint8Array {
+    const len = this.ffi.batch_instances_len(this.engine, batch) * this.instanceStride
+    return new Uint8Array(this.memory.buffer, this.ffi.batch_instances(this.engine, batch), len)
+  }
+
+  get instanceStride(): number {
+    return this.ffi.instance_stride()
+  }
+
+  /**
+   * Adds an instance to a batch, at the origin and white, returning its
+   * index, or -1 if there is no such batch.
+   */
+  addInstance(batch: number): number {
+    return this.ffi.add_instance(this.engine, batch)
+  }
+
+  /**
+   * Removes an instance of a batch, moving the last one into its place.
+   */
+  removeInstance(batch: number, instance: number): boolean {
+    return Boolean(this.ffi.remove_instance(this.engine, batch, instance))
+  }
+
+  clearInstances(batch: number) {
+    this.ffi.clear_instances(this.engine, batch)
+  }
+
+  setInstanceTransform(batch: number, instance: number, translation: number[], rotation = [0, 0, 0, 1], scale = 1): boolean {
+    const [tx, ty, tz] = translation
+    const [rx, ry, rz, rw] = rotation
+    return Boolean(this.ffi.set_instance_transform(this.engine, batch, instance, tx, ty, tz, rx, ry, rz, rw, scale))
+  }
+
+  setInstanceColor(batch: number, instance: number, r: number, g: number, b: number, a = 255): boolean {
+    return Boolean(this.ffi.set_instance_color(this.engine, batch, instance, r, g, b, a))
+  }
+
+  /**
+   * Replaces the instances of the batches with the scene nodes drawing
+   * their meshes, with their colors, culling the ones out of the view.
+   */
+  instanceScene() {
+    this.ffi.instance_scene(this.engine)
   }
 
-  rotateCamera(pixels: number): number {
-    return this.ffi.rotate_camera(pixels)
+  /**
+   * Points per-instance attributes at the instances of a batch uploaded to
+   * the bound `ARRAY_BUFFER`: the model matrix to 4 consecutive locations,
+   * and the color, if the batch is colored and has a location.
+   */
+  pointInstanceAttributes(gl: WebGL2RenderingContext, batch: number, model: number, color = -1) {
+    const stride = this.instanceStride
+    for (let i = 0; i < 4; i++) {
+      gl.enableVertexAttribArray(model + i)
+      gl.vertexAttribPointer(model + i, 4, gl.FLOAT, false, stride, i * 16)
+      gl.vertexAttribDivisor(model + i, 1)
+    }
+    if (color < 0) return
+    if (this.ffi.batch_colored(this.engine, batch)) {
+      gl.enableVertexAttribArray(color)
+      gl.vertexAttribPointer(color, 4, gl.UNSIGNED_BYTE, true, stride, 64)
+      gl.vertexAttribDivisor(color, 1)
+    } else {
+      gl.disableVertexAttribArray(color)
+      gl.vertexAttrib4f(color, 1, 1, 1, 1)
+    }
+  }
+
+  /**
+   * Draws all the instances of a batch with the bound index buffer.
+   */
+  drawBatch(gl: WebGL2RenderingContext, batch: number) {
+    const { first, count, instances } = this.batch(batch)
+    const size = this.indexFormat === gl.UNSIGNED_INT ? 4 : 2
+    gl.drawElementsInstanced(gl.TRIANGLES, count, this.indexFormat, first * size, instances)
+  }
+
+  /**
+   * Adds a sprite of the whole texture, untinted, on the layer 0, returning
+   * its index.
+   */
+  addSprite(texture: number, x: number, y: number, width: number, height: number): number {
+    return this.ffi.add_sprite(this.engine, texture, x, y, width, height)
+  }
+
+  /**
+   * Removes a sprite, moving the last one into its place.
+   */
+  removeSprite(sprite: number): boolean {
+    return Boolean(this.ffi.remove_sprite(this.engine, sprite))
+  }
+
+  clearSprites() {
+    this.ffi.clear_sprites(this.engine)
+  }
+
+  get spritesLength(): number {
+    return this.ffi.sprites_len(this.engine)
+  }
+
+  setSpriteTransform(sprite: number, x: number, y: number, width: number, height: number, rotation = 0): boolean {
+    return Boolean(this.ffi.set_sprite_transform(this.engine, sprite, x, y, width, height, rotation))
+  }
+
+  /**
+   * Sets the rectangle of the texture a sprite shows, in texture coordinates.
+   */
+  setSpriteUv(sprite: number, u0: number, v0: number, u1: number, v1: number): boolean {
+    return Boolean(this.ffi.set_sprite_uv(this.engine, sprite, u0, v0, u1, v1))
+  }
+
+  setSpriteTint(sprite: number, r: number, g: number, b: number, a = 255): boolean {
+    return Boolean(this.ffi.set_sprite_tint(this.engine, sprite, r, g, b, a))
+  }
+
+  /**
+   * Sets the texture and the layer of a sprite, drawn from the lowest layer
+   * up.
+   */
+  setSpriteTexture(sprite: number, texture: number, layer = 0): boolean {
+    return Boolean(this.ffi.set_sprite_texture(this.engine, sprite, texture, layer))
+  }
+
+  /**
+   * Sprite quad vertices: a position and a texture coordinate as floats,
+   * and an RGBA tint, 20 bytes each.
+   */
+  get spriteVerticesData(): Uint8Array {
+    return new Uint8Array(this.memory.buffer, this.ffi.sprite_vertices(this.engine), this.ffi.sprite_vertices_len(this.engine) * 20)
+  }
+
+  get spriteIndicesData(): Uint8Array {
+    const size = this.spriteIndexFormat === 0x1405 ? 4 : 2
+    return new Uint8Array(this.memory.buffer, this.ffi.sprite_indices(this.engine), this.ffi.sprite_indices_len(this.engine) * size)
+  }
+
+  get spriteIndexFormat(): number {
+    return this.ffi.sprite_index_format(this.engine)
+  }
+
+  /**
+   * Sprite batches in the order to draw them, each a texture and a range of
+   * the sprite indices.
+   */
+  get spriteBatches(): { texture: number, layer: number, first: number, count: number }[] {
+    const data = new Int32Array(this.memory.buffer, this.ffi.sprite_batches(this.engine), this.ffi.sprite_batches_len(this.engine) * 4)
+    const result = []
+    for (let i = 0; i < data.length; i += 4) {
+      result.push({ texture: data[i] >>> 0, layer: data[i + 1], first: data[i + 2], count: data[i + 3] })
+    }
+    return result
+  }
+
+  /**
+   * Points the position, texture coordinate and tint attributes at the
+   * sprite vertices uploaded to the bound `ARRAY_BUFFER`.
+   */
+  pointSpriteAttributes(gl: WebGLRenderingContext, position: number, uv: number, tint: number) {
+    const attributes: [number, number, number, boolean, number][] = [
+      [position, 2, gl.FLOAT, false, 0],
+      [uv, 2, gl.FLOAT, false, 8],
+      [tint, 4, gl.UNSIGNED_BYTE, true, 16],
+    ]
+    for (const [location, size, type, normalized, offset] of attributes) {
+      if (location < 0) continue
+      gl.enableVertexAttribArray(location)
+      gl.vertexAttribPointer(location, size, type, normalized, 20, offset)
+    }
+  }
+
+  /**
+   * Draws the sprites with the bound index buffer, binding the textures by
+   * their numbers.
+   */
+  drawSprites(gl: WebGLRenderingContext, textures: WebGLTexture[]) {
+    const type = this.spriteIndexFormat
+    const size = type === gl.UNSIGNED_INT ? 4 : 2
+    for (const batch of this.spriteBatches) {
+      gl.bindTexture(gl.TEXTURE_2D, textures[batch.texture])
+      gl.drawElements(gl.TRIANGLES, batch.count, type, batch.first * size)
+    }
+  }
+
+  /**
+   * Adds an empty atlas, returning its index, which is also the number of
+   * the texture the sprites of its images are drawn with.
+   */
+  addAtlas(maxSize = 4096, padding = 1, extrude = 0, powerOfTwo = true): number {
+    return this.ffi.add_atlas(this.engine, maxSize, padding, extrude, powerOfTwo)
+  }
+
+  /**
+   * Adds an image to an atlas, growing it if needed, and returns the index
+   * of its slot, or -1 if it doesn't fit.
+   */
+  atlasInsert(atlas: number, width: number, height: number): number {
+    return this.ffi.atlas_insert(this.engine, atlas, width, height)
+  }
+
+  /**
+   * Replaces the images of an atlas with the sizes, packed anew. Returns
+   * false if they don't fit, in which case the atlas is kept intact.
+   */
+  packAtlas(atlas: number, sizes: { width: number, height: number }[]): boolean {
+    const data = new Uint32Array(sizes.flatMap(s => [s.width, s.height]))
+    const ptr = this.ffi.upload_buffer(this.engine, data.byteLength)
+    if (ptr === 0) {
+      return false
+    }
+    new Uint32Array(this.memory.buffer, ptr, data.length).set(data)
+    return Boolean(this.ffi.pack_atlas(this.engine, atlas))
+  }
+
+  atlasSize(atlas: number): { width: number, height: number } {
+    return { width: this.ffi.atlas_width(this.engine, atlas), height: this.ffi.atlas_height(this.engine, atlas) }
+  }
+
+  /**
+   * Places of the images of an atlas in pixels, without the padding and the
+   * extruded edges.
+   */
+  atlasSlots(atlas: number): { x: number, y: number, width: number, height: number }[] {
+    const data = new Uint32Array(this.memory.buffer, this.ffi.atlas_slots(this.engine, atlas), this.ffi.atlas_slots_len(this.engine, atlas) * 4)
+    const result = []
+    for (let i = 0; i < data.length; i += 4) {
+      result.push({ x: data[i], y: data[i + 1], width: data[i + 2], height: data[i + 3] })
+    }
+    return result
+  }
+
+  /**
+   * Shows an image of an atlas on a sprite, which has to be set again after
+   * the atlas grows.
+   */
+  setSpriteSlot(sprite: number, atlas: number, slot: number): boolean {
+    return Boolean(this.ffi.set_sprite_slot(this.engine, sprite, atlas, slot))
   }
-};
\ No newline at end of file
+};
@@ -0,0 +1,287 @@
+use crate::core::{Color, Mat4, Transform};
+
+/// Node is an object of the scene, placed relative to its parent, or to the
+/// scene if it is a root.
+pub struct Node {
+    pub name: String,
+    /// Mesh of the asset the node draws, if any.
+    pub mesh: Option<usize>,
+    /// Color the instance of the mesh is drawn with, see
+    /// `Engine::instance_scene`.
+    pub color: Color,
+    local: Transform,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    world: Mat4,
+    dirty: bool,
+}
+
+/// Scene is a hierarchy of nodes. World matrices of the nodes are cached,
+/// and only recomputed by an update for the nodes which have changed since
+/// the last one, along with their descendants.
+pub struct Scene {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    stack: Vec<(usize, bool)>,
+}
+
+impl Node {
+    /// Return the transformation relative to the parent.
+    #[inline]
+    pub fn local(&self) -> &Transform {
+        &self.local
+    }
+
+    #[inline]
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    #[inline]
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Return the transformation relative to the scene as of the last
+    /// update.
+    #[inline]
+    pub fn world(&self) -> &Mat4 {
+        &self.world
+    }
+
+    /// Return whether the local transformation has changed since the last
+    /// update.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Default for Scene {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    #[inline]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    #[inline]
+    pub fn node(&self, node: usize) -> Option<&Node> {
+        self.nodes.get(node)
+    }
+
+    /// Return a node to change its name or mesh.
+    #[inline]
+    pub fn node_mut(&mut self, node: usize) -> Option<&mut Node> {
+        self.nodes.get_mut(node)
+    }
+
+    /// Return the nodes without a parent.
+    #[inline]
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    /// Return the first node of a name.
+    #[inline]
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    /// Add a node under a parent, or as a root, and return its index. Return
+    /// `None` if the parent is out of bounds.
+    pub fn add(&mut self, name: &str, local: Transform, parent: Option<usize>) -> Option<usize> {
+        if parent.is_some_and(|p| p >= self.nodes.len()) {
+            return None;
+        }
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            mesh: None,
+            color: Color(255, 255, 255, 255),
+            local,
+            parent,
+            children: Vec::new(),
+            world: Mat4::identity(),
+            dirty: true,
+        });
+        match parent {
+            Some(p) => self.nodes[p].children.push(node),
+            None => self.roots.push(node),
+        }
+        Some(node)
+    }
+
+    /// Return the local transformation of a node to be changed, marking it
+    /// for the next update.
+    #[inline]
+    pub fn local_mut(&mut self, node: usize) -> Option<&mut Transform> {
+        let node = self.nodes.get_mut(node)?;
+        node.dirty = true;
+        Some(&mut node.local)
+    }
+
+    /// Move a node under another parent, or make it a root, keeping its
+    /// local transformation. Return false if either is out of bounds, or
+    /// the parent is the node itself or one of its descendants.
+    pub fn set_parent(&mut self, node: usize, parent: Option<usize>) -> bool {
+        if node >= self.nodes.len() || parent.is_some_and(|p| p >= self.nodes.len()) {
+            return false;
+        }
+        let mut ancestor = parent;
+        while let Some(a) = ancestor {
+            if a == node {
+                return false;
+            }
+            ancestor = self.nodes[a].parent;
+        }
+
+        let siblings = match self.nodes[node].parent {
+            Some(p) => &mut self.nodes[p].children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|&n| n != node);
+        match parent {
+            Some(p) => self.nodes[p].children.push(node),
+            None => self.roots.push(node),
+        }
+        let node = &mut self.nodes[node];
+        node.parent = parent;
+        node.dirty = true;
+        true
+    }
+
+    /// Recompute world matrices of the nodes which have changed, and of
+    /// their descendants, parents first. Return the number of nodes
+    /// recomputed.
+    pub fn update(&mut self) -> usize {
+        let mut count = 0;
+        self.stack.clear();
+        self.stack
+            .extend(self.roots.iter().rev().map(|&r| (r, false)));
+        while let Some((i, parent_changed)) = self.stack.pop() {
+            let changed = parent_changed || self.nodes[i].dirty;
+            if changed {
+                let parent = self.nodes[i].parent.map(|p| self.nodes[p].world);
+                let node = &mut self.nodes[i];
+                let local = node.local.to_mat4();
+                node.world = parent.map_or(local, |p| local * p);
+                node.dirty = false;
+                count += 1;
+            }
+            self.stack
+                .extend(self.nodes[i].children.iter().rev().map(|&c| (c, changed)));
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Mat4, Quat, Transform, Vec3};
+    use crate::scene::Scene;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn at(x: f32, y: f32, z: f32) -> Transform {
+        Transform::new(Vec3(x, y, z), Quat::identity(), Vec3(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn hierarchy() {
+        let mut scene = Scene::new();
+        let root = scene.add("root", at(1.0, 0.0, 0.0), None).unwrap();
+        let child = scene.add("child", at(0.0, 2.0, 0.0), Some(root)).unwrap();
+        let other = scene.add("other", at(0.0, 0.0, 3.0), None).unwrap();
+        assert_eq!(None, scene.add("bad", at(0.0, 0.0, 0.0), Some(5)));
+        assert_eq!([root, other], scene.roots());
+        assert_eq!([child], scene.node(root).unwrap().children());
+        assert_eq!(Some(child), scene.find("child"));
+
+        assert_eq!(3, scene.update());
+        let world = |s: &Scene, n: usize| {
+            s.node(n)
+                .unwrap()
+                .world()
+                .transform_point(Vec3(0.0, 0.0, 0.0))
+        };
+        assert_eq!(Vec3(1.0, 2.0, 0.0), world(&scene, child));
+
+        // Rotating the parent turns the child around it
+        let r = scene.local_mut(root).unwrap();
+        r.rotation = Quat::from_axis_angle(Vec3(0.0, 0.0, 1.0), FRAC_PI_2);
+        assert!(scene.node(root).unwrap().is_dirty());
+        assert_eq!(2, scene.update());
+        assert_eq!(Vec3(-1.0, 0.0, 0.0), world(&scene, child));
+        assert_eq!(Vec3(1.0, 0.0, 0.0), world(&scene, root));
+
+        // Only the changed nodes are recomputed
+        assert_eq!(0, scene.update());
+        scene.local_mut(child).unwrap().scale = Vec3(2.0, 2.0, 2.0);
+        assert_eq!(1, scene.update());
+        assert_eq!(
+            Mat4::compose(
+                Vec3(-1.0, 0.0, 0.0),
+                Vec3(2.0, 2.0, 2.0),
+                scene.node(root).unwrap().local().rotation
+            ),
+            *scene.node(child).unwrap().world()
+        );
+    }
+
+    #[test]
+    fn reparent() {
+        let mut scene = Scene::new();
+        let a = scene.add("a", at(1.0, 0.0, 0.0), None).unwrap();
+        let b = scene.add("b", at(0.0, 1.0, 0.0), Some(a)).unwrap();
+        let c = scene.add("c", at(0.0, 0.0, 1.0), Some(b)).unwrap();
+        scene.update();
+
+        // Cycles are rejected
+        assert!(!scene.set_parent(a, Some(c)));
+        assert!(!scene.set_parent(a, Some(a)));
+        assert!(!scene.set_parent(3, None));
+        assert!(!scene.set_parent(a, Some(3)));
+
+        assert!(scene.set_parent(c, None));
+        assert_eq!([a, c], scene.roots());
+        assert!(scene.node(b).unwrap().children().is_empty());
+        assert_eq!(1, scene.update());
+        assert_eq!(
+            Mat4::translation(Vec3(0.0, 0.0, 1.0)),
+            *scene.node(c).unwrap().world()
+        );
+
+        assert!(scene.set_parent(b, Some(c)));
+        assert_eq!([a, c], scene.roots());
+        assert_eq!(Some(c), scene.node(b).unwrap().parent());
+        assert_eq!(1, scene.update());
+        assert_eq!(
+            Mat4::translation(Vec3(0.0, 1.0, 1.0)),
+            *scene.node(b).unwrap().world()
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use crate::core::{Color, Mat2x3, Vec2};
+use crate::mesh::Indices;
+use crate::region::{Region, View};
+
+/// Rect is an axis aligned rectangle, such as the part of a texture a sprite
+/// shows, in texture coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+/// Sprite is a textured quad, centered at its position and turned by its
+/// rotation in radians. Sprites of a lower layer are drawn first.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Sprite {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub rotation: f32,
+    pub uv: Rect,
+    pub tint: Color,
+    pub layer: i32,
+    /// Texture of the host the sprite is drawn with.
+    pub texture: u32,
+}
+
+/// SpriteVertex is a corner of a sprite quad: the 4 floats of a textured
+/// vertex, followed by the tint, which makes 80 bytes a quad.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq)]
+pub struct SpriteVertex {
+    pub position: Vec2,
+    pub uv: Vec2,
+    pub tint: Color,
+}
+
+/// SpriteBatch is a range of the sprite indices drawn with a single call,
+/// as all its sprites have the same texture and layer.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SpriteBatch {
+    pub texture: u32,
+    pub layer: i32,
+    pub first: u32,
+    pub count: u32,
+}
+
+/// Batcher keeps the sprites and writes them into a single vertex stream
+/// with an index buffer, sorted by layer and then by texture, so that every
+/// texture is bound once per layer. Sprites of the same layer and texture
+/// keep their order. Building reuses the memory, so it doesn't allocate
+/// while the sprites fit into it.
+pub struct Batcher {
+    pub sprites: Vec<Sprite>,
+    order: Vec<(i32, u32, u32)>,
+    vertices: Region<SpriteVertex>,
+    indices: Indices,
+    batches: Vec<SpriteBatch>,
+}
+
+impl Rect {
+    #[inline]
+    pub const fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Return the rectangle of the whole texture.
+    #[inline]
+    pub const fn unit() -> Self {
+        Self::new(Vec2(0.0, 0.0), Vec2(1.0, 1.0))
+    }
+
+    #[inline]
+    pub fn size(self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+impl Sprite {
+    /// Return a sprite of the whole texture, untinted, on the layer 0.
+    #[inline]
+    pub const fn new(texture: u32, position: Vec2, size: Vec2) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            uv: Rect::unit(),
+            tint: Color(255, 255, 255, 255),
+            layer: 0,
+            texture,
+        }
+    }
+
+    /// Return the corners of the quad, counter-clockwise from the bottom
+    /// left one, which shows the minimum of the texture rectangle.
+    #[inline]
+    pub fn corners(&self) -> [SpriteVertex; 4] {
+        let m = Mat2x3::compose(self.position, self.size, self.rotation);
+        let Rect { min, max } = self.uv;
+        [
+            (Vec2(-0.5, -0.5), min),
+            (Vec2(0.5, -0.5), Vec2(max.0, min.1)),
+            (Vec2(0.5, 0.5), max),
+            (Vec2(-0.5, 0.5), Vec2(min.0, max.1)),
+        ]
+        .map(|(corner, uv)| SpriteVertex {
+            position: m * corner,
+            uv,
+            tint: self.tint,
+        })
+    }
+}
+
+impl Default for Batcher {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Batcher {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sprites: Vec::new(),
+            order: Vec::new(),
+            vertices: Region::new(),
+            indices: Indices::new(),
+            batches: Vec::new(),
+        }
+    }
+
+    /// Return a batcher with the memory for the given number of sprites.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sprites: Vec::with_capacity(capacity),
+            order: Vec::with_capacity(capacity),
+            vertices: Region::with_capacity(4 * capacity),
+            indices: Indices::with_capacity(6 * capacity),
+            batches: Vec::new(),
+        }
+    }
+
+    /// Add a sprite and return its index.
+    #[inline]
+    pub fn push(&mut self, sprite: Sprite) -> usize {
+        self.sprites.push(sprite);
+        self.sprites.len() - 1
+    }
+
+    /// Remove a sprite, moving the last one into its place. Return false if
+    /// it is out of bounds.
+    #[inline]
+    pub fn remove(&mut self, sprite: usize) -> bool {
+        if sprite >= self.sprites.len() {
+            return false;
+        }
+        self.sprites.swap_remove(sprite);
+        true
+    }
+
+    /// Return the quad vertices as of the last build.
+    #[inline]
+    pub fn vertices(&self) -> &[SpriteVertex] {
+        &self.vertices
+    }
+
+    /// Return the indices of the quad triangles as of the last build.
+    #[inline]
+    pub fn indices(&self) -> &Indices {
+        &self.indices
+    }
+
+    /// Return the batches as of the last build, in the order to draw them.
+    #[inline]
+    pub fn batches(&self) -> &[SpriteBatch] {
+        &self.batches
+    }
+
+    /// Write the quads of the sprites and group them into batches.
+    pub fn build(&mut self) {
+        // The index makes every key unique, so an unstable sort, which
+        // doesn't allocate, keeps the order of equal sprites.
+        self.order.clear();
+        self.order.extend(
+            self.sprites
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (s.layer, s.texture, i as u32)),
+        );
+        self.order.sort_unstable();
+
+        self.vertices.clear();
+        self.indices.clear();
+        self.batches.clear();
+        for &(layer, texture, i) in &self.order {
+            let first = self.indices.len() as u32;
+            match self.batches.last_mut() {
+                Some(b) if b.layer == layer && b.texture == texture => b.count += 6,
+                _ => self.batches.push(SpriteBatch {
+                    texture,
+                    layer,
+                    first,
+                    count: 6,
+                }),
+            }
+            let v = self.vertices.len() as u32;
+            self.vertices.extend(self.sprites[i as usize].corners());
+            self.indices.extend([v, v + 1, v + 2, v + 2, v + 3, v]);
+        }
+    }
+
+    /// Return the view of the vertices for the host.
+    #[inline]
+    pub fn vertices_view(&self) -> View {
+        self.vertices.view()
+    }
+
+    /// Return the view of the indices for the host.
+    #[inline]
+    pub fn indices_view(&self) -> View {
+        self.indices.view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Color, Vec2};
+    use crate::mesh::IndexFormat;
+    use crate::sprite::{Batcher, Rect, Sprite, SpriteBatch};
+    use std::f32::consts::FRAC_PI_2;
+    use std::mem;
+
+    #[test]
+    fn corners() {
+        let mut sprite = Sprite::new(0, Vec2(10.0, 20.0), Vec2(4.0, 2.0));
+        sprite.uv = Rect::new(Vec2(0.25, 0.5), Vec2(0.5, 1.0));
+        sprite.tint = Color(255, 0, 0, 255);
+        let [a, b, c, d] = sprite.corners();
+        assert_eq!(Vec2(8.0, 19.0), a.position);
+        assert_eq!(Vec2(12.0, 21.0), c.position);
+        assert_eq!(Vec2(0.5, 0.5), b.uv);
+        assert_eq!(Vec2(0.25, 1.0), d.uv);
+        assert!(a.tint == sprite.tint);
+        assert_eq!(80, 4 * mem::size_of_val(&a));
+
+        // Turning a quarter swaps the extents
+        sprite.rotation = FRAC_PI_2;
+        assert_eq!(Vec2(11.0, 18.0), sprite.corners()[0].position);
+    }
+
+    #[test]
+    fn batches() {
+        let mut batcher = Batcher::with_capacity(8);
+        let ptr = batcher.vertices_view().ptr;
+        let sprite = |texture, layer, x| Sprite {
+            layer,
+            ..Sprite::new(texture, Vec2(x, 0.0), Vec2(1.0, 1.0))
+        };
+        batcher.push(sprite(1, 0, 0.0));
+        batcher.push(sprite(2, 0, 1.0));
+        batcher.push(sprite(1, 0, 2.0));
+        batcher.push(sprite(1, -1, 3.0));
+        batcher.push(sprite(2, 1, 4.0));
+        batcher.build();
+
+        let batch = |texture, layer, first, count| SpriteBatch {
+            texture,
+            layer,
+            first,
+            count,
+        };
+        assert_eq!(
+            [
+                batch(1, -1, 0, 6),
+                batch(1, 0, 6, 12),
+                batch(2, 0, 18, 6),
+                batch(2, 1, 24, 6),
+            ],
+            batcher.batches()
+        );
+        assert_eq!(20, batcher.vertices().len());
+        assert_eq!(ptr, batcher.vertices_view().ptr);
+        assert_eq!(IndexFormat::U16, batcher.indices().format());
+
+        // Sprites of a batch keep their order, and quads share the corners
+        assert_eq!(Vec2(-0.5, -0.5), batcher.vertices()[4].position);
+        assert_eq!(Vec2(1.5, -0.5), batcher.vertices()[8].position);
+        let indices: Vec<u32> = batcher.indices().iter().skip(6).take(6).collect();
+        assert_eq!(vec![4, 5, 6, 6, 7, 4], indices);
+
+        assert!(batcher.remove(3));
+        assert!(!batcher.remove(4));
+        batcher.build();
+        assert_eq!(3, batcher.batches().len());
+        assert_eq!(0, batcher.batches()[0].first);
+    }
+}
@@ -1,16 +1,82 @@
-use crate::core::{Color, Mat4, Vec3};
+use crate::anim::Animator;
+use crate::atlas::Atlas;
+use crate::camera::{Camera, Controller};
+use crate::core::{Aabb, Color, Mat4, Ray, Sphere, Vec3};
+use crate::ecs::{Schedule, World};
+use crate::input::Input;
+use crate::instance::Batch;
+use crate::mesh::Mesh;
+use crate::region::Layout;
+use crate::scene::Scene;
+use crate::sprite::Batcher;
+use crate::timestep::Timestep;
+use crate::vertex::{Packing, VertexBuffer};
 
 #[repr(C)]
 pub struct Screen(pub f32, pub f32);
 
 #[repr(C)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Vertex(pub Vec3, pub Color);
 
+impl Aabb {
+    /// Return the smallest box enclosing positions of all the vertices.
+    #[inline]
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        Self::fit(vertices.iter().map(|v| v.0))
+    }
+}
+
+impl Sphere {
+    /// Return a sphere enclosing positions of all the vertices.
+    #[inline]
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        Self::fit(vertices.iter().map(|v| v.0))
+    }
+}
+
 #[repr(C)]
 pub struct State {
     pub screen: Screen,
-    pub camera: Vec3,
+    pub input: Input,
+    pub camera: Camera,
+    pub controller: Controller,
     pub view_matrix: Mat4,
-    pub vertices: Vec<Vertex>,
+    pub ray: Ray,
+    pub scene: Scene,
+    pub world: World,
+    pub schedule: Schedule,
+    pub mesh: Mesh,
+    pub vertex_buffer: VertexBuffer,
+    pub batches: Vec<Batch>,
+    pub sprites: Batcher,
+    pub atlases: Vec<Atlas>,
+    pub packing: Packing,
+    pub uploads: Vec<Vec<u8>>,
+    pub animator: Option<Animator>,
+    pub layout: Layout,
+    pub timestep: Timestep,
+    pub previous_camera: Camera,
     pub count: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Aabb, Color, Sphere, Vec3};
+    use crate::state::Vertex;
+
+    #[test]
+    fn bounds() {
+        let c = Color(255, 255, 255, 255);
+        let v = [
+            Vertex(Vec3(0.5, -0.5, 1.0), c),
+            Vertex(Vec3(-0.5, 0.5, 0.0), c),
+        ];
+        let a = Aabb(Vec3(-0.5, -0.5, 0.0), Vec3(0.5, 0.5, 1.0));
+        assert_eq!(a, Aabb::from_vertices(&v));
+        assert_eq!(
+            Sphere(Vec3(0.0, 0.0, 0.5), 0.8660),
+            Sphere::from_vertices(&v)
+        );
+    }
+}
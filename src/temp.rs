@@ -1,4 +1,3 @@
-
 // #[no_mangle]
 // pub unsafe extern "C" fn vec_data(v: *mut Vec<u8>) -> *const u8 {
 //     v.as_ref().expect("Not available").as_ptr()
@@ -28,4 +27,4 @@
 // "#,
 //     );
 //     Box::into_raw(Box::new(s))
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,122 @@
+/// Number of simulation steps per second by default.
+pub const TICK_RATE: f64 = 60.0;
+
+/// Number of steps a single frame may catch up with by default.
+pub const MAX_STEPS: u32 = 5;
+
+/// Timestep splits the time between frames into fixed simulation steps, so
+/// that the simulation speed doesn't depend on the display refresh rate.
+/// The time left over is kept for the next frame, and the fraction of a step
+/// it makes is the alpha to interpolate the last two simulation states with.
+pub struct Timestep {
+    step: f64,
+    max_steps: u32,
+    accumulator: f64,
+}
+
+impl Default for Timestep {
+    #[inline]
+    fn default() -> Self {
+        Self::new(TICK_RATE, MAX_STEPS)
+    }
+}
+
+impl Timestep {
+    /// Return a timestep of the given number of steps per second, which
+    /// doesn't catch up with more than the given number of steps per frame.
+    #[inline]
+    pub const fn new(rate: f64, max_steps: u32) -> Self {
+        Self {
+            step: 1000.0 / rate,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Return the duration of a step in milliseconds.
+    #[inline]
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Set the number of steps per second, ignoring non positive and non
+    /// finite rates.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f64) {
+        if rate > 0.0 && rate.is_finite() {
+            self.step = 1000.0 / rate;
+        }
+    }
+
+    #[inline]
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+
+    /// Accumulate the time of a frame in milliseconds and return the number
+    /// of steps to simulate. Time beyond the maximum number of steps is
+    /// dropped, so that a slow frame doesn't make the next ones even slower.
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        if dt.is_finite() && dt > 0.0 {
+            self.accumulator += dt;
+        }
+        let steps = (self.accumulator / self.step).floor();
+        if steps > self.max_steps as f64 {
+            self.accumulator = self.accumulator.rem_euclid(self.step);
+            return self.max_steps;
+        }
+        self.accumulator -= steps * self.step;
+        steps as u32
+    }
+
+    /// Return the fraction of a step the accumulated time makes, from 0 to 1.
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timestep::Timestep;
+
+    #[test]
+    fn advance() {
+        let mut t = Timestep::new(100.0, 4);
+        assert_eq!(10.0, t.step());
+        assert_eq!(0, t.advance(5.0));
+        assert_eq!(0.5, t.alpha());
+        assert_eq!(1, t.advance(7.5));
+        assert_eq!(0.25, t.alpha());
+        assert_eq!(3, t.advance(30.0));
+        assert_eq!(0.25, t.alpha());
+
+        // The same total time gives the same steps, whatever the frame rate
+        let (mut a, mut b) = (Timestep::new(60.0, 100), Timestep::new(60.0, 100));
+        let steps_a: u32 = (0..101).map(|_| a.advance(10.0)).sum();
+        let steps_b: u32 = (0..5).map(|_| b.advance(202.0)).sum();
+        assert_eq!(60, steps_a);
+        assert_eq!(60, steps_b);
+    }
+
+    #[test]
+    fn catch_up() {
+        let mut t = Timestep::new(100.0, 4);
+        assert_eq!(4, t.advance(1005.0));
+        assert_eq!(0.5, t.alpha());
+        assert_eq!(0, t.advance(0.0));
+
+        // Invalid frame times are ignored
+        assert_eq!(0, t.advance(-20.0));
+        assert_eq!(0, t.advance(f64::NAN));
+        assert_eq!(0.5, t.alpha());
+
+        t.set_rate(0.0);
+        t.set_rate(f64::INFINITY);
+        t.set_rate(f64::NAN);
+        assert_eq!(10.0, t.step());
+        t.set_rate(50.0);
+        t.set_max_steps(1);
+        assert_eq!(1, t.advance(40.0));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::region::View;
+use crate::state::Vertex;
+
+/// Semantic is the meaning of a vertex attribute, which the host binds to a
+/// shader attribute location.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Semantic {
+    Position = 0,
+    Normal = 1,
+    Tangent = 2,
+    Texcoord = 3,
+    Color = 4,
+    Joints = 5,
+    Weights = 6,
+}
+
+/// Component is the type of the components of an attribute, which is the
+/// WebGL type to point the attribute with, the same as the glTF component
+/// type.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Component {
+    I8 = 0x1400,
+    U8 = 0x1401,
+    I16 = 0x1402,
+    U16 = 0x1403,
+    U32 = 0x1405,
+    F32 = 0x1406,
+}
+
+/// Attribute describes where an attribute of a vertex is in a buffer, the
+/// same way `vertexAttribPointer` takes it: the offset in bytes of the
+/// attribute of the first vertex, and the stride in bytes to the next one.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Attribute {
+    pub semantic: Semantic,
+    pub component: Component,
+    pub count: u32,
+    pub offset: u32,
+    pub stride: u32,
+    /// Whether integer components are mapped to the 0 to 1 range, or the -1
+    /// to 1 one for signed types, rather than converted as they are.
+    pub normalized: bool,
+}
+
+/// Format is the list of the attributes of a vertex.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Format(pub Vec<Attribute>);
+
+/// Packing is the way the attributes are laid out in a buffer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Packing {
+    /// Attributes of a vertex are next to each other, vertex after vertex.
+    Interleaved,
+    /// Attributes of all the vertices are in a block per attribute, such as
+    /// to update a single one often, or to skip the unused ones.
+    Deinterleaved,
+}
+
+/// VertexBuffer is the bytes of a number of vertices in a format, with the
+/// attributes laid out in a packing.
+pub struct VertexBuffer {
+    format: Format,
+    data: Vec<u8>,
+    len: usize,
+}
+
+impl Component {
+    /// Return the size of a component in bytes.
+    #[inline]
+    pub fn size(self) -> usize {
+        match self {
+            Component::I8 | Component::U8 => 1,
+            Component::I16 | Component::U16 => 2,
+            Component::U32 | Component::F32 => 4,
+        }
+    }
+
+    /// Write a value as a component, clamping it to the range of the type,
+    /// and scaling it to the range first if it is normalized.
+    #[inline]
+    fn write(self, value: f32, normalized: bool, out: &mut [u8]) {
+        fn int(value: f32, normalized: bool, min: f32, max: f32) -> f32 {
+            let value = if normalized {
+                (value * max).round()
+            } else {
+                value
+            };
+            value.clamp(min, max)
+        }
+        match self {
+            Component::I8 => out[0] = int(value, normalized, -127.0, 127.0) as i8 as u8,
+            Component::U8 => out[0] = int(value, normalized, 0.0, 255.0) as u8,
+            Component::I16 => out
+                .copy_from_slice(&(int(value, normalized, -32767.0, 32767.0) as i16).to_le_bytes()),
+            Component::U16 => {
+                out.copy_from_slice(&(int(value, normalized, 0.0, 65535.0) as u16).to_le_bytes())
+            }
+            Component::U32 => out.copy_from_slice(
+                &(int(value, normalized, 0.0, u32::MAX as f32) as u32).to_le_bytes(),
+            ),
+            Component::F32 => out.copy_from_slice(&value.to_le_bytes()),
+        }
+    }
+}
+
+impl Attribute {
+    /// Return an attribute to be laid out by a format.
+    #[inline]
+    pub const fn new(
+        semantic: Semantic,
+        component: Component,
+        count: u32,
+        normalized: bool,
+    ) -> Self {
+        Self {
+            semantic,
+            component,
+            count,
+            offset: 0,
+            stride: 0,
+            normalized,
+        }
+    }
+
+    /// Return the size of the attribute of a vertex in bytes.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.count as usize * self.component.size()
+    }
+}
+
+/// Return a size rounded up to 4 bytes, which WebGL requires offsets and
+/// strides of float attributes to be aligned to.
+#[inline]
+fn align(size: usize) -> usize {
+    (size + 3) & !3
+}
+
+impl Format {
+    /// Return the format of the engine `Vertex`.
+    pub fn position_color() -> Self {
+        Self(vec![
+            Attribute::new(Semantic::Position, Component::F32, 3, false),
+            Attribute::new(Semantic::Color, Component::U8, 4, true),
+        ])
+        .pack(Packing::Interleaved, 0)
+    }
+
+    /// Append an attribute.
+    #[inline]
+    pub fn with(
+        mut self,
+        semantic: Semantic,
+        component: Component,
+        count: u32,
+        normalized: bool,
+    ) -> Self {
+        self.0
+            .push(Attribute::new(semantic, component, count, normalized));
+        self
+    }
+
+    /// Return the attribute of a semantic.
+    #[inline]
+    pub fn attribute(&self, semantic: Semantic) -> Option<&Attribute> {
+        self.0.iter().find(|a| a.semantic == semantic)
+    }
+
+    /// Return the size of a vertex in bytes, with the attributes aligned.
+    #[inline]
+    pub fn vertex_size(&self) -> usize {
+        self.0.iter().map(|a| align(a.size())).sum()
+    }
+
+    /// Lay the attributes out for the given number of vertices, setting
+    /// their offsets and strides.
+    pub fn pack(mut self, packing: Packing, len: usize) -> Self {
+        let stride = self.vertex_size();
+        let mut offset = 0;
+        for a in &mut self.0 {
+            a.offset = offset as u32;
+            match packing {
+                Packing::Interleaved => {
+                    a.stride = stride as u32;
+                    offset += align(a.size());
+                }
+                Packing::Deinterleaved => {
+                    a.stride = a.size() as u32;
+                    offset += align(a.size() * len);
+                }
+            }
+        }
+        self
+    }
+}
+
+impl VertexBuffer {
+    /// Return a buffer without any vertices or attributes.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self {
+            format: Format(Vec::new()),
+            data: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Return a buffer of zeroed vertices in a format, with the attributes
+    /// laid out in a packing.
+    pub fn new(format: Format, len: usize, packing: Packing) -> Self {
+        let format = format.pack(packing, len);
+        let size = match packing {
+            Packing::Interleaved => format.vertex_size() * len,
+            Packing::Deinterleaved => format.0.iter().map(|a| align(a.size() * len)).sum(),
+        };
+        Self {
+            format,
+            data: vec![0; size],
+            len,
+        }
+    }
+
+    /// Return a buffer of engine vertices.
+    pub fn from_vertices(vertices: &[Vertex], packing: Packing) -> Self {
+        let mut buffer = Self::new(Format::position_color(), vertices.len(), packing);
+        let positions: Vec<f32> = vertices
+            .iter()
+            .flat_map(|v| [v.0 .0, v.0 .1, v.0 .2])
+            .collect();
+        let colors: Vec<f32> = vertices
+            .iter()
+            .flat_map(|v| [v.1 .0, v.1 .1, v.1 .2, v.1 .3])
+            .map(|c| c as f32 / 255.0)
+            .collect();
+        buffer.write(Semantic::Position, 0, &positions);
+        buffer.write(Semantic::Color, 0, &colors);
+        buffer
+    }
+
+    /// Return the format, with the offsets and strides of the attributes in
+    /// this buffer.
+    #[inline]
+    pub fn format(&self) -> &Format {
+        &self.format
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Return the number of vertices.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Write values of an attribute, with all the components of a vertex
+    /// after each other, starting at a vertex. Values past the buffer are
+    /// ignored. Return false if the format has no such attribute.
+    pub fn write(&mut self, semantic: Semantic, first: usize, values: &[f32]) -> bool {
+        let Some(&a) = self.format.attribute(semantic) else {
+            return false;
+        };
+        let size = a.component.size();
+        let vertices = values.chunks_exact(a.count as usize);
+        for (i, vertex) in (first..self.len).zip(vertices) {
+            let start = a.offset as usize + i * a.stride as usize;
+            for (j, &value) in vertex.iter().enumerate() {
+                let at = start + j * size;
+                a.component
+                    .write(value, a.normalized, &mut self.data[at..at + size]);
+            }
+        }
+        true
+    }
+
+    /// Return the view of the buffer for the host.
+    #[inline]
+    pub fn view(&self) -> View {
+        View::of(&self.data, self.data.capacity())
+    }
+}
+
+impl Default for VertexBuffer {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Color, Vec3};
+    use crate::state::Vertex;
+    use crate::vertex::{Component, Format, Packing, Semantic, VertexBuffer};
+    use std::mem;
+
+    #[test]
+    fn format() {
+        let format = Format::position_color();
+        assert_eq!(mem::size_of::<Vertex>(), format.vertex_size());
+        let color = format.attribute(Semantic::Color).unwrap();
+        assert_eq!(12, color.offset);
+        assert_eq!(16, color.stride);
+        assert!(format.attribute(Semantic::Normal).is_none());
+
+        // Attributes are aligned to 4 bytes
+        let format = Format::default()
+            .with(Semantic::Texcoord, Component::U16, 3, true)
+            .with(Semantic::Joints, Component::U8, 1, false)
+            .pack(Packing::Interleaved, 10);
+        assert_eq!(12, format.vertex_size());
+        assert_eq!(8, format.0[1].offset);
+
+        let format = format.pack(Packing::Deinterleaved, 10);
+        assert_eq!((0, 6), (format.0[0].offset, format.0[0].stride));
+        assert_eq!((60, 1), (format.0[1].offset, format.0[1].stride));
+    }
+
+    #[test]
+    fn interleaved() {
+        let vertices = [
+            Vertex(Vec3(1.0, 2.0, 3.0), Color(255, 0, 128, 255)),
+            Vertex(Vec3(4.0, 5.0, 6.0), Color(1, 2, 3, 4)),
+        ];
+        let buffer = VertexBuffer::from_vertices(&vertices, Packing::Interleaved);
+        assert_eq!(2, buffer.len());
+
+        // The same bytes as the engine vertices
+        let bytes = unsafe {
+            std::slice::from_raw_parts(vertices.as_ptr() as *const u8, mem::size_of_val(&vertices))
+        };
+        assert_eq!(bytes, buffer.data());
+    }
+
+    #[test]
+    fn deinterleaved() {
+        let format = Format::default()
+            .with(Semantic::Position, Component::F32, 2, false)
+            .with(Semantic::Weights, Component::I8, 2, true);
+        let mut buffer = VertexBuffer::new(format, 3, Packing::Deinterleaved);
+        assert_eq!(24 + 8, buffer.data().len());
+        assert!(buffer.write(Semantic::Position, 1, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        assert!(buffer.write(Semantic::Weights, 0, &[1.0, -1.0, 0.5, -2.0]));
+        assert!(!buffer.write(Semantic::Normal, 0, &[0.0]));
+
+        let floats: Vec<f32> = buffer.data()[..24]
+            .chunks(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        assert_eq!(vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0], floats);
+        assert_eq!(
+            [127, -127, 64, -127, 0, 0],
+            buffer.data()[24..30]
+                .iter()
+                .map(|&b| b as i8)
+                .collect::<Vec<_>>()[..]
+        );
+    }
+}